@@ -0,0 +1,69 @@
+//! Benchmarks for writing [`Style`]s, for every combination of foreground and background color kinds
+//!
+//! `NeverSome` is `NoColor`, `AlwaysSome` is a color type and `MaybeSome` is an `Option` of a color type
+
+use core::fmt::Write;
+
+use colorz::{ansi, rgb::RgbColor, Color, Style};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const RGB: RgbColor = RgbColor {
+    red: 255,
+    green: 128,
+    blue: 0,
+};
+
+macro_rules! bench_styles {
+    ($c:ident, $($name:literal => $style:expr,)*) => {$({
+        let mut group = $c.benchmark_group($name);
+        let mut buffer = String::with_capacity(128);
+
+        let styles = [
+            ("no effects", $style),
+            ("one effect", $style.bold()),
+            ("many effects", $style.bold().italics().underline()),
+        ];
+
+        for (effects, style) in styles {
+            group.bench_with_input(BenchmarkId::new("apply", effects), &style, |b, style| {
+                b.iter(|| {
+                    buffer.clear();
+                    write!(buffer, "{}", black_box(style).apply()).unwrap();
+                    black_box(&buffer);
+                })
+            });
+
+            group.bench_with_input(BenchmarkId::new("clear", effects), &style, |b, style| {
+                b.iter(|| {
+                    buffer.clear();
+                    write!(buffer, "{}", black_box(style).clear()).unwrap();
+                    black_box(&buffer);
+                })
+            });
+        }
+
+        group.finish();
+    })*};
+}
+
+fn styles(c: &mut Criterion) {
+    bench_styles! {
+        c,
+        "never/never" => Style::new(),
+        "always/never" => Style::new().fg(ansi::Red),
+        "never/always" => Style::new().bg(ansi::Blue),
+        "always/always" => Style::new().fg(ansi::Red).bg(RGB),
+        "maybe/never" => Style::new().fg(Some(Color::Rgb(RGB))),
+        "never/maybe" => Style::new().bg(Some(Color::Ansi(ansi::AnsiColor::Blue))),
+        "maybe/always" => Style::new().fg(Some(Color::Rgb(RGB))).bg(ansi::Blue),
+        "always/maybe" => Style::new().fg(ansi::Red).bg(Some(Color::Rgb(RGB))),
+        "runtime" => Style::new()
+            .fg(Color::Rgb(RGB))
+            .bg(ansi::Blue)
+            .underline_color(ansi::Red)
+            .into_runtime_style(),
+    }
+}
+
+criterion_group!(benches, styles);
+criterion_main!(benches);
@@ -0,0 +1,487 @@
+//! Procedural macros for `colorz`
+//!
+//! These macros are re-exported by `colorz` with the `macros` feature, and should be used from there.
+//! The `colorz` wrappers pass the path to `colorz` as the first argument, so the generated code
+//! works even if `colorz` is renamed.
+
+#![forbid(unsafe_code, missing_docs)]
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// The placeholder for the path to `colorz` in generated code
+const CRATE: &str = "__colorz_crate";
+
+/// The effects, as `(name, variant)`, in the same order as `colorz::Effect`
+const EFFECTS: [(&str, &str); 13] = [
+    ("bold", "Bold"),
+    ("dimmed", "Dimmed"),
+    ("italics", "Italic"),
+    ("underline", "Underline"),
+    ("double_underline", "DoubleUnderline"),
+    ("blink", "Blink"),
+    ("blink_fast", "BlinkFast"),
+    ("reverse", "Reversed"),
+    ("hide", "Hidden"),
+    ("strikethrough", "Strikethrough"),
+    ("overline", "Overline"),
+    ("superscript", "SuperScript"),
+    ("subscript", "SubScript"),
+];
+
+/// The ANSI colors, as `(name, variant)`
+const ANSI_COLORS: [(&str, &str); 18] = [
+    ("black", "Black"),
+    ("red", "Red"),
+    ("green", "Green"),
+    ("yellow", "Yellow"),
+    ("blue", "Blue"),
+    ("magenta", "Magenta"),
+    ("purple", "Magenta"),
+    ("cyan", "Cyan"),
+    ("white", "White"),
+    ("bright black", "BrightBlack"),
+    ("bright red", "BrightRed"),
+    ("bright green", "BrightGreen"),
+    ("bright yellow", "BrightYellow"),
+    ("bright blue", "BrightBlue"),
+    ("bright magenta", "BrightMagenta"),
+    ("bright cyan", "BrightCyan"),
+    ("bright white", "BrightWhite"),
+    ("default", "Default"),
+];
+
+/// A style, with the colors as code
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Style {
+    foreground: Option<String>,
+    background: Option<String>,
+    underline_color: Option<String>,
+    /// A bit for each item in `EFFECTS`
+    effects: u16,
+}
+
+impl Style {
+    /// Apply `other` on top of this style
+    fn merge(&self, other: &Self) -> Self {
+        Self {
+            foreground: other.foreground.clone().or_else(|| self.foreground.clone()),
+            background: other.background.clone().or_else(|| self.background.clone()),
+            underline_color: other
+                .underline_color
+                .clone()
+                .or_else(|| self.underline_color.clone()),
+            effects: self.effects | other.effects,
+        }
+    }
+
+    /// The code for a runtime `colorz::Style`, which can be evaluated in a const context
+    fn to_code(&self) -> String {
+        let color = |color: &Option<String>| match color {
+            Some(color) => format!("::core::option::Option::Some({color})"),
+            None => format!("::core::option::Option::None::<{CRATE}::Color>"),
+        };
+
+        let mut code = format!(
+            "{CRATE}::Style::new().fg({}).bg({}).underline_color({})",
+            color(&self.foreground),
+            color(&self.background),
+            color(&self.underline_color),
+        );
+
+        for (i, (_, variant)) in EFFECTS.iter().enumerate() {
+            if self.effects & (1 << i) != 0 {
+                code += &format!(".with({CRATE}::Effect::{variant})");
+            }
+        }
+
+        code
+    }
+}
+
+fn parse_hex(digits: &str) -> Option<u8> {
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    u8::from_str_radix(digits, 16).ok()
+}
+
+/// Parse a color like `colorz::Color`'s `FromStr` impl, into code for a `colorz::Color`
+fn parse_color(name: &str) -> Result<String, String> {
+    if let Some(hex) = name.strip_prefix('#') {
+        let invalid = || format!("invalid hex color `{name}`");
+
+        return match hex.len() {
+            1 | 2 => {
+                let code = parse_hex(hex).ok_or_else(invalid)?;
+                Ok(format!(
+                    "{CRATE}::Color::Xterm({CRATE}::xterm::XtermColor::from_code({code}))"
+                ))
+            }
+            6 => {
+                let red = parse_hex(&hex[0..2]).ok_or_else(invalid)?;
+                let green = parse_hex(&hex[2..4]).ok_or_else(invalid)?;
+                let blue = parse_hex(&hex[4..6]).ok_or_else(invalid)?;
+                Ok(format!(
+                    "{CRATE}::Color::Rgb({CRATE}::rgb::RgbColor {{ red: {red}, green: {green}, blue: {blue} }})"
+                ))
+            }
+            _ => Err(invalid()),
+        };
+    }
+
+    if !name.is_empty() && name.len() <= 3 && name.bytes().all(|b| b.is_ascii_digit()) {
+        let code: u8 = name.parse().map_err(|_| {
+            format!("xterm color codes must be in the range 0..=255, found `{name}`")
+        })?;
+        return Ok(format!(
+            "{CRATE}::Color::Xterm({CRATE}::xterm::XtermColor::from_code({code}))"
+        ));
+    }
+
+    if let Some((_, variant)) = ANSI_COLORS.iter().find(|(ansi, _)| *ansi == name) {
+        return Ok(format!(
+            "{CRATE}::Color::Ansi({CRATE}::ansi::AnsiColor::{variant})"
+        ));
+    }
+
+    // CSS color names are the names of the `CssColor` variants, unknown names are reported by rustc
+    if name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.bytes().all(|b| b.is_ascii_alphabetic())
+    {
+        return Ok(format!(
+            "{CRATE}::Color::Css({CRATE}::css::CssColor::{name})"
+        ));
+    }
+
+    Err(format!("unknown color `{name}`"))
+}
+
+/// Parse a style like `colorz::Style`'s `FromStr` impl
+fn parse_style(s: &str) -> Result<Style, String> {
+    let mut style = Style::default();
+    let mut words = s.split_ascii_whitespace();
+
+    while let Some(word) = words.next() {
+        let (slot, name) = match word.split_once(':') {
+            Some(("fg", name)) => (&mut style.foreground, name),
+            Some(("bg", name)) => (&mut style.background, name),
+            Some(("ul", name)) => (&mut style.underline_color, name),
+            Some(_) => return Err(format!("unknown style item `{word}`")),
+            None => match EFFECTS.iter().position(|(effect, _)| *effect == word) {
+                Some(i) => {
+                    style.effects |= 1 << i;
+                    continue;
+                }
+                None => (&mut style.foreground, word),
+            },
+        };
+
+        let color = if name == "bright" {
+            // `bright blue` is split across two words
+            let bright = format!("bright {}", words.next().unwrap_or_default());
+            parse_color(&bright)?
+        } else {
+            parse_color(name)?
+        };
+
+        *slot = Some(color);
+    }
+
+    Ok(style)
+}
+
+/// The value of a string literal
+fn string_value(literal: &Literal) -> Option<String> {
+    let repr = literal.to_string();
+
+    if let Some(raw) = repr.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let body = raw.get(hashes..raw.len().checked_sub(hashes)?)?;
+        return Some(body.strip_prefix('"')?.strip_suffix('"')?.into());
+    }
+
+    let body = repr.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::new();
+    let mut chars = body.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+
+        match chars.next()? {
+            'n' => value.push('\n'),
+            'r' => value.push('\r'),
+            't' => value.push('\t'),
+            '0' => value.push('\0'),
+            '\\' => value.push('\\'),
+            '\'' => value.push('\''),
+            '"' => value.push('"'),
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                value.push(char::from(parse_hex(&hex)?));
+            }
+            'u' => {
+                let hex: String = chars.by_ref().skip(1).take_while(|&c| c != '}').collect();
+                value.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+            }
+            '\n' => {
+                // a line continuation skips the newline and any leading whitespace
+                let rest = chars.as_str().trim_start();
+                chars = rest.chars();
+            }
+            _ => return None,
+        }
+    }
+
+    Some(value)
+}
+
+/// A format string where the markup tags are replaced with `{__colorz_N}` arguments
+struct Markup {
+    format: String,
+    /// The `(from, to)` styles of each argument
+    transitions: Vec<(Style, Style)>,
+}
+
+fn parse_markup(s: &str) -> Result<Markup, String> {
+    let mut markup = Markup {
+        format: String::new(),
+        transitions: Vec::new(),
+    };
+    let mut stack: Vec<Style> = Vec::new();
+    let mut current = Style::default();
+    let mut chars = s.chars().peekable();
+
+    let transition = |markup: &mut Markup, from: &Style, to: &Style| {
+        if from != to {
+            markup.format += &format!("{{__colorz_{}}}", markup.transitions.len());
+            markup.transitions.push((from.clone(), to.clone()));
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            // copy format arguments as is, so `<` in `{:<4}` isn't treated as a tag
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                markup.format += "{{";
+            }
+            '{' => {
+                markup.format.push('{');
+                for c in chars.by_ref() {
+                    markup.format.push(c);
+                    if c == '}' {
+                        break;
+                    }
+                }
+            }
+            '<' if chars.peek() == Some(&'<') => {
+                chars.next();
+                markup.format.push('<');
+            }
+            '<' => {
+                let mut tag = String::new();
+                loop {
+                    match chars.next() {
+                        Some('>') => break,
+                        Some(c) => tag.push(c),
+                        None => {
+                            return Err(format!(
+                                "unclosed tag `<{tag}`, use `<<` for a literal `<`"
+                            ))
+                        }
+                    }
+                }
+
+                let next = if tag.trim() == "/" {
+                    if stack.pop().is_none() {
+                        return Err("`</>` doesn't close any tag".into());
+                    }
+
+                    stack
+                        .iter()
+                        .fold(Style::default(), |style, tag| style.merge(tag))
+                } else {
+                    let style = parse_style(&tag)?;
+                    let next = current.merge(&style);
+                    stack.push(style);
+                    next
+                };
+
+                transition(&mut markup, &current, &next);
+                current = next;
+            }
+            c => markup.format.push(c),
+        }
+    }
+
+    // close any tags which are still open
+    transition(&mut markup, &current, &Style::default());
+
+    Ok(markup)
+}
+
+/// Turn code into tokens, replacing the `CRATE` placeholder with the path to `colorz`
+fn tokens(code: &str, krate: &TokenStream, span: Span) -> TokenStream {
+    fn replace(tokens: TokenStream, krate: &TokenStream, span: Span) -> TokenStream {
+        tokens
+            .into_iter()
+            .flat_map(|token| match token {
+                TokenTree::Ident(ident) if ident.to_string() == CRATE => krate.clone(),
+                TokenTree::Group(group) => {
+                    let mut new =
+                        Group::new(group.delimiter(), replace(group.stream(), krate, span));
+                    new.set_span(span);
+                    TokenTree::Group(new).into()
+                }
+                mut token => {
+                    token.set_span(span);
+                    token.into()
+                }
+            })
+            .collect()
+    }
+
+    replace(code.parse().expect("generated invalid code"), krate, span)
+}
+
+fn error(message: &str, span: Span) -> TokenStream {
+    let mut message = Literal::string(message);
+    message.set_span(span);
+
+    let mut tokens = tokens("::core::compile_error!", &TokenStream::new(), span);
+    tokens.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        TokenTree::Literal(message).into(),
+    ))]);
+    tokens
+}
+
+/// Take a leading `[...]` group
+fn bracketed(input: &mut impl Iterator<Item = TokenTree>) -> TokenStream {
+    match input.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => group.stream(),
+        _ => panic!("colorz-macros should only be used through colorz"),
+    }
+}
+
+/// Take a leading string literal
+fn string_literal(
+    input: &mut impl Iterator<Item = TokenTree>,
+) -> Result<(String, Span), TokenStream> {
+    let literal = match input.next() {
+        Some(TokenTree::Literal(literal)) => literal,
+        // literals passed through `macro_rules` fragments are wrapped in an invisible group
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::None => {
+            return string_literal(&mut group.stream().into_iter());
+        }
+        Some(token) => return Err(error("expected a string literal", token.span())),
+        None => return Err(error("expected a string literal", Span::call_site())),
+    };
+
+    match string_value(&literal) {
+        Some(value) => Ok((value, literal.span())),
+        None => Err(error("expected a string literal", literal.span())),
+    }
+}
+
+/// `style!([$crate] "bold red")`, see `colorz::style!`
+#[proc_macro]
+pub fn style(input: TokenStream) -> TokenStream {
+    let mut input = input.into_iter();
+    let krate = bracketed(&mut input);
+
+    let (value, span) = match string_literal(&mut input) {
+        Ok(literal) => literal,
+        Err(error) => return error,
+    };
+
+    if let Some(token) = input.next() {
+        return error("expected a single string literal", token.span());
+    }
+
+    match parse_style(&value) {
+        Ok(style) => tokens(&style.to_code(), &krate, span),
+        Err(message) => error(&message, span),
+    }
+}
+
+/// `format_markup!([$crate] [path::to::format] [stream] "<red>{}</>", args...)`, see `colorz::cformat!`
+#[proc_macro]
+pub fn format_markup(input: TokenStream) -> TokenStream {
+    let mut input = input.into_iter();
+    let krate = bracketed(&mut input);
+    let target = bracketed(&mut input);
+    let stream = bracketed(&mut input);
+
+    let (value, span) = match string_literal(&mut input) {
+        Ok(literal) => literal,
+        Err(error) => return error,
+    };
+
+    let markup = match parse_markup(&value) {
+        Ok(markup) => markup,
+        Err(message) => return error(&message, span),
+    };
+
+    let mut format = Literal::string(&markup.format);
+    // keep the span of the original literal, so arguments can be captured from the caller
+    format.set_span(span);
+
+    let mut args = TokenStream::from(TokenTree::Literal(format));
+    let rest: Vec<TokenTree> = input.collect();
+    let trailing_comma = matches!(rest.last(), Some(TokenTree::Punct(p)) if p.as_char() == ',');
+    let has_rest = !rest.is_empty();
+    args.extend(rest);
+
+    for (i, (from, to)) in markup.transitions.iter().enumerate() {
+        if i != 0 || !has_rest || !trailing_comma {
+            args.extend([TokenTree::Punct(Punct::new(',', Spacing::Alone))]);
+        }
+
+        args.extend([
+            TokenTree::Ident(Ident::new(&format!("__colorz_{i}"), span)),
+            TokenTree::Punct(Punct::new('=', Spacing::Alone)),
+        ]);
+
+        let mut transition = tokens(
+            &format!(
+                "{{ const TRANSITION: {CRATE}::__private::Transition = {CRATE}::__private::Transition::new({}, {}, __colorz_stream); TRANSITION }}",
+                from.to_code(),
+                to.to_code(),
+            ),
+            &krate,
+            span,
+        );
+        transition = replace_stream(transition, &stream);
+        args.extend(transition);
+    }
+
+    let mut output = target;
+    output.extend([
+        TokenTree::Punct(Punct::new('!', Spacing::Alone)),
+        TokenTree::Group(Group::new(Delimiter::Parenthesis, args)),
+    ]);
+    output
+}
+
+/// Replace the `__colorz_stream` placeholder with the stream
+fn replace_stream(tokens: TokenStream, stream: &TokenStream) -> TokenStream {
+    tokens
+        .into_iter()
+        .flat_map(|token| match token {
+            TokenTree::Ident(ident) if ident.to_string() == "__colorz_stream" => {
+                TokenTree::Group(Group::new(Delimiter::Parenthesis, stream.clone())).into()
+            }
+            TokenTree::Group(group) => {
+                let mut new = Group::new(group.delimiter(), replace_stream(group.stream(), stream));
+                new.set_span(group.span());
+                TokenTree::Group(new).into()
+            }
+            token => TokenStream::from(token),
+        })
+        .collect()
+}
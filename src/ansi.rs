@@ -89,9 +89,12 @@ macro_rules! MkAnsiColor {
             }
 
             #[inline]
-            /// The ANSI background color args
+            /// The ANSI underline color args, the default color resets the underline color
             pub const fn underline_args(self) -> &'static str {
-                self.to_xterm().underline_args()
+                match self {
+                    Self::Default => "59",
+                    _ => self.to_xterm().underline_args(),
+                }
             }
 
             #[inline]
@@ -113,7 +116,10 @@ macro_rules! MkAnsiColor {
             #[inline]
             /// The ANSI underline color escape sequence
             pub const fn underline_escape(self) -> &'static str {
-                self.to_xterm().underline_escape()
+                match self {
+                    Self::Default => "\x1b[59m",
+                    _ => self.to_xterm().underline_escape(),
+                }
             }
 
             #[inline]
@@ -145,7 +151,7 @@ macro_rules! MkAnsiColor {
 
             #[inline]
             fn underline_args(self) -> &'static str {
-                self.to_xterm().underline_args()
+                self.underline_args()
             }
 
             #[inline]
@@ -170,7 +176,7 @@ macro_rules! MkAnsiColor {
 
             #[inline]
             fn underline_escape(self) -> &'static str {
-                self.to_xterm().underline_escape()
+                self.underline_escape()
             }
         }
 
@@ -217,7 +223,7 @@ macro_rules! MkAnsiColor {
 
                 #[inline]
                 fn underline_args(self) -> &'static str {
-                    <xterm_from_code!($xterm)>::UNDERLINE_ARGS
+                    Self::DYNAMIC.underline_args()
                 }
 
                 #[inline]
@@ -232,7 +238,7 @@ macro_rules! MkAnsiColor {
 
                 #[inline]
                 fn underline_escape(self) -> &'static str {
-                    <xterm_from_code!($xterm)>::UNDERLINE_ESCAPE
+                    Self::DYNAMIC.underline_escape()
                 }
             }
         )*
@@ -261,3 +267,36 @@ MkAnsiColor! {
 
     16 Default   39 49
 }
+
+impl AnsiColor {
+    /// The name of this color, as accepted by [`Color`](crate::Color)'s [`FromStr`](core::str::FromStr) impl
+    ///
+    /// ```
+    /// use colorz::ansi::AnsiColor;
+    ///
+    /// assert_eq!(AnsiColor::Red.name(), "red");
+    /// assert_eq!(AnsiColor::BrightBlue.name(), "bright blue");
+    /// ```
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Black => "black",
+            Self::Red => "red",
+            Self::Green => "green",
+            Self::Yellow => "yellow",
+            Self::Blue => "blue",
+            Self::Magenta => "magenta",
+            Self::Cyan => "cyan",
+            Self::White => "white",
+            Self::BrightBlack => "bright black",
+            Self::BrightRed => "bright red",
+            Self::BrightGreen => "bright green",
+            Self::BrightYellow => "bright yellow",
+            Self::BrightBlue => "bright blue",
+            Self::BrightMagenta => "bright magenta",
+            Self::BrightCyan => "bright cyan",
+            Self::BrightWhite => "bright white",
+            Self::Default => "default",
+        }
+    }
+}
@@ -0,0 +1,520 @@
+//! Helpers for wiring up a `--color=auto|always|never` command line flag
+//!
+//! [`ColorChoice`] can be parsed from a string, so it can be used directly with any
+//! argument parser which uses [`FromStr`](core::str::FromStr), like `argh`.
+//!
+//! ```rust
+//! use colorz::cli::ColorChoice;
+//!
+//! let choice: ColorChoice = "auto".parse().unwrap();
+//! assert_eq!(choice, ColorChoice::Auto);
+//!
+//! // sets the global coloring mode, see `ColorChoice::apply` for details
+//! choice.apply();
+//! ```
+//!
+//! With the `clap` feature, [`ColorArgs`] can be flattened into your arguments, and
+//! [`HelpStyles`] can be used to style the generated help text with colorz styles.
+
+use crate::mode::{self, Mode};
+#[cfg(feature = "clap")]
+use crate::{ansi::AnsiColor, Color, Effect, Style};
+
+/// When to color the output, as specified on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColorChoice {
+    /// Color the output if the stream supports it (may also be spelled `detect`)
+    #[default]
+    Auto,
+    /// Always color the output
+    Always,
+    /// Never color the output
+    Never,
+}
+
+/// An error if parsing a color choice from a string fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorChoiceFromStrError;
+
+#[cfg(feature = "std")]
+impl std::error::Error for ColorChoiceFromStrError {}
+
+impl core::fmt::Display for ColorChoiceFromStrError {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(r#"Invalid color choice: valid options include "auto", "always", "never""#)
+    }
+}
+
+impl core::str::FromStr for ColorChoice {
+    type Err = ColorChoiceFromStrError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_ascii_bytes(s.as_bytes())
+    }
+}
+
+impl core::fmt::Display for ColorChoice {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<ColorChoice> for Mode {
+    #[inline]
+    fn from(choice: ColorChoice) -> Self {
+        choice.mode()
+    }
+}
+
+impl From<Mode> for ColorChoice {
+    #[inline]
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Detect => Self::Auto,
+            Mode::Always => Self::Always,
+            Mode::Never => Self::Never,
+        }
+    }
+}
+
+impl ColorChoice {
+    /// Parse the color choice from some ascii encoded bytes
+    ///
+    /// This accepts everything that [`Mode::from_ascii_bytes`] does, as well as `auto`
+    #[inline]
+    pub const fn from_ascii_bytes(s: &[u8]) -> Result<Self, ColorChoiceFromStrError> {
+        if let [a, b, c, d] = *s {
+            return match [
+                a.to_ascii_lowercase(),
+                b.to_ascii_lowercase(),
+                c.to_ascii_lowercase(),
+                d.to_ascii_lowercase(),
+            ] {
+                [b'a', b'u', b't', b'o'] => Ok(Self::Auto),
+                _ => Err(ColorChoiceFromStrError),
+            };
+        }
+
+        match Mode::from_ascii_bytes(s) {
+            Ok(Mode::Detect) => Ok(Self::Auto),
+            Ok(Mode::Always) => Ok(Self::Always),
+            Ok(Mode::Never) => Ok(Self::Never),
+            Err(_) => Err(ColorChoiceFromStrError),
+        }
+    }
+
+    /// The name of this color choice, as accepted on the command line
+    #[inline]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Always => "always",
+            Self::Never => "never",
+        }
+    }
+
+    /// The corresponding coloring mode
+    #[inline]
+    pub const fn mode(self) -> Mode {
+        match self {
+            Self::Auto => Mode::Detect,
+            Self::Always => Mode::Always,
+            Self::Never => Mode::Never,
+        }
+    }
+
+    /// Set the global coloring mode from this choice
+    ///
+    /// An explicit `always` or `never` on the command line takes precedence over the environment.
+    /// For `auto`, the coloring mode is reset to [`Mode::Detect`], and then if the `std` feature is enabled,
+    /// the environment is consulted via [`mode::set_coloring_mode_from_env`].
+    ///
+    /// ```rust
+    /// use colorz::{cli::ColorChoice, mode};
+    ///
+    /// ColorChoice::Never.apply();
+    /// assert_eq!(mode::get_coloring_mode(), mode::Mode::Never);
+    /// ```
+    #[inline]
+    pub fn apply(self) {
+        mode::set_coloring_mode(self.mode());
+
+        #[cfg(feature = "std")]
+        if let Self::Auto = self {
+            mode::set_coloring_mode_from_env();
+        }
+    }
+}
+
+/// A `--color <WHEN>` argument which can be flattened into a `clap` parser
+///
+/// ```rust
+/// use clap::{Command, Args, FromArgMatches};
+/// use colorz::cli::{ColorArgs, ColorChoice};
+///
+/// let command = ColorArgs::augment_args(Command::new("app"));
+/// let matches = command.get_matches_from(["app", "--color", "never"]);
+/// let args = ColorArgs::from_arg_matches(&matches).unwrap();
+///
+/// assert_eq!(args.color, ColorChoice::Never);
+/// args.apply();
+/// ```
+#[cfg(feature = "clap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "clap")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ColorArgs {
+    /// When to color the output
+    pub color: ColorChoice,
+}
+
+#[cfg(feature = "clap")]
+impl ColorArgs {
+    const ID: &'static str = "color";
+
+    /// Set the global coloring mode, see [`ColorChoice::apply`] for details
+    #[inline]
+    pub fn apply(&self) {
+        self.color.apply()
+    }
+}
+
+#[cfg(feature = "clap")]
+impl clap::ValueEnum for ColorChoice {
+    #[inline]
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Auto, Self::Always, Self::Never]
+    }
+
+    #[inline]
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        let value = clap::builder::PossibleValue::new(self.as_str());
+
+        Some(match self {
+            Self::Auto => value
+                .alias("detect")
+                .help("Color the output if the terminal supports it"),
+            Self::Always => value.help("Always color the output"),
+            Self::Never => value.help("Never color the output"),
+        })
+    }
+}
+
+#[cfg(feature = "clap")]
+impl From<ColorChoice> for clap::ColorChoice {
+    #[inline]
+    fn from(choice: ColorChoice) -> Self {
+        match choice {
+            ColorChoice::Auto => Self::Auto,
+            ColorChoice::Always => Self::Always,
+            ColorChoice::Never => Self::Never,
+        }
+    }
+}
+
+#[cfg(feature = "clap")]
+impl clap::FromArgMatches for ColorArgs {
+    #[inline]
+    fn from_arg_matches(matches: &clap::ArgMatches) -> Result<Self, clap::Error> {
+        let mut args = Self::default();
+        args.update_from_arg_matches(matches)?;
+        Ok(args)
+    }
+
+    #[inline]
+    fn update_from_arg_matches(&mut self, matches: &clap::ArgMatches) -> Result<(), clap::Error> {
+        let color = matches
+            .try_get_one::<ColorChoice>(Self::ID)
+            .map_err(|err| clap::Error::raw(clap::error::ErrorKind::ValueValidation, err))?;
+
+        if let Some(&color) = color {
+            self.color = color;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "clap")]
+impl clap::Args for ColorArgs {
+    #[inline]
+    fn augment_args(cmd: clap::Command) -> clap::Command {
+        cmd.arg(
+            clap::Arg::new(Self::ID)
+                .long("color")
+                .value_name("WHEN")
+                .help("When to color the output")
+                .value_parser(clap::builder::EnumValueParser::<ColorChoice>::new())
+                .default_value("auto")
+                .global(true),
+        )
+    }
+
+    #[inline]
+    fn augment_args_for_update(cmd: clap::Command) -> clap::Command {
+        Self::augment_args(cmd)
+    }
+}
+
+/// The styles used for `clap`'s help and error messages
+///
+/// This can be converted to `clap`'s [`Styles`](clap::builder::Styles) in a const context
+///
+/// ```rust
+/// use colorz::{cli::HelpStyles, Style, xterm};
+///
+/// const STYLES: clap::builder::Styles = HelpStyles {
+///     header: Style::new().fg(xterm::Orange).bold().const_into_runtime_style(),
+///     ..HelpStyles::DEFAULT
+/// }
+/// .into_clap_styles();
+///
+/// let command = clap::Command::new("app").styles(STYLES);
+/// ```
+#[cfg(feature = "clap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "clap")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HelpStyles {
+    /// The style of section headers, like `Usage:` and `Options:`
+    pub header: Style,
+    /// The style of `error:` in error messages
+    pub error: Style,
+    /// The style of the usage line
+    pub usage: Style,
+    /// The style of literal text, like flag names
+    pub literal: Style,
+    /// The style of value placeholders, like `<WHEN>`
+    pub placeholder: Style,
+    /// The style of suggested values in error messages
+    pub valid: Style,
+    /// The style of invalid values in error messages
+    pub invalid: Style,
+    /// The style of context in help messages, like `[default: auto]`
+    pub context: Style,
+    /// The style of values in the context, like the `auto` in `[default: auto]`
+    pub context_value: Style,
+}
+
+#[cfg(feature = "clap")]
+impl HelpStyles {
+    /// Unstyled help and error messages
+    pub const PLAIN: Self = Self {
+        header: Style::new().const_into_runtime_style(),
+        error: Style::new().const_into_runtime_style(),
+        usage: Style::new().const_into_runtime_style(),
+        literal: Style::new().const_into_runtime_style(),
+        placeholder: Style::new().const_into_runtime_style(),
+        valid: Style::new().const_into_runtime_style(),
+        invalid: Style::new().const_into_runtime_style(),
+        context: Style::new().const_into_runtime_style(),
+        context_value: Style::new().const_into_runtime_style(),
+    };
+
+    /// The same styles as `cargo`'s help and error messages
+    pub const DEFAULT: Self = Self {
+        header: Style::new()
+            .fg(crate::ansi::BrightGreen)
+            .bold()
+            .const_into_runtime_style(),
+        error: Style::new()
+            .fg(crate::ansi::BrightRed)
+            .bold()
+            .const_into_runtime_style(),
+        usage: Style::new()
+            .fg(crate::ansi::BrightGreen)
+            .bold()
+            .const_into_runtime_style(),
+        literal: Style::new()
+            .fg(crate::ansi::BrightCyan)
+            .bold()
+            .const_into_runtime_style(),
+        placeholder: Style::new()
+            .fg(crate::ansi::Cyan)
+            .const_into_runtime_style(),
+        valid: Style::new()
+            .fg(crate::ansi::BrightCyan)
+            .bold()
+            .const_into_runtime_style(),
+        invalid: Style::new()
+            .fg(crate::ansi::Yellow)
+            .bold()
+            .const_into_runtime_style(),
+        context: Style::new()
+            .fg(crate::ansi::Cyan)
+            .const_into_runtime_style(),
+        context_value: Style::new()
+            .fg(crate::ansi::Cyan)
+            .bold()
+            .const_into_runtime_style(),
+    };
+
+    /// Convert to `clap`'s [`Styles`](clap::builder::Styles)
+    #[inline]
+    pub const fn into_clap_styles(self) -> clap::builder::Styles {
+        clap::builder::Styles::plain()
+            .header(clap_style(self.header))
+            .error(clap_style(self.error))
+            .usage(clap_style(self.usage))
+            .literal(clap_style(self.literal))
+            .placeholder(clap_style(self.placeholder))
+            .valid(clap_style(self.valid))
+            .invalid(clap_style(self.invalid))
+            .context(clap_style(self.context))
+            .context_value(clap_style(self.context_value))
+    }
+}
+
+#[cfg(feature = "clap")]
+impl Default for HelpStyles {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(feature = "clap")]
+impl From<HelpStyles> for clap::builder::Styles {
+    #[inline]
+    fn from(styles: HelpStyles) -> Self {
+        styles.into_clap_styles()
+    }
+}
+
+/// Convert a colorz [`Style`] into a `clap` (`anstyle`) style
+///
+/// `anstyle` doesn't support every effect, so [`Effect::BlinkFast`] is converted to a normal blink,
+/// and [`Effect::Overline`], [`Effect::SuperScript`] and [`Effect::SubScript`] are dropped.
+///
+/// ```rust
+/// use colorz::{cli::clap_style, Style, ansi};
+///
+/// let style = clap_style(Style::new().fg(ansi::Red).bold().into_runtime_style());
+///
+/// assert_eq!(style, clap::builder::styling::AnsiColor::Red.on_default().bold());
+/// ```
+#[cfg(feature = "clap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "clap")))]
+#[inline]
+pub const fn clap_style(style: Style) -> clap::builder::styling::Style {
+    use clap::builder::styling::Effects;
+
+    let mut effects = Effects::new();
+    let mut data = style.effects;
+
+    while let Some(effect) = data.const_first() {
+        data = data.without(effect);
+
+        effects = effects.insert(match effect {
+            Effect::Bold => Effects::BOLD,
+            Effect::Dimmed => Effects::DIMMED,
+            Effect::Italic => Effects::ITALIC,
+            Effect::Underline => Effects::UNDERLINE,
+            Effect::DoubleUnderline => Effects::DOUBLE_UNDERLINE,
+            Effect::Blink | Effect::BlinkFast => Effects::BLINK,
+            Effect::Reversed => Effects::INVERT,
+            Effect::Hidden => Effects::HIDDEN,
+            Effect::Strikethrough => Effects::STRIKETHROUGH,
+            Effect::Overline | Effect::SuperScript | Effect::SubScript => Effects::new(),
+        });
+    }
+
+    clap::builder::styling::Style::new()
+        .fg_color(clap_color(style.foreground))
+        .bg_color(clap_color(style.background))
+        .underline_color(clap_color(style.underline_color))
+        .effects(effects)
+}
+
+#[cfg(feature = "clap")]
+const fn clap_color(color: Option<Color>) -> Option<clap::builder::styling::Color> {
+    use clap::builder::styling::{self as s, Ansi256Color, RgbColor};
+
+    let color = match color {
+        Some(color) => color,
+        None => return None,
+    };
+
+    Some(match color {
+        Color::Ansi(color) => s::Color::Ansi(match color {
+            AnsiColor::Black => s::AnsiColor::Black,
+            AnsiColor::Red => s::AnsiColor::Red,
+            AnsiColor::Green => s::AnsiColor::Green,
+            AnsiColor::Yellow => s::AnsiColor::Yellow,
+            AnsiColor::Blue => s::AnsiColor::Blue,
+            AnsiColor::Magenta => s::AnsiColor::Magenta,
+            AnsiColor::Cyan => s::AnsiColor::Cyan,
+            AnsiColor::White => s::AnsiColor::White,
+            AnsiColor::BrightBlack => s::AnsiColor::BrightBlack,
+            AnsiColor::BrightRed => s::AnsiColor::BrightRed,
+            AnsiColor::BrightGreen => s::AnsiColor::BrightGreen,
+            AnsiColor::BrightYellow => s::AnsiColor::BrightYellow,
+            AnsiColor::BrightBlue => s::AnsiColor::BrightBlue,
+            AnsiColor::BrightMagenta => s::AnsiColor::BrightMagenta,
+            AnsiColor::BrightCyan => s::AnsiColor::BrightCyan,
+            AnsiColor::BrightWhite => s::AnsiColor::BrightWhite,
+            AnsiColor::Default => return None,
+        }),
+        Color::Xterm(color) => s::Color::Ansi256(Ansi256Color(color as u8)),
+        Color::Css(color) => {
+            let color = color.rgb();
+            s::Color::Rgb(RgbColor(color.red, color.green, color.blue))
+        }
+        Color::Rgb(color) => s::Color::Rgb(RgbColor(color.red, color.green, color.blue)),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::ColorChoice;
+
+    #[test]
+    fn color_choice_from_str() {
+        for (input, choice) in [
+            ("auto", ColorChoice::Auto),
+            ("AUTO", ColorChoice::Auto),
+            ("detect", ColorChoice::Auto),
+            ("always", ColorChoice::Always),
+            ("Always", ColorChoice::Always),
+            ("never", ColorChoice::Never),
+        ] {
+            assert_eq!(input.parse(), Ok(choice));
+            assert_eq!(choice.as_str().parse(), Ok(choice));
+        }
+
+        assert!("autos".parse::<ColorChoice>().is_err());
+        assert!("yes".parse::<ColorChoice>().is_err());
+        assert!("".parse::<ColorChoice>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "clap")]
+    fn clap_args() {
+        use super::ColorArgs;
+        use clap::{Args, FromArgMatches};
+
+        let parse = |args: &[&str]| {
+            let command = ColorArgs::augment_args(clap::Command::new("app"));
+            let matches = command.try_get_matches_from(args)?;
+            ColorArgs::from_arg_matches(&matches).map(|args| args.color)
+        };
+
+        assert_eq!(parse(&["app"]).unwrap(), ColorChoice::Auto);
+        assert_eq!(
+            parse(&["app", "--color=always"]).unwrap(),
+            ColorChoice::Always
+        );
+        assert_eq!(
+            parse(&["app", "--color", "detect"]).unwrap(),
+            ColorChoice::Auto
+        );
+        assert_eq!(
+            parse(&["app", "--color", "never"]).unwrap(),
+            ColorChoice::Never
+        );
+        assert!(parse(&["app", "--color", "sometimes"]).is_err());
+    }
+}
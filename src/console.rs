@@ -0,0 +1,547 @@
+//! Colors for legacy Windows consoles, which don't interpret escape sequences
+//!
+//! Before virtual terminal processing, the Windows console colored text with an attribute word
+//! which is set between writes, instead of escape sequences in the text. This module translates
+//! [`Style`]s into those attribute words ([`attributes`]), and [`ConsoleWriter`] turns text with
+//! SGR sequences into plain writes and attribute changes.
+//!
+//! colorz doesn't call the console API itself, that's left to a [`ConsoleBackend`], so the
+//! translation works (and can be tested) on any platform.
+//!
+//! ```rust
+//! use colorz::console::{self, ConsoleBackend, ConsoleWriter};
+//! use colorz::{mode::Stream, Colorize};
+//!
+//! #[derive(Default)]
+//! struct Recorder {
+//!     attributes: u16,
+//!     writes: Vec<(String, u16)>,
+//! }
+//!
+//! impl ConsoleBackend for Recorder {
+//!     fn attributes(&mut self) -> std::io::Result<u16> {
+//!         Ok(self.attributes)
+//!     }
+//!
+//!     fn set_attributes(&mut self, attributes: u16) -> std::io::Result<()> {
+//!         self.attributes = attributes;
+//!         Ok(())
+//!     }
+//!
+//!     fn write_str(&mut self, s: &str) -> std::io::Result<()> {
+//!         self.writes.push((s.to_string(), self.attributes));
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let gray = console::FOREGROUND_RED | console::FOREGROUND_GREEN | console::FOREGROUND_BLUE;
+//! let mut writer = ConsoleWriter::new(Recorder { attributes: gray, ..Recorder::default() })?;
+//! write!(writer, "{} ok", "error".red().bold().stream(Stream::AlwaysColor))?;
+//!
+//! # #[cfg(not(feature = "strip-colors"))]
+//! assert_eq!(
+//!     writer.backend().writes,
+//!     [
+//!         ("error".to_string(), console::FOREGROUND_RED | console::FOREGROUND_INTENSITY),
+//!         (" ok".to_string(), gray),
+//!     ]
+//! );
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::io;
+use std::string::String;
+
+use crate::{
+    string::{apply_sgr, PLAIN},
+    text::{self, Segment},
+    xterm::XtermColor,
+    Color, Effect, Style,
+};
+
+/// The foreground has blue in it
+pub const FOREGROUND_BLUE: u16 = 0x0001;
+/// The foreground has green in it
+pub const FOREGROUND_GREEN: u16 = 0x0002;
+/// The foreground has red in it
+pub const FOREGROUND_RED: u16 = 0x0004;
+/// The foreground is the bright version of the color
+pub const FOREGROUND_INTENSITY: u16 = 0x0008;
+/// The background has blue in it
+pub const BACKGROUND_BLUE: u16 = 0x0010;
+/// The background has green in it
+pub const BACKGROUND_GREEN: u16 = 0x0020;
+/// The background has red in it
+pub const BACKGROUND_RED: u16 = 0x0040;
+/// The background is the bright version of the color
+pub const BACKGROUND_INTENSITY: u16 = 0x0080;
+/// The foreground and background are swapped
+pub const COMMON_LVB_REVERSE_VIDEO: u16 = 0x4000;
+/// The text is underlined
+pub const COMMON_LVB_UNDERSCORE: u16 = 0x8000;
+
+const FOREGROUND_MASK: u16 = 0x000f;
+const BACKGROUND_MASK: u16 = 0x00f0;
+
+/// The colors of the legacy console palette, indexed by their foreground attributes
+const PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (0, 0, 128),
+    (0, 128, 0),
+    (0, 128, 128),
+    (128, 0, 0),
+    (128, 0, 128),
+    (128, 128, 0),
+    (192, 192, 192),
+    (128, 128, 128),
+    (0, 0, 255),
+    (0, 255, 0),
+    (0, 255, 255),
+    (255, 0, 0),
+    (255, 0, 255),
+    (255, 255, 0),
+    (255, 255, 255),
+];
+
+/// The console API which colors are written with
+///
+/// On Windows this is implemented with `GetConsoleScreenBufferInfo`, `SetConsoleTextAttribute`
+/// and `WriteConsoleW`, and a mock implementation can be used to test the output anywhere.
+pub trait ConsoleBackend {
+    /// The current attributes, which text is written with
+    fn attributes(&mut self) -> io::Result<u16>;
+
+    /// Set the attributes for the following writes
+    fn set_attributes(&mut self, attributes: u16) -> io::Result<()>;
+
+    /// Write text, without any escape sequences
+    fn write_str(&mut self, s: &str) -> io::Result<()>;
+
+    /// Turn on virtual terminal processing, so escape sequences can be written directly
+    ///
+    /// Returns `false` if the console doesn't support it, which is the default.
+    #[inline]
+    fn enable_virtual_terminal_processing(&mut self) -> io::Result<bool> {
+        Ok(false)
+    }
+}
+
+/// Turn on virtual terminal processing if the platform needs it, returns whether escape sequences can be written
+///
+/// This only does something on Windows, where it calls
+/// [`ConsoleBackend::enable_virtual_terminal_processing`]. Terminals on other platforms always
+/// interpret escape sequences, so the backend isn't used.
+///
+/// ```rust
+/// # use colorz::console::{self, ConsoleBackend};
+/// # struct Console;
+/// # impl ConsoleBackend for Console {
+/// #     fn attributes(&mut self) -> std::io::Result<u16> { Ok(7) }
+/// #     fn set_attributes(&mut self, _: u16) -> std::io::Result<()> { Ok(()) }
+/// #     fn write_str(&mut self, _: &str) -> std::io::Result<()> { Ok(()) }
+/// # }
+/// if !console::enable_virtual_terminal_processing(&mut Console)? {
+///     // write through a `ConsoleWriter` instead
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[inline]
+#[allow(clippy::missing_const_for_fn)]
+pub fn enable_virtual_terminal_processing<B: ConsoleBackend + ?Sized>(
+    backend: &mut B,
+) -> io::Result<bool> {
+    #[cfg(windows)]
+    {
+        backend.enable_virtual_terminal_processing()
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = backend;
+        Ok(true)
+    }
+}
+
+/// The foreground attributes of the nearest console color
+fn console_color(color: Color) -> Option<u16> {
+    let (red, green, blue) = match color {
+        Color::Ansi(crate::ansi::AnsiColor::Default) => return None,
+        Color::Ansi(color) => return Some(ansi_attributes(color.to_xterm() as u16)),
+        Color::Xterm(color) if (color as u16) < 16 => return Some(ansi_attributes(color as u16)),
+        Color::Xterm(color) => xterm_rgb(color),
+        Color::Css(color) => {
+            let color = color.rgb();
+            (color.red, color.green, color.blue)
+        }
+        Color::Rgb(color) => (color.red, color.green, color.blue),
+    };
+
+    let distance = |&(r, g, b): &(u8, u8, u8)| {
+        let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).pow(2);
+        d(r, red) + d(g, green) + d(b, blue)
+    };
+
+    (0..16).min_by_key(|&i| distance(&PALETTE[usize::from(i)]))
+}
+
+/// ANSI colors have red in the lowest bit and blue in the highest bit, the console swaps them
+const fn ansi_attributes(index: u16) -> u16 {
+    let red = if index & 1 != 0 { FOREGROUND_RED } else { 0 };
+    let blue = if index & 4 != 0 { FOREGROUND_BLUE } else { 0 };
+
+    red | blue | (index & (FOREGROUND_GREEN | FOREGROUND_INTENSITY))
+}
+
+/// The rgb value of an xterm color from the color cube or the grayscale ramp
+fn xterm_rgb(color: XtermColor) -> (u8, u8, u8) {
+    let index = color as u8;
+
+    if index >= 232 {
+        let level = 8 + (index - 232) * 10;
+        return (level, level, level);
+    }
+
+    let level = |x: u8| if x == 0 { 0 } else { 55 + x * 40 };
+    let index = index - 16;
+    (level(index / 36), level(index / 6 % 6), level(index % 6))
+}
+
+/// The console attributes for a style
+///
+/// `default` is the console's attributes before anything was written, which are used for unset
+/// colors. Bold text is bright, underlines use [`COMMON_LVB_UNDERSCORE`], reversed text swaps
+/// the foreground and background, and hidden text has the background color in the foreground.
+/// The other effects aren't supported by the console, and colors which aren't ANSI colors use
+/// the nearest console color.
+///
+/// ```rust
+/// use colorz::{ansi, console, Style};
+///
+/// let default = console::FOREGROUND_RED | console::FOREGROUND_GREEN | console::FOREGROUND_BLUE;
+/// let style = Style::new().fg(ansi::BrightYellow).bg(ansi::Blue).underline().into_runtime_style();
+///
+/// assert_eq!(
+///     console::attributes(&style, default),
+///     console::FOREGROUND_RED
+///         | console::FOREGROUND_GREEN
+///         | console::FOREGROUND_INTENSITY
+///         | console::BACKGROUND_BLUE
+///         | console::COMMON_LVB_UNDERSCORE
+/// );
+/// ```
+#[inline]
+pub fn attributes(style: &Style, default: u16) -> u16 {
+    let mut fg = default & FOREGROUND_MASK;
+    let mut bg = (default & BACKGROUND_MASK) >> 4;
+
+    if let Some(color) = style.foreground.and_then(console_color) {
+        fg = color;
+    }
+
+    if let Some(color) = style.background.and_then(console_color) {
+        bg = color;
+    }
+
+    if style.is(Effect::Bold) {
+        fg |= FOREGROUND_INTENSITY;
+    }
+
+    if style.is(Effect::Reversed) {
+        core::mem::swap(&mut fg, &mut bg);
+    }
+
+    if style.is(Effect::Hidden) {
+        fg = bg;
+    }
+
+    let mut attributes = fg | bg << 4;
+
+    if style.is(Effect::Underline) || style.is(Effect::DoubleUnderline) {
+        attributes |= COMMON_LVB_UNDERSCORE;
+    }
+
+    attributes
+}
+
+/// Writes text with SGR sequences to a [`ConsoleBackend`] as plain text and attribute changes
+///
+/// Other escape sequences are left out. The attributes the console had when the writer was
+/// created are used for unset colors, and set again by [`reset`](Self::reset).
+#[derive(Debug)]
+pub struct ConsoleWriter<B> {
+    backend: B,
+    default: u16,
+    current: u16,
+    style: Style,
+}
+
+impl<B: ConsoleBackend> ConsoleWriter<B> {
+    /// Create a writer, with the current attributes of the console as the defaults
+    #[inline]
+    pub fn new(mut backend: B) -> io::Result<Self> {
+        let default = backend.attributes()?;
+
+        Ok(Self {
+            backend,
+            default,
+            current: default,
+            style: PLAIN,
+        })
+    }
+
+    /// The backend which is written to
+    #[inline]
+    pub const fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// The backend which is written to
+    #[inline]
+    pub const fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    /// Get the backend back
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.backend
+    }
+
+    /// The style of the text which is written next
+    #[inline]
+    pub const fn style(&self) -> Style {
+        self.style
+    }
+
+    /// Write text which may contain SGR sequences
+    ///
+    /// Each escape sequence has to be in a single call
+    #[inline]
+    pub fn write_str(&mut self, s: &str) -> io::Result<()> {
+        for segment in text::segments(s) {
+            match segment {
+                Segment::Text(text) => {
+                    let attributes = attributes(&self.style, self.default);
+                    if attributes != self.current {
+                        self.backend.set_attributes(attributes)?;
+                        self.current = attributes;
+                    }
+
+                    self.backend.write_str(text)?;
+                }
+                Segment::Escape(escape) => {
+                    if let Some(params) = escape
+                        .strip_prefix("\x1b[")
+                        .and_then(|escape| escape.strip_suffix('m'))
+                    {
+                        apply_sgr(&mut self.style, params);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write formatted text which may contain SGR sequences, for use with [`write!`]
+    #[inline]
+    pub fn write_fmt(&mut self, args: core::fmt::Arguments<'_>) -> io::Result<()> {
+        let mut s = String::new();
+        // formatting into a string only fails if a Display impl does
+        core::fmt::Write::write_fmt(&mut s, args)
+            .map_err(|_| io::Error::other("formatter error"))?;
+        self.write_str(&s)
+    }
+
+    /// Go back to the console's default attributes
+    #[inline]
+    pub fn reset(&mut self) -> io::Result<()> {
+        self.style = PLAIN;
+        if self.current != self.default {
+            self.backend.set_attributes(self.default)?;
+            self.current = self.default;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{ansi::AnsiColor, css, rgb::RgbColor, xterm};
+
+    const GRAY: u16 = FOREGROUND_RED | FOREGROUND_GREEN | FOREGROUND_BLUE;
+
+    #[derive(Debug, PartialEq)]
+    enum Call {
+        SetAttributes(u16),
+        Write(String),
+    }
+
+    struct MockConsole {
+        attributes: u16,
+        calls: Vec<Call>,
+    }
+
+    impl ConsoleBackend for MockConsole {
+        fn attributes(&mut self) -> io::Result<u16> {
+            Ok(self.attributes)
+        }
+
+        fn set_attributes(&mut self, attributes: u16) -> io::Result<()> {
+            self.attributes = attributes;
+            self.calls.push(Call::SetAttributes(attributes));
+            Ok(())
+        }
+
+        fn write_str(&mut self, s: &str) -> io::Result<()> {
+            self.calls.push(Call::Write(String::from(s)));
+            Ok(())
+        }
+
+        fn enable_virtual_terminal_processing(&mut self) -> io::Result<bool> {
+            panic!("virtual terminal processing is only enabled on Windows")
+        }
+    }
+
+    fn mock() -> MockConsole {
+        MockConsole {
+            attributes: GRAY,
+            calls: Vec::new(),
+        }
+    }
+
+    fn attrs<F, B, U>(style: Style<F, B, U>) -> u16
+    where
+        F: Into<Option<Color>>,
+        B: Into<Option<Color>>,
+        U: Into<Option<Color>>,
+    {
+        attributes(&style.into_runtime_style(), GRAY)
+    }
+
+    #[test]
+    fn ansi_colors() {
+        const BRIGHT: [AnsiColor; 8] = [
+            AnsiColor::BrightBlack,
+            AnsiColor::BrightRed,
+            AnsiColor::BrightGreen,
+            AnsiColor::BrightYellow,
+            AnsiColor::BrightBlue,
+            AnsiColor::BrightMagenta,
+            AnsiColor::BrightCyan,
+            AnsiColor::BrightWhite,
+        ];
+
+        let expected = [
+            (AnsiColor::Black, 0),
+            (AnsiColor::Red, FOREGROUND_RED),
+            (AnsiColor::Green, FOREGROUND_GREEN),
+            (AnsiColor::Yellow, FOREGROUND_RED | FOREGROUND_GREEN),
+            (AnsiColor::Blue, FOREGROUND_BLUE),
+            (AnsiColor::Magenta, FOREGROUND_RED | FOREGROUND_BLUE),
+            (AnsiColor::Cyan, FOREGROUND_GREEN | FOREGROUND_BLUE),
+            (AnsiColor::White, GRAY),
+        ];
+
+        for ((color, fg), bright) in expected.into_iter().zip(BRIGHT) {
+            let bright_fg = fg | FOREGROUND_INTENSITY;
+
+            assert_eq!(attrs(Style::new().fg(color)), fg, "{color:?}");
+            assert_eq!(attrs(Style::new().fg(bright)), bright_fg, "{bright:?}");
+            assert_eq!(attrs(Style::new().bg(color)), GRAY | fg << 4, "{color:?}");
+            assert_eq!(attrs(Style::new().bg(bright)), GRAY | bright_fg << 4);
+        }
+
+        assert_eq!(attrs(Style::new().fg(AnsiColor::Default)), GRAY);
+        assert_eq!(attrs(Style::new().bg(AnsiColor::Default)), GRAY);
+    }
+
+    #[test]
+    fn other_colors() {
+        assert_eq!(attrs(Style::new().fg(xterm::Red)), FOREGROUND_RED);
+        assert_eq!(
+            attrs(Style::new().fg(xterm::Aqua)),
+            FOREGROUND_GREEN | FOREGROUND_BLUE | FOREGROUND_INTENSITY
+        );
+        assert_eq!(attrs(Style::new().fg(xterm::Gray0)), 0);
+        assert_eq!(attrs(Style::new().fg(xterm::Gray3)), 0);
+        assert_eq!(
+            attrs(Style::new().fg(xterm::Gray93)),
+            GRAY | FOREGROUND_INTENSITY
+        );
+        assert_eq!(attrs(Style::new().fg(css::Navy)), FOREGROUND_BLUE);
+        assert_eq!(
+            attrs(Style::new().bg(RgbColor {
+                red: 250,
+                green: 10,
+                blue: 240
+            })),
+            GRAY | (FOREGROUND_RED | FOREGROUND_BLUE | FOREGROUND_INTENSITY) << 4
+        );
+    }
+
+    #[test]
+    fn effects() {
+        assert_eq!(attrs(Style::new().bold()), GRAY | FOREGROUND_INTENSITY);
+        assert_eq!(attrs(Style::new().italics().dimmed().blink()), GRAY);
+        assert_eq!(
+            attrs(Style::new().underline()),
+            GRAY | COMMON_LVB_UNDERSCORE
+        );
+        assert_eq!(
+            attrs(Style::new().double_underline()),
+            GRAY | COMMON_LVB_UNDERSCORE
+        );
+        assert_eq!(
+            attrs(
+                Style::new()
+                    .fg(AnsiColor::Red)
+                    .bg(AnsiColor::Blue)
+                    .reverse()
+            ),
+            FOREGROUND_BLUE | BACKGROUND_RED
+        );
+        assert_eq!(
+            attrs(Style::new().fg(AnsiColor::Red).bg(AnsiColor::Blue).hide()),
+            FOREGROUND_BLUE | BACKGROUND_BLUE
+        );
+    }
+
+    #[test]
+    fn writer() {
+        let mut writer = ConsoleWriter::new(mock()).unwrap();
+
+        writer
+            .write_str("a\x1b[31;1mb\x1b[Kc\x1b[22md\x1b[0m\x1b[4me")
+            .unwrap();
+        writer.reset().unwrap();
+        writer.reset().unwrap();
+
+        assert_eq!(
+            writer.into_inner().calls,
+            [
+                Call::Write("a".into()),
+                Call::SetAttributes(FOREGROUND_RED | FOREGROUND_INTENSITY),
+                Call::Write("b".into()),
+                Call::Write("c".into()),
+                Call::SetAttributes(FOREGROUND_RED),
+                Call::Write("d".into()),
+                Call::SetAttributes(GRAY | COMMON_LVB_UNDERSCORE),
+                Call::Write("e".into()),
+                Call::SetAttributes(GRAY),
+            ]
+        );
+    }
+
+    #[test]
+    fn virtual_terminal_processing() {
+        // the mock panics if it's called
+        if cfg!(not(windows)) {
+            assert!(enable_virtual_terminal_processing(&mut mock()).unwrap());
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Control sequences for the cursor, the screen and the terminal window
+//!
+//! Each sequence is a zero-allocation value which writes its escape sequence with [`Display`].
+//! Sequences without any parameters also have an `ESCAPE` constant, like
+//! [`ansi::Red::FOREGROUND_ESCAPE`](crate::ansi::Red::FOREGROUND_ESCAPE).
+//!
+//! Like [`StyledValue`](crate::StyledValue), sequences are only written if
+//! [`mode::should_color`](crate::mode::should_color) allows it, so they don't end up in files or
+//! pipes. By default the [default stream](crate::mode::get_default_stream) is checked, and
+//! [`Control::stream`] picks another stream.
+//!
+//! ```rust
+//! use colorz::{control::{self, Control}, mode::Stream};
+//!
+//! assert_eq!(control::HideCursor::ESCAPE, "\x1b[?25l");
+//!
+//! print!("{}{}", control::ClearScreen, control::CursorTo { x: 0, y: 0 });
+//!
+//! let up = control::CursorUp(2).stream(Stream::AlwaysColor);
+//! # #[cfg(not(feature = "strip-colors"))]
+//! assert_eq!(up.to_string(), "\x1b[2A");
+//!
+//! let title = control::SetWindowTitle("hello").stream(Stream::NeverColor);
+//! assert_eq!(title.to_string(), "");
+//! ```
+
+use core::fmt::{self, Display, Write};
+
+use crate::mode::{self, Stream};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A control sequence, see the [module docs](self) for details
+///
+/// This trait is sealed, and can't be implemented outside of `colorz`
+pub trait Control: private::Sealed {
+    /// Write the escape sequence, without checking if it should be written
+    fn fmt_escape(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    /// Only write the sequence if [`mode::should_color`] allows it for `stream`
+    #[inline]
+    fn stream(self, stream: Stream) -> OnStream<Self>
+    where
+        Self: Sized,
+    {
+        OnStream {
+            control: self,
+            stream,
+        }
+    }
+}
+
+/// A control sequence which is written if [`mode::should_color`] allows it for a stream, see [`Control::stream`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OnStream<C> {
+    control: C,
+    stream: Stream,
+}
+
+impl<C: Control> Display for OnStream<C> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if mode::should_color(Some(self.stream), &[]) {
+            self.control.fmt_escape(f)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+macro_rules! Fixed {
+    ($($(#[$meta:meta])* $name:ident $escape:literal)*) => {$(
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name;
+
+        impl $name {
+            /// The escape sequence
+            pub const ESCAPE: &'static str = $escape;
+        }
+
+        impl private::Sealed for $name {}
+
+        impl Control for $name {
+            #[inline]
+            fn fmt_escape(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(Self::ESCAPE)
+            }
+        }
+
+        impl Display for $name {
+            #[inline]
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                if mode::should_color(None, &[]) {
+                    self.fmt_escape(f)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    )*};
+}
+
+Fixed! {
+    /// Save the cursor position (`ESC 7`)
+    SaveCursor "\x1b7"
+    /// Restore the cursor position saved by [`SaveCursor`] (`ESC 8`)
+    RestoreCursor "\x1b8"
+    /// Hide the cursor
+    HideCursor "\x1b[?25l"
+    /// Show the cursor
+    ShowCursor "\x1b[?25h"
+    /// Clear the line the cursor is on
+    ClearLine "\x1b[2K"
+    /// Clear from the cursor to the end of the line (`CSI K`)
+    ClearToEndOfLine "\x1b[K"
+    /// Clear from the start of the line to the cursor
+    ClearToStartOfLine "\x1b[1K"
+    /// Clear the screen, without moving the cursor
+    ClearScreen "\x1b[2J"
+    /// Clear from the cursor to the end of the screen
+    ClearToEndOfScreen "\x1b[J"
+    /// Clear from the start of the screen to the cursor
+    ClearToStartOfScreen "\x1b[1J"
+    /// Switch to the alternate screen, which doesn't have any scrollback
+    EnterAlternateScreen "\x1b[?1049h"
+    /// Switch back from the alternate screen, restoring what was on the screen before
+    LeaveAlternateScreen "\x1b[?1049l"
+    /// Enable bracketed paste, so pasted text is wrapped in `ESC [200~` and `ESC [201~`
+    EnableBracketedPaste "\x1b[?2004h"
+    /// Disable bracketed paste
+    DisableBracketedPaste "\x1b[?2004l"
+}
+
+macro_rules! Parameterized {
+    ($($(#[$meta:meta])* $name:ident($self:ident, $f:ident) $body:block)*) => {$(
+        impl private::Sealed for $name {}
+
+        impl Control for $name {
+            #[inline]
+            fn fmt_escape(&$self, $f: &mut fmt::Formatter<'_>) -> fmt::Result $body
+        }
+
+        impl Display for $name {
+            #[inline]
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                if mode::should_color(None, &[]) {
+                    self.fmt_escape(f)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    )*};
+}
+
+/// Move the cursor up some lines
+///
+/// Moving by 0 lines writes nothing, since most terminals treat it as 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CursorUp(pub u16);
+
+/// Move the cursor down some lines
+///
+/// Moving by 0 lines writes nothing, since most terminals treat it as 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CursorDown(pub u16);
+
+/// Move the cursor right some columns
+///
+/// Moving by 0 columns writes nothing, since most terminals treat it as 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CursorForward(pub u16);
+
+/// Move the cursor left some columns
+///
+/// Moving by 0 columns writes nothing, since most terminals treat it as 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CursorBack(pub u16);
+
+/// Move the cursor to a column in the current line, starting from 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CursorToColumn(pub u16);
+
+/// Move the cursor to a position on the screen, starting from 0 in the top left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CursorTo {
+    /// The column
+    pub x: u16,
+    /// The row
+    pub y: u16,
+}
+
+fn fmt_move(f: &mut fmt::Formatter<'_>, n: u16, direction: char) -> fmt::Result {
+    if n == 0 {
+        Ok(())
+    } else {
+        write!(f, "\x1b[{n}{direction}")
+    }
+}
+
+Parameterized! {
+    CursorUp(self, f) { fmt_move(f, self.0, 'A') }
+    CursorDown(self, f) { fmt_move(f, self.0, 'B') }
+    CursorForward(self, f) { fmt_move(f, self.0, 'C') }
+    CursorBack(self, f) { fmt_move(f, self.0, 'D') }
+    CursorToColumn(self, f) { write!(f, "\x1b[{}G", u32::from(self.0) + 1) }
+    CursorTo(self, f) { write!(f, "\x1b[{};{}H", u32::from(self.y) + 1, u32::from(self.x) + 1) }
+}
+
+/// Set the window title and icon name (`OSC 0`)
+///
+/// Control characters in the title are left out, so it can't end the sequence early
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SetTitle<T>(pub T);
+
+/// Set the window title (`OSC 2`)
+///
+/// Control characters in the title are left out, so it can't end the sequence early
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SetWindowTitle<T>(pub T);
+
+/// Leaves out control characters
+struct Printable<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+impl Write for Printable<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for part in s.split(char::is_control) {
+            self.0.write_str(part)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn fmt_title(f: &mut fmt::Formatter<'_>, kind: u8, title: &dyn Display) -> fmt::Result {
+    write!(f, "\x1b]{kind};")?;
+    write!(Printable(f), "{title}")?;
+    f.write_char('\x07')
+}
+
+impl<T> private::Sealed for SetTitle<T> {}
+
+impl<T: Display> Control for SetTitle<T> {
+    #[inline]
+    fn fmt_escape(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_title(f, 0, &self.0)
+    }
+}
+
+impl<T: Display> Display for SetTitle<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if mode::should_color(None, &[]) {
+            self.fmt_escape(f)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<T> private::Sealed for SetWindowTitle<T> {}
+
+impl<T: Display> Control for SetWindowTitle<T> {
+    #[inline]
+    fn fmt_escape(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_title(f, 2, &self.0)
+    }
+}
+
+impl<T: Display> Display for SetWindowTitle<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if mode::should_color(None, &[]) {
+            self.fmt_escape(f)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn escapes() {
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        let escape = |control: &dyn Fn(Stream) -> std::string::String| {
+            assert_eq!(control(Stream::NeverColor), "");
+            control(Stream::AlwaysColor)
+        };
+
+        assert_eq!(escape(&|s| HideCursor.stream(s).to_string()), "\x1b[?25l");
+        assert_eq!(escape(&|s| ClearToEndOfLine.stream(s).to_string()), "\x1b[K");
+        assert_eq!(escape(&|s| CursorUp(3).stream(s).to_string()), "\x1b[3A");
+        assert_eq!(escape(&|s| CursorBack(12).stream(s).to_string()), "\x1b[12D");
+        assert_eq!(escape(&|s| CursorDown(0).stream(s).to_string()), "");
+        assert_eq!(escape(&|s| CursorToColumn(0).stream(s).to_string()), "\x1b[1G");
+        assert_eq!(
+            escape(&|s| CursorTo { x: 4, y: 9 }.stream(s).to_string()),
+            "\x1b[10;5H"
+        );
+        assert_eq!(
+            escape(&|s| SetTitle("a\x07b\x1b]c").stream(s).to_string()),
+            "\x1b]0;ab]c\x07"
+        );
+        assert_eq!(
+            escape(&|s| SetWindowTitle(format_args!("{}%", 50)).stream(s).to_string()),
+            "\x1b]2;50%\x07"
+        );
+    }
+}
@@ -84,6 +84,40 @@ macro_rules! Css {
         )*
 
         impl CssColor {
+            /// The name of this color, as accepted by [`Color`]'s [`FromStr`](core::str::FromStr) impl
+            ///
+            /// ```
+            /// use colorz::css::CssColor;
+            ///
+            /// assert_eq!(CssColor::AliceBlue.name(), "AliceBlue");
+            /// ```
+            #[inline]
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(Self::$name => stringify!($name),)*
+                }
+            }
+
+            /// Every Css color, in declaration order
+            #[cfg(all(test, feature = "serde"))]
+            pub(crate) const ALL: &'static [Self] = &[$(Self::$name,)*];
+
+            /// Get a Css color by it's [name](Self::name)
+            ///
+            /// ```
+            /// use colorz::css::CssColor;
+            ///
+            /// assert_eq!(CssColor::from_name("AliceBlue"), Some(CssColor::AliceBlue));
+            /// assert_eq!(CssColor::from_name("aliceblue"), None);
+            /// ```
+            #[inline]
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $(stringify!($name) => Some(Self::$name),)*
+                    _ => None,
+                }
+            }
+
             /// The ANSI color args
             #[inline]
             pub const fn args(self) -> &'static str {
@@ -0,0 +1,458 @@
+//! Syntax highlighted `Debug` output
+//!
+//! [`Colorize::debug_colored`](crate::Colorize::debug_colored) wraps any [`Debug`](fmt::Debug)
+//! value, and colors the output of `{:?}` and `{:#?}` as it is written. Type and variant names,
+//! field names, string and char literals, numbers, punctuation and brackets each get their own
+//! style from the [`DebugStyles`], so no derive or extra trait is needed.
+//!
+//! ```rust
+//! use colorz::Colorize;
+//!
+//! #[derive(Debug)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//!     label: Option<&'static str>,
+//! }
+//!
+//! let point = Point { x: 1, y: -2, label: Some("origin") };
+//!
+//! println!("{:#?}", point.debug_colored());
+//! ```
+//!
+//! The output is split up with simple rules, which work for derived `Debug` impls and
+//! the standard library's types:
+//! * words starting with an uppercase letter are type or variant names
+//! * other words are field names, except for `true` and `false` which are styled like numbers
+//! * anything in `"` or `'` is a string
+
+use core::fmt::{self, Display, Write};
+
+use crate::{ansi, mode::Stream, Style};
+
+/// The styles used by [`DebugColored`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DebugStyles {
+    /// The style of type and variant names
+    pub name: Style,
+    /// The style of field names
+    pub field: Style,
+    /// The style of string and char literals, including the quotes
+    pub string: Style,
+    /// The style of numbers and `true`/`false`
+    pub number: Style,
+    /// The style of punctuation (`:`, `,`, `..`, etc.)
+    pub punctuation: Style,
+    /// The style of brackets (`{}`, `[]` and `()`)
+    pub bracket: Style,
+}
+
+impl DebugStyles {
+    /// No styling at all
+    pub const PLAIN: Self = Self {
+        name: Style::new().const_into_runtime_style(),
+        field: Style::new().const_into_runtime_style(),
+        string: Style::new().const_into_runtime_style(),
+        number: Style::new().const_into_runtime_style(),
+        punctuation: Style::new().const_into_runtime_style(),
+        bracket: Style::new().const_into_runtime_style(),
+    };
+
+    /// Styles similar to most editor themes
+    pub const DEFAULT: Self = Self {
+        name: Style::new().fg(ansi::Yellow).const_into_runtime_style(),
+        field: Style::new().fg(ansi::Cyan).const_into_runtime_style(),
+        string: Style::new().fg(ansi::Green).const_into_runtime_style(),
+        number: Style::new().fg(ansi::Magenta).const_into_runtime_style(),
+        punctuation: Style::new().dimmed().const_into_runtime_style(),
+        bracket: Style::new().bold().const_into_runtime_style(),
+    };
+
+    /// Take the styles from a theme, using the [default styles](Self::DEFAULT) for any which are missing
+    ///
+    /// The styles are named `debug.name`, `debug.field`, `debug.string`, `debug.number`,
+    /// `debug.punctuation` and `debug.bracket`
+    ///
+    /// ```rust
+    /// use colorz::{debug::DebugStyles, Colorize};
+    ///
+    /// let theme = "debug.field = italics blue\ndebug.string = bright green".parse().unwrap();
+    /// let styles = DebugStyles::from_theme(&theme);
+    ///
+    /// println!("{:#?}", (1, "two").debug_colored().styles(styles));
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    #[inline]
+    pub fn from_theme(theme: &crate::theme::Theme) -> Self {
+        let get = |name, default| theme.get(name).unwrap_or(default);
+
+        Self {
+            name: get("debug.name", Self::DEFAULT.name),
+            field: get("debug.field", Self::DEFAULT.field),
+            string: get("debug.string", Self::DEFAULT.string),
+            number: get("debug.number", Self::DEFAULT.number),
+            punctuation: get("debug.punctuation", Self::DEFAULT.punctuation),
+            bracket: get("debug.bracket", Self::DEFAULT.bracket),
+        }
+    }
+
+    /// Replace the styles which shouldn't be colored on this stream with plain styles
+    fn painted(mut self, stream: Option<Stream>) -> Self {
+        for style in [
+            &mut self.name,
+            &mut self.field,
+            &mut self.string,
+            &mut self.number,
+            &mut self.punctuation,
+            &mut self.bracket,
+        ] {
+            if !style.should_color(stream) {
+                *style = Style::new().into_runtime_style();
+            }
+        }
+
+        self
+    }
+
+    const fn get(&self, class: Class) -> Option<&Style> {
+        match class {
+            Class::Plain => None,
+            Class::Name => Some(&self.name),
+            Class::Field => Some(&self.field),
+            Class::String => Some(&self.string),
+            Class::Number => Some(&self.number),
+            Class::Punctuation => Some(&self.punctuation),
+            Class::Bracket => Some(&self.bracket),
+        }
+    }
+}
+
+impl Default for DebugStyles {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A value whose `Debug` output is syntax highlighted, see the [module docs](self) for details
+pub struct DebugColored<'a, T: ?Sized> {
+    value: &'a T,
+    styles: DebugStyles,
+    stream: Option<Stream>,
+}
+
+impl<T: ?Sized> Clone for DebugColored<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for DebugColored<'_, T> {}
+
+impl<'a, T: ?Sized> DebugColored<'a, T> {
+    /// Highlight the `Debug` output of a value with the [default styles](DebugStyles::DEFAULT)
+    #[inline]
+    pub const fn new(value: &'a T) -> Self {
+        Self {
+            value,
+            styles: DebugStyles::DEFAULT,
+            stream: None,
+        }
+    }
+
+    /// Set the styles to use
+    #[inline]
+    pub const fn styles(mut self, styles: DebugStyles) -> Self {
+        self.styles = styles;
+        self
+    }
+
+    /// Set the stream used to decide if the output is colored
+    #[inline]
+    pub const fn stream(mut self, stream: Stream) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Plain,
+    Name,
+    Field,
+    String,
+    Number,
+    Punctuation,
+    Bracket,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Normal,
+    Word(Class),
+    Number,
+    Quoted { quote: char, escaped: bool },
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Could the first char in `rest` be part of a number, because it's followed by a digit
+///
+/// Numbers are often written in pieces (`-`, `2`, `.`, `5`), so a char at the end of a write
+/// is assumed to be followed by a digit
+fn digit_follows(rest: &str) -> bool {
+    let mut chars = rest.chars();
+    chars.next();
+    chars.next().is_none_or(|c| c.is_ascii_digit())
+}
+
+/// Styles the `Debug` output as it is written, keeping track of strings and words which are
+/// split across multiple writes
+struct Highlighter<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    styles: DebugStyles,
+    state: State,
+    current: Class,
+}
+
+impl Highlighter<'_, '_> {
+    fn classify(&mut self, c: char, rest: &str) -> Class {
+        match self.state {
+            State::Quoted { quote, escaped } => {
+                if escaped {
+                    self.state = State::Quoted {
+                        quote,
+                        escaped: false,
+                    };
+                } else if c == '\\' {
+                    self.state = State::Quoted {
+                        quote,
+                        escaped: true,
+                    };
+                } else if c == quote {
+                    self.state = State::Normal;
+                }
+
+                return Class::String;
+            }
+            State::Word(class) if is_word(c) => return class,
+            State::Number if is_word(c) || (c == '.' && digit_follows(rest)) => {
+                return Class::Number
+            }
+            _ => self.state = State::Normal,
+        }
+
+        match c {
+            '"' | '\'' => {
+                self.state = State::Quoted {
+                    quote: c,
+                    escaped: false,
+                };
+                Class::String
+            }
+            '0'..='9' => {
+                self.state = State::Number;
+                Class::Number
+            }
+            '-' if digit_follows(rest) => {
+                self.state = State::Number;
+                Class::Number
+            }
+            c if is_word(c) => {
+                let word = &rest[..rest.find(|c| !is_word(c)).unwrap_or(rest.len())];
+
+                let class = if c.is_uppercase() {
+                    Class::Name
+                } else if word == "true" || word == "false" {
+                    Class::Number
+                } else {
+                    Class::Field
+                };
+
+                self.state = State::Word(class);
+                class
+            }
+            '{' | '}' | '[' | ']' | '(' | ')' => Class::Bracket,
+            c if c.is_whitespace() => Class::Plain,
+            _ => Class::Punctuation,
+        }
+    }
+
+    fn switch(&mut self, class: Class) -> fmt::Result {
+        let old = self
+            .styles
+            .get(self.current)
+            .filter(|style| !style.is_plain());
+        let new = self.styles.get(class).filter(|style| !style.is_plain());
+        self.current = class;
+
+        if old == new {
+            return Ok(());
+        }
+
+        if let Some(old) = old {
+            old.clear().fmt(self.f)?;
+        }
+
+        if let Some(new) = new {
+            new.apply().fmt(self.f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Write for Highlighter<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut start = 0;
+
+        for (i, c) in s.char_indices() {
+            let class = self.classify(c, &s[i..]);
+
+            if class != self.current {
+                self.f.write_str(&s[start..i])?;
+                self.switch(class)?;
+                start = i;
+            }
+        }
+
+        self.f.write_str(&s[start..])
+    }
+}
+
+impl<T: fmt::Debug + ?Sized> fmt::Debug for DebugColored<'_, T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let styles = self.styles.painted(self.stream);
+
+        if styles == DebugStyles::PLAIN {
+            return self.value.fmt(f);
+        }
+
+        let alternate = f.alternate();
+        let mut highlighter = Highlighter {
+            f,
+            styles,
+            state: State::Normal,
+            current: Class::Plain,
+        };
+
+        if alternate {
+            write!(highlighter, "{:#?}", self.value)?;
+        } else {
+            write!(highlighter, "{:?}", self.value)?;
+        }
+
+        highlighter.switch(Class::Plain)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use core::fmt;
+    use std::format;
+
+    use super::DebugStyles;
+    use crate::{ansi, mode::Stream, Colorize, Style};
+
+    const STYLES: DebugStyles = DebugStyles {
+        name: Style::new().fg(ansi::Red).const_into_runtime_style(),
+        field: Style::new().fg(ansi::Blue).const_into_runtime_style(),
+        string: Style::new().fg(ansi::Green).const_into_runtime_style(),
+        number: Style::new().fg(ansi::Magenta).const_into_runtime_style(),
+        ..DebugStyles::PLAIN
+    };
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Point {
+        x: i32,
+        y: f32,
+        label: Option<&'static str>,
+        tags: [char; 1],
+        hidden: bool,
+    }
+
+    const POINT: Point = Point {
+        x: 1,
+        y: -2.5,
+        label: Some("a \"b\""),
+        tags: ['\''],
+        hidden: false,
+    };
+
+    /// Writes strings and words in several pieces
+    struct Split;
+
+    impl fmt::Debug for Split {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("\"a\\")?;
+            f.write_str("\"b\" Na")?;
+            f.write_str("me 1")?;
+            f.write_str("2")
+        }
+    }
+
+    #[test]
+    fn plain() {
+        let value = POINT.debug_colored().stream(Stream::NeverColor);
+
+        assert_eq!(format!("{value:?}"), format!("{POINT:?}"));
+        assert_eq!(format!("{value:#?}"), format!("{POINT:#?}"));
+
+        let value = POINT
+            .debug_colored()
+            .styles(DebugStyles::PLAIN)
+            .stream(Stream::AlwaysColor);
+
+        assert_eq!(format!("{value:?}"), format!("{POINT:?}"));
+    }
+
+    #[test]
+    fn styled() {
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        let value = POINT
+            .debug_colored()
+            .styles(STYLES)
+            .stream(Stream::AlwaysColor);
+
+        assert_eq!(
+            format!("{value:?}"),
+            "\x1b[31mPoint\x1b[39m { \x1b[34mx\x1b[39m: \x1b[35m1\x1b[39m, \x1b[34my\x1b[39m: \
+            \x1b[35m-2.5\x1b[39m, \x1b[34mlabel\x1b[39m: \x1b[31mSome\x1b[39m(\x1b[32m\"a \\\"b\\\"\"\
+            \x1b[39m), \x1b[34mtags\x1b[39m: [\x1b[32m'\\''\x1b[39m], \x1b[34mhidden\x1b[39m: \
+            \x1b[35mfalse\x1b[39m }"
+        );
+
+        assert_eq!(
+            format!(
+                "{:#?}",
+                (1..3,)
+                    .debug_colored()
+                    .styles(STYLES)
+                    .stream(Stream::AlwaysColor)
+            ),
+            "(\n    \x1b[35m1\x1b[39m..\x1b[35m3\x1b[39m,\n)"
+        );
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                Split
+                    .debug_colored()
+                    .styles(STYLES)
+                    .stream(Stream::AlwaysColor)
+            ),
+            "\x1b[32m\"a\\\"b\"\x1b[39m \x1b[31mName\x1b[39m \x1b[35m12\x1b[39m"
+        );
+    }
+}
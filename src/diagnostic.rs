@@ -0,0 +1,814 @@
+//! Compiler style diagnostics, with source snippets and labelled underlines
+//!
+//! A [`Diagnostic`] has a [`Severity`], a message, and labels which point at byte ranges of the
+//! source. It renders like `rustc`'s errors: a header, a `-->` line with the path, the labelled
+//! source lines behind a gutter with line numbers, and any notes.
+//!
+//! ```rust
+//! use colorz::{diagnostic::{Diagnostic, Severity}, mode::Stream};
+//!
+//! let source = "fn main() {\n    let x: i32 = \"hello\";\n}";
+//!
+//! let diagnostic = Diagnostic::new(Severity::Error, "mismatched types")
+//!     .code("E0308")
+//!     .path("src/main.rs")
+//!     .source(source)
+//!     .primary(29..36, "expected `i32`, found `&str`")
+//!     .secondary(23..26, "expected due to this")
+//!     .stream(Stream::NeverColor);
+//!
+//! assert_eq!(
+//!     diagnostic.to_string(),
+//!     r#"error[E0308]: mismatched types
+//!  --> src/main.rs:2:18
+//!   |
+//! 2 |     let x: i32 = "hello";
+//!   |            ---   ^^^^^^^ expected `i32`, found `&str`
+//!   |            |
+//!   |            expected due to this"#
+//! );
+//! ```
+//!
+//! Underlines are drawn with `^` (for primary labels) and `-` (for secondary labels) under the
+//! source line. With [`Diagnostic::curly_underlines`], the source itself is given a curly
+//! underline in the label's color instead, when the output is colored. Terminals don't report
+//! whether they support curly underlines, so this is off by default.
+//!
+//! Labels which span several lines are underlined to the end of their first line.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::{
+    fmt::{self, Display, Write},
+    ops::Range,
+};
+
+use crate::{ansi, mode::Stream, text, Colorize, Style};
+
+/// How severe a [`Diagnostic`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// An error
+    Error,
+    /// A warning
+    Warning,
+    /// Extra information
+    Note,
+    /// A suggestion
+    Help,
+}
+
+impl Severity {
+    /// The name of the severity, as shown in the diagnostic
+    ///
+    /// ```rust
+    /// use colorz::diagnostic::Severity;
+    ///
+    /// assert_eq!(Severity::Warning.name(), "warning");
+    /// ```
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Note => "note",
+            Self::Help => "help",
+        }
+    }
+}
+
+impl Display for Severity {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The styles used by a [`Diagnostic`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiagnosticStyles {
+    /// The style of errors, and their primary labels
+    pub error: Style,
+    /// The style of warnings, and their primary labels
+    pub warning: Style,
+    /// The style of notes, and their primary labels
+    pub note: Style,
+    /// The style of help messages, and their primary labels
+    pub help: Style,
+    /// The style of the message in the header
+    pub message: Style,
+    /// The style of the gutter, line numbers and the `-->` arrow
+    pub gutter: Style,
+    /// The style of secondary labels
+    pub secondary: Style,
+}
+
+impl DiagnosticStyles {
+    /// No styling at all
+    pub const PLAIN: Self = Self {
+        error: Style::new().const_into_runtime_style(),
+        warning: Style::new().const_into_runtime_style(),
+        note: Style::new().const_into_runtime_style(),
+        help: Style::new().const_into_runtime_style(),
+        message: Style::new().const_into_runtime_style(),
+        gutter: Style::new().const_into_runtime_style(),
+        secondary: Style::new().const_into_runtime_style(),
+    };
+
+    /// Styles similar to the ones used by `rustc`
+    pub const DEFAULT: Self = Self {
+        error: Style::new()
+            .fg(ansi::BrightRed)
+            .bold()
+            .const_into_runtime_style(),
+        warning: Style::new()
+            .fg(ansi::BrightYellow)
+            .bold()
+            .const_into_runtime_style(),
+        note: Style::new()
+            .fg(ansi::BrightGreen)
+            .bold()
+            .const_into_runtime_style(),
+        help: Style::new()
+            .fg(ansi::BrightCyan)
+            .bold()
+            .const_into_runtime_style(),
+        message: Style::new().bold().const_into_runtime_style(),
+        gutter: Style::new()
+            .fg(ansi::BrightBlue)
+            .bold()
+            .const_into_runtime_style(),
+        secondary: Style::new()
+            .fg(ansi::BrightBlue)
+            .bold()
+            .const_into_runtime_style(),
+    };
+
+    /// The style of a severity
+    #[inline]
+    pub const fn severity(&self, severity: Severity) -> Style {
+        match severity {
+            Severity::Error => self.error,
+            Severity::Warning => self.warning,
+            Severity::Note => self.note,
+            Severity::Help => self.help,
+        }
+    }
+}
+
+impl Default for DiagnosticStyles {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Label<'a> {
+    span: Range<usize>,
+    message: Cow<'a, str>,
+    primary: bool,
+}
+
+/// A compiler style diagnostic, see the [module docs](self) for details
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic<'a> {
+    severity: Severity,
+    message: Cow<'a, str>,
+    code: Option<Cow<'a, str>>,
+    path: Option<Cow<'a, str>>,
+    source: &'a str,
+    labels: Vec<Label<'a>>,
+    notes: Vec<(Severity, Cow<'a, str>)>,
+    styles: DiagnosticStyles,
+    curly: bool,
+    stream: Stream,
+}
+
+impl<'a> Diagnostic<'a> {
+    /// Create a diagnostic without any source or labels, written to [`Stream::Stderr`]
+    #[inline]
+    pub fn new(severity: Severity, message: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            code: None,
+            path: None,
+            source: "",
+            labels: Vec::new(),
+            notes: Vec::new(),
+            styles: DiagnosticStyles::DEFAULT,
+            curly: false,
+            stream: Stream::Stderr,
+        }
+    }
+
+    /// Set the code shown after the severity, like `E0308`
+    #[inline]
+    pub fn code(mut self, code: impl Into<Cow<'a, str>>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Set the path shown after `-->`
+    #[inline]
+    pub fn path(mut self, path: impl Into<Cow<'a, str>>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Set the source which the labels point into
+    #[inline]
+    pub const fn source(mut self, source: &'a str) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Add a primary label, which is underlined with `^` in the severity's style
+    ///
+    /// The span is a range of bytes in the [source](Self::source), and the message may be empty
+    #[inline]
+    pub fn primary(mut self, span: Range<usize>, message: impl Into<Cow<'a, str>>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+            primary: true,
+        });
+        self
+    }
+
+    /// Add a secondary label, which is underlined with `-` in the [secondary style](DiagnosticStyles::secondary)
+    ///
+    /// The span is a range of bytes in the [source](Self::source), and the message may be empty
+    #[inline]
+    pub fn secondary(mut self, span: Range<usize>, message: impl Into<Cow<'a, str>>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+            primary: false,
+        });
+        self
+    }
+
+    /// Add a `= note: ...` line at the end
+    #[inline]
+    pub fn note(mut self, message: impl Into<Cow<'a, str>>) -> Self {
+        self.notes.push((Severity::Note, message.into()));
+        self
+    }
+
+    /// Add a `= help: ...` line at the end
+    #[inline]
+    pub fn help(mut self, message: impl Into<Cow<'a, str>>) -> Self {
+        self.notes.push((Severity::Help, message.into()));
+        self
+    }
+
+    /// Set the styles to use
+    #[inline]
+    pub const fn styles(mut self, styles: DiagnosticStyles) -> Self {
+        self.styles = styles;
+        self
+    }
+
+    /// Underline labelled source with curly underlines instead of `^` and `-`, when the output is colored
+    #[inline]
+    pub const fn curly_underlines(mut self, curly: bool) -> Self {
+        self.curly = curly;
+        self
+    }
+
+    /// Set the stream the diagnostic will be written to, which decides if it is colored
+    #[inline]
+    pub const fn stream(mut self, stream: Stream) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    const fn label_style(&self, label: &Resolved<'_>) -> Style {
+        if label.primary {
+            self.styles.severity(self.severity)
+        } else {
+            self.styles.secondary
+        }
+    }
+}
+
+/// A label which has been located in the source
+struct Resolved<'a> {
+    /// The line number, starting from 0
+    line: usize,
+    /// The byte range within the line
+    bytes: Range<usize>,
+    /// The column range within the line, in visible columns
+    columns: Range<usize>,
+    message: &'a str,
+    primary: bool,
+}
+
+/// The visible width of source text, where tabs are shown as 4 spaces
+fn width(s: &str) -> usize {
+    text::visible_width(s) + 4 * s.matches('\t').count()
+}
+
+const fn digits(mut n: usize) -> usize {
+    let mut digits = 1;
+
+    while n >= 10 {
+        n /= 10;
+        digits += 1;
+    }
+
+    digits
+}
+
+fn floor_char_boundary(s: &str, mut index: usize) -> usize {
+    index = index.min(s.len());
+
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+
+    index
+}
+
+/// The line containing the byte at `index`, as its line number and byte offset
+fn line_of(source: &str, index: usize) -> (usize, usize) {
+    let before = &source[..index];
+    let line = before.matches('\n').count();
+    let start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, start)
+}
+
+fn line_text(source: &str, start: usize) -> &str {
+    let line = &source[start..];
+    let line = line.split('\n').next().unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+fn write_source(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    for (i, part) in s.split('\t').enumerate() {
+        if i != 0 {
+            f.write_str("    ")?;
+        }
+
+        f.write_str(part)?;
+    }
+
+    Ok(())
+}
+
+impl Diagnostic<'_> {
+    /// The text of a line, starting from 0
+    fn line(&self, line: usize) -> &str {
+        let text = self.source.split('\n').nth(line).unwrap_or("");
+        text.strip_suffix('\r').unwrap_or(text)
+    }
+
+    fn resolve(&self) -> Vec<Resolved<'_>> {
+        let mut labels: Vec<_> = self
+            .labels
+            .iter()
+            .map(|label| {
+                let start = floor_char_boundary(self.source, label.span.start);
+                let end = floor_char_boundary(self.source, label.span.end).max(start);
+
+                let (line, line_start) = line_of(self.source, start);
+                let text = line_text(self.source, line_start);
+
+                // a label on the `\r\n` at the end of a line points just past its text
+                let bytes_start = (start - line_start).min(text.len());
+                let bytes = bytes_start..(end - line_start).min(text.len());
+                let start_column = width(&text[..bytes.start]);
+                let end_column = width(&text[..bytes.end]).max(start_column + 1);
+
+                Resolved {
+                    line,
+                    bytes,
+                    columns: start_column..end_column,
+                    message: &label.message,
+                    primary: label.primary,
+                }
+            })
+            .collect();
+
+        labels.sort_by_key(|label| (label.line, label.columns.start));
+        labels
+    }
+
+    fn fmt_gutter(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        gutter: usize,
+        line: Option<usize>,
+    ) -> fmt::Result {
+        let style = self.styles.gutter;
+
+        match line {
+            Some(line) => write!(
+                f,
+                "{} ",
+                format_args!("{:>gutter$} |", line + 1)
+                    .style_with(style)
+                    .stream(self.stream)
+            ),
+            None => write!(
+                f,
+                "{}",
+                format_args!("{:gutter$} |", "")
+                    .style_with(style)
+                    .stream(self.stream)
+            ),
+        }
+    }
+
+    fn fmt_source_line(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        text: &str,
+        labels: &[Resolved<'_>],
+        curly: bool,
+    ) -> fmt::Result {
+        if !curly {
+            return write_source(f, text);
+        }
+
+        let mut position = 0;
+
+        for label in labels {
+            let start = label.bytes.start.max(position);
+            let end = label.bytes.end.max(start);
+
+            write_source(f, &text[position..start])?;
+
+            let color = self.label_style(label).foreground;
+            let style = Style::new()
+                .underline()
+                .underline_color(color)
+                .into_runtime_style();
+
+            write!(f, "{}\x1b[4:3m", style.apply())?;
+            write_source(f, &text[start..end])?;
+            write!(f, "{}", style.clear())?;
+
+            position = end;
+        }
+
+        write_source(f, &text[position..])
+    }
+
+    /// Write the underlines and messages of the labels on a line, like `rustc`
+    ///
+    /// The last label's message goes right after the underlines, and the other messages each
+    /// get their own line, connected to their label with `|`
+    fn fmt_labels(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        gutter: usize,
+        labels: &[Resolved<'_>],
+        curly: bool,
+    ) -> fmt::Result {
+        let Some(last) = labels.last() else {
+            return Ok(());
+        };
+
+        if curly && labels.iter().all(|label| label.message.is_empty()) {
+            return Ok(());
+        }
+
+        f.write_char('\n')?;
+        self.fmt_gutter(f, gutter, None)?;
+        f.write_char(' ')?;
+
+        let mut column = 0;
+
+        for label in labels {
+            let start = label.columns.start.max(column);
+            let len = label.columns.end.saturating_sub(start);
+            write!(f, "{:1$}", "", start - column)?;
+
+            if curly {
+                write!(f, "{:1$}", "", len)?;
+            } else {
+                let marker = if label.primary { '^' } else { '-' };
+                let markers: String = core::iter::repeat_n(marker, len).collect();
+                let style = self.label_style(label);
+                write!(f, "{}", markers.style_with(style).stream(self.stream))?;
+            }
+
+            column = start + len;
+        }
+
+        if !last.message.is_empty() {
+            let style = self.label_style(last);
+            write!(f, " {}", last.message.style_with(style).stream(self.stream))?;
+        }
+
+        let labels = &labels[..labels.len() - 1];
+
+        for (i, label) in labels.iter().enumerate().rev() {
+            if label.message.is_empty() {
+                continue;
+            }
+
+            // a line with only connectors, then a line with the message
+            for with_message in [false, true] {
+                f.write_char('\n')?;
+                self.fmt_gutter(f, gutter, None)?;
+                f.write_char(' ')?;
+
+                let mut column = 0;
+                let connectors = if with_message {
+                    &labels[..i]
+                } else {
+                    &labels[..=i]
+                };
+
+                for label in connectors.iter().filter(|label| !label.message.is_empty()) {
+                    let start = label.columns.start.max(column);
+                    write!(f, "{:1$}", "", start - column)?;
+                    write!(
+                        f,
+                        "{}",
+                        "|".style_with(self.label_style(label)).stream(self.stream)
+                    )?;
+                    column = start + 1;
+                }
+
+                if with_message {
+                    let start = label.columns.start.max(column);
+                    write!(f, "{:1$}", "", start - column)?;
+                    write!(
+                        f,
+                        "{}",
+                        label
+                            .message
+                            .style_with(self.label_style(label))
+                            .stream(self.stream)
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Diagnostic<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = self.styles.severity(self.severity);
+
+        match &self.code {
+            Some(code) => write!(
+                f,
+                "{}",
+                format_args!("{}[{code}]", self.severity)
+                    .style_with(severity)
+                    .stream(self.stream)
+            )?,
+            None => write!(
+                f,
+                "{}",
+                self.severity.style_with(severity).stream(self.stream)
+            )?,
+        }
+
+        let message = self.styles.message;
+        write!(
+            f,
+            "{}",
+            format_args!(": {}", self.message)
+                .style_with(message)
+                .stream(self.stream)
+        )?;
+
+        let labels = self.resolve();
+        let gutter = labels
+            .iter()
+            .map(|label| digits(label.line + 1))
+            .max()
+            .unwrap_or(0);
+
+        let curly = self.curly && Style::new().underline().should_color(self.stream);
+
+        if let Some(path) = &self.path {
+            let location = labels.iter().find(|label| label.primary).or(labels.first());
+
+            f.write_char('\n')?;
+            write!(
+                f,
+                "{:gutter$}{} {path}",
+                "",
+                "-->".style_with(self.styles.gutter).stream(self.stream)
+            )?;
+
+            if let Some(label) = location {
+                let text = self.line(label.line);
+                let column = text[..label.bytes.start].chars().count();
+                write!(f, ":{}:{}", label.line + 1, column + 1)?;
+            }
+        }
+
+        if !labels.is_empty() {
+            f.write_char('\n')?;
+            self.fmt_gutter(f, gutter, None)?;
+        }
+
+        let mut previous = None;
+        let mut rest = labels.as_slice();
+
+        while let Some(first) = rest.first() {
+            let line = first.line;
+            let len = rest.iter().take_while(|label| label.line == line).count();
+            let (on_line, next) = rest.split_at(len);
+            rest = next;
+
+            match previous {
+                // show a single line between labels, instead of eliding it
+                Some(previous) if line == previous + 2 => {
+                    f.write_char('\n')?;
+                    self.fmt_gutter(f, gutter, Some(previous + 1))?;
+                    write_source(f, self.line(previous + 1))?;
+                }
+                Some(previous) if line > previous + 2 => f.write_str("\n...")?,
+                _ => (),
+            }
+
+            f.write_char('\n')?;
+            self.fmt_gutter(f, gutter, Some(line))?;
+            self.fmt_source_line(f, self.line(line), on_line, curly)?;
+            self.fmt_labels(f, gutter, on_line, curly)?;
+
+            previous = Some(line);
+        }
+
+        if !self.notes.is_empty() && !labels.is_empty() {
+            f.write_char('\n')?;
+            self.fmt_gutter(f, gutter, None)?;
+        }
+
+        for (severity, note) in &self.notes {
+            let style = self.styles.message;
+            write!(
+                f,
+                "\n{:gutter$} = {}: {note}",
+                "",
+                severity.style_with(style).stream(self.stream)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::{Diagnostic, DiagnosticStyles, Severity};
+    use crate::{ansi, mode::Stream, Style};
+
+    const SOURCE: &str =
+        "one\n\ttwo\nthree\nfour\nfive\nsix 日本 seven\neight\nnine\nnine and a half\nten";
+
+    #[test]
+    fn plain() {
+        let span = |s: &str| {
+            let start = SOURCE.find(s).unwrap();
+            start..start + s.len()
+        };
+
+        let diagnostic = Diagnostic::new(Severity::Warning, "unused things")
+            .source(SOURCE)
+            .path("lib.rs")
+            .secondary(span("two"), "")
+            .primary(span("three"), "here")
+            .secondary(span("five"), "")
+            .primary(span("seven"), "and here")
+            .primary(span("ten"), "")
+            .note("a note")
+            .help("some help")
+            .stream(Stream::NeverColor);
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "\
+warning: unused things
+  --> lib.rs:3:1
+   |
+ 2 |     two
+   |     ---
+ 3 | three
+   | ^^^^^ here
+ 4 | four
+ 5 | five
+   | ----
+ 6 | six 日本 seven
+   |          ^^^^^ and here
+...
+10 | ten
+   | ^^^
+   |
+   = note: a note
+   = help: some help"
+        );
+
+        let diagnostic = Diagnostic::new(Severity::Error, "no source")
+            .note("a note")
+            .stream(Stream::NeverColor);
+        assert_eq!(diagnostic.to_string(), "error: no source\n = note: a note");
+    }
+
+    #[test]
+    fn overlapping() {
+        let diagnostic = Diagnostic::new(Severity::Error, "many labels")
+            .source("let a = b + c;")
+            .primary(8..13, "whole")
+            .secondary(4..5, "a")
+            .secondary(8..9, "")
+            .secondary(12..13, "c")
+            .primary(14..20, "past the end")
+            .stream(Stream::NeverColor);
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "\
+error: many labels
+  |
+1 | let a = b + c;
+  |     -   ^^^^^ ^ past the end
+  |     |   |   |
+  |     |   |   c
+  |     |   |
+  |     |   whole
+  |     |
+  |     a"
+        );
+    }
+
+    #[test]
+    fn crlf() {
+        let diagnostic = Diagnostic::new(Severity::Error, "expected `;`")
+            .source("ab\r\ncd")
+            .primary(3..4, "here")
+            .stream(Stream::NeverColor);
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "\
+error: expected `;`
+  |
+1 | ab
+  |   ^ here"
+        );
+
+        let diagnostic = Diagnostic::new(Severity::Error, "crlf")
+            .source("ab\r\ncd\r\n")
+            .primary(4..6, "second line")
+            .stream(Stream::NeverColor);
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: crlf\n  |\n2 | cd\n  | ^^ second line"
+        );
+    }
+
+    #[test]
+    fn styled() {
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        let styles = DiagnosticStyles {
+            error: Style::new().fg(ansi::Red).into_runtime_style(),
+            gutter: Style::new().fg(ansi::Blue).into_runtime_style(),
+            ..DiagnosticStyles::PLAIN
+        };
+
+        let diagnostic = Diagnostic::new(Severity::Error, "oops")
+            .source("abc")
+            .primary(1..2, "b")
+            .styles(styles)
+            .stream(Stream::AlwaysColor);
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "\
+\x1b[31merror\x1b[39m: oops
+\x1b[34m  |\x1b[39m
+\x1b[34m1 |\x1b[39m abc
+\x1b[34m  |\x1b[39m  \x1b[31m^\x1b[39m \x1b[31mb\x1b[39m"
+        );
+
+        let diagnostic = diagnostic.curly_underlines(true);
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "\
+\x1b[31merror\x1b[39m: oops
+\x1b[34m  |\x1b[39m
+\x1b[34m1 |\x1b[39m a\x1b[4;58;5;1m\x1b[4:3mb\x1b[24;59mc
+\x1b[34m  |\x1b[39m    \x1b[31mb\x1b[39m"
+        );
+    }
+}
@@ -0,0 +1,797 @@
+//! Colored line diffs with word-level highlighting
+//!
+//! [`Diff`] compares two texts line by line, and renders the changes as a unified diff (the
+//! default) or side by side. Removed and added lines are tinted with the [`DiffStyles`], and when
+//! a removed line is paired up with an added line, the words which changed are highlighted more
+//! strongly.
+//!
+//! ```rust
+//! use colorz::diff::Diff;
+//!
+//! let old = "fn main() {\n    println!(\"hello\");\n}";
+//! let new = "fn main() {\n    println!(\"hello world\");\n}";
+//!
+//! println!("{}", Diff::new(old, new));
+//! ```
+//!
+//! When the styles aren't colored (for example when the output isn't a terminal), lines are
+//! still marked with `-` and `+`, and changed words are put in brackets.
+//!
+//! ```rust
+//! use colorz::{diff::Diff, mode::Stream};
+//!
+//! let diff = Diff::new("a\nb = 1\nc", "a\nb = 2\nc").stream(Stream::NeverColor);
+//!
+//! assert_eq!(diff.to_string(), "@@ -1,3 +1,3 @@\n a\n-b = [1]\n+b = [2]\n c");
+//! ```
+
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::{self, Display, Write};
+
+use crate::{mode::Stream, text, xterm, Style};
+
+/// The styles used by a [`Diff`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiffStyles {
+    /// The style of removed lines
+    pub removed: Style,
+    /// The style of added lines
+    pub added: Style,
+    /// The style of changed words in removed lines
+    pub removed_word: Style,
+    /// The style of changed words in added lines
+    pub added_word: Style,
+    /// The style of unchanged lines
+    pub context: Style,
+    /// The style of hunk headers (`@@ -1,3 +1,3 @@`)
+    pub hunk: Style,
+}
+
+impl DiffStyles {
+    /// No styling at all
+    pub const PLAIN: Self = Self {
+        removed: Style::new().const_into_runtime_style(),
+        added: Style::new().const_into_runtime_style(),
+        removed_word: Style::new().const_into_runtime_style(),
+        added_word: Style::new().const_into_runtime_style(),
+        context: Style::new().const_into_runtime_style(),
+        hunk: Style::new().const_into_runtime_style(),
+    };
+
+    /// Dark red and green background tints, with brighter tints for changed words
+    pub const DEFAULT: Self = Self {
+        removed: Style::new()
+            .bg(xterm::Firebrick4)
+            .const_into_runtime_style(),
+        added: Style::new().bg(xterm::DarkGreen).const_into_runtime_style(),
+        removed_word: Style::new().bg(xterm::Red4).const_into_runtime_style(),
+        added_word: Style::new().bg(xterm::Green4).const_into_runtime_style(),
+        context: Style::new().const_into_runtime_style(),
+        hunk: Style::new()
+            .fg(crate::ansi::Cyan)
+            .const_into_runtime_style(),
+    };
+}
+
+impl Default for DiffStyles {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A diff between two texts, see the [module docs](self) for details
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diff<'a> {
+    old: Vec<&'a str>,
+    new: Vec<&'a str>,
+    styles: DiffStyles,
+    context: usize,
+    width: Option<usize>,
+    stream: Option<Stream>,
+}
+
+impl<'a> Diff<'a> {
+    /// Compare two texts line by line
+    #[inline]
+    pub fn new(old: &'a str, new: &'a str) -> Self {
+        Self::from_lines(old.lines(), new.lines())
+    }
+
+    /// Compare two sequences of lines
+    #[inline]
+    pub fn from_lines<O, N>(old: O, new: N) -> Self
+    where
+        O: IntoIterator<Item = &'a str>,
+        N: IntoIterator<Item = &'a str>,
+    {
+        Self {
+            old: old.into_iter().collect(),
+            new: new.into_iter().collect(),
+            styles: DiffStyles::DEFAULT,
+            context: 3,
+            width: None,
+            stream: None,
+        }
+    }
+
+    /// Set the styles to use
+    #[inline]
+    pub const fn styles(mut self, styles: DiffStyles) -> Self {
+        self.styles = styles;
+        self
+    }
+
+    /// Set the number of unchanged lines shown around each change (3 by default)
+    #[inline]
+    pub const fn context(mut self, context: usize) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Show removed and added lines one after the other, marked with `-` and `+` (the default)
+    #[inline]
+    pub const fn unified(mut self) -> Self {
+        self.width = None;
+        self
+    }
+
+    /// Show the old text on the left and the new text on the right, in `width` columns in total
+    ///
+    /// Lines which don't fit in their half are truncated with `…`
+    #[inline]
+    pub const fn side_by_side(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Set the stream used to decide if the diff is colored
+    #[inline]
+    pub const fn stream(mut self, stream: Stream) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    fn paint(&self, style: Style) -> Option<Style> {
+        if !style.is_plain() && style.should_color(self.stream) {
+            Some(style)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Removed,
+    Added,
+}
+
+/// The shortest edit script from `old` to `new`, found with Myers' linear space algorithm
+fn edits<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Op> {
+    let mut ops = Vec::with_capacity(old.len().max(new.len()));
+    diff_into(old, new, &mut ops);
+    ops
+}
+
+/// Push the edit script from `old` to `new`, splitting it at a middle snake until one side is empty
+fn diff_into<T: PartialEq>(old: &[T], new: &[T], ops: &mut Vec<Op>) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    ops.extend(core::iter::repeat_n(Op::Equal, prefix));
+
+    if a.is_empty() || b.is_empty() {
+        ops.extend(core::iter::repeat_n(Op::Removed, a.len()));
+        ops.extend(core::iter::repeat_n(Op::Added, b.len()));
+    } else {
+        // the ends differ, so there are at least two edits and both halves are smaller
+        let (x, y, u, v) = middle_snake(a, b);
+        diff_into(&a[..x], &b[..y], ops);
+        ops.extend(core::iter::repeat_n(Op::Equal, u - x));
+        diff_into(&a[u..], &b[v..], ops);
+    }
+
+    ops.extend(core::iter::repeat_n(Op::Equal, suffix));
+}
+
+/// Find a run of equal items `a[x..u] == b[y..v]` in the middle of a shortest edit script, by
+/// searching forwards from the start and backwards from the end until the paths overlap
+///
+/// Both slices must be non-empty.
+fn middle_snake<T: PartialEq>(a: &[T], b: &[T]) -> (usize, usize, usize, usize) {
+    let (n, m) = (a.len(), b.len());
+    let max = (n + m).div_ceil(2) + 1;
+    let delta = n as isize - m as isize;
+    let odd = delta % 2 != 0;
+
+    // forward[k] is the furthest x reached on diagonal x - y = k from the start, and backward[k]
+    // is the furthest distance from the end reached on diagonal (n - x) - (m - y) = k
+    let mut forward = vec![0_usize; 2 * max + 1];
+    let mut backward = vec![0_usize; 2 * max + 1];
+    let at = |k: isize| (k + max as isize) as usize;
+
+    for d in 0..max as isize {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+            let (x0, y0) = (x, y);
+
+            while x < n && y < m && a[x] == b[y] {
+                x += 1;
+                y += 1;
+            }
+
+            forward[at(k)] = x;
+
+            let r = delta - k;
+            if odd && (-(d - 1)..=d - 1).contains(&r) && x + backward[at(r)] >= n {
+                return (x0, y0, x, y);
+            }
+        }
+
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+            let (x0, y0) = (x, y);
+
+            while x < n && y < m && a[n - x - 1] == b[m - y - 1] {
+                x += 1;
+                y += 1;
+            }
+
+            backward[at(k)] = x;
+
+            let f = delta - k;
+            if !odd && (-d..=d).contains(&f) && forward[at(f)] + x >= n {
+                return (n - x, m - y, n - x0, m - y0);
+            }
+        }
+    }
+
+    // a path of length n + m always exists, so the searches meet before d reaches max
+    (0, 0, 0, 0)
+}
+
+/// Split a line into words, runs of whitespace, and single punctuation characters
+fn tokens(line: &str) -> Vec<&str> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut tokens = Vec::new();
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        let len = if is_word(c) {
+            rest.find(|c| !is_word(c)).unwrap_or(rest.len())
+        } else if c.is_whitespace() {
+            rest.find(|c: char| !c.is_whitespace())
+                .unwrap_or(rest.len())
+        } else {
+            c.len_utf8()
+        };
+
+        let (token, next) = rest.split_at(len);
+        tokens.push(token);
+        rest = next;
+    }
+
+    tokens
+}
+
+/// Runs of unchanged (`false`) and changed (`true`) text in a line
+type Parts<'a> = Vec<(bool, &'a str)>;
+
+/// Merge the tokens of `line` into runs of unchanged and changed text, where whitespace between
+/// two changed tokens is also treated as changed
+fn parts<'a>(line: &'a str, tokens: &[&str], mut changed: Vec<bool>) -> Parts<'a> {
+    for i in 1..changed.len().saturating_sub(1) {
+        if !changed[i]
+            && changed[i - 1]
+            && changed[i + 1]
+            && tokens[i].chars().all(char::is_whitespace)
+        {
+            changed[i] = true;
+        }
+    }
+
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut end = 0;
+
+    // tokens are adjacent slices of the line, so runs are found by adding up their lengths
+    for (i, token) in tokens.iter().enumerate() {
+        if i != 0 && changed[i] != changed[i - 1] {
+            parts.push((changed[i - 1], &line[start..end]));
+            start = end;
+        }
+
+        end += token.len();
+    }
+
+    if let Some(&last) = changed.last() {
+        parts.push((last, &line[start..end]));
+    }
+
+    parts
+}
+
+/// Find the changed words between a removed line and an added line
+///
+/// Returns `None` if the lines have no words in common, since highlighting every word
+/// wouldn't add anything to the line styles
+fn word_diff<'a>(old: &'a str, new: &'a str) -> Option<(Parts<'a>, Parts<'a>)> {
+    let old_tokens = tokens(old);
+    let new_tokens = tokens(new);
+    let ops = edits(&old_tokens, &new_tokens);
+
+    let mut old_changed = Vec::with_capacity(old_tokens.len());
+    let mut new_changed = Vec::with_capacity(new_tokens.len());
+    let mut common = false;
+
+    for op in ops {
+        match op {
+            Op::Equal => {
+                let token = old_tokens[old_changed.len()];
+                common |= !token.chars().all(char::is_whitespace);
+                old_changed.push(false);
+                new_changed.push(false);
+            }
+            Op::Removed => old_changed.push(true),
+            Op::Added => new_changed.push(true),
+        }
+    }
+
+    if !common {
+        return None;
+    }
+
+    Some((
+        parts(old, &old_tokens, old_changed),
+        parts(new, &new_tokens, new_changed),
+    ))
+}
+
+/// A line in the edit script, along with the number of old and new lines before it
+#[derive(Debug, Clone, Copy)]
+struct Line {
+    op: Op,
+    old: usize,
+    new: usize,
+}
+
+/// The ranges of lines which are shown, each change with `context` lines around it
+fn hunks(lines: &[Line], context: usize) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if line.op == Op::Equal {
+            continue;
+        }
+
+        let start = i.saturating_sub(context);
+        let end = i.saturating_add(context).saturating_add(1).min(lines.len());
+
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    hunks
+}
+
+fn newline(f: &mut fmt::Formatter<'_>, first: &mut bool) -> fmt::Result {
+    if core::mem::take(first) {
+        Ok(())
+    } else {
+        f.write_char('\n')
+    }
+}
+
+impl Diff<'_> {
+    fn fmt_hunk_header(&self, f: &mut fmt::Formatter<'_>, lines: &[Line]) -> fmt::Result {
+        let old_len = lines.iter().filter(|line| line.op != Op::Added).count();
+        let new_len = lines.iter().filter(|line| line.op != Op::Removed).count();
+
+        // empty ranges start at the line before them
+        let old_start = lines[0].old + usize::from(old_len != 0);
+        let new_start = lines[0].new + usize::from(new_len != 0);
+
+        let style = self.paint(self.styles.hunk);
+
+        if let Some(style) = style {
+            style.apply().fmt(f)?;
+        }
+
+        write!(f, "@@ -{old_start},{old_len} +{new_start},{new_len} @@")?;
+
+        if let Some(style) = style {
+            style.clear().fmt(f)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a single line, truncated and padded to `width` columns if it's given
+    fn fmt_line(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        marker: char,
+        (line_style, word_style): (Style, Style),
+        parts: &[(bool, &str)],
+        width: Option<usize>,
+        pad: bool,
+    ) -> fmt::Result {
+        let line_style = self.paint(line_style);
+        let word_style = self.paint(word_style);
+
+        let mut content = String::new();
+        content.push(marker);
+
+        for &(changed, text) in parts {
+            if !changed {
+                content.push_str(text);
+            } else if let Some(word_style) = word_style {
+                write!(
+                    content,
+                    "{}{text}{}",
+                    word_style.apply(),
+                    word_style.clear()
+                )?;
+
+                if let Some(line_style) = line_style {
+                    write!(content, "{}", line_style.apply())?;
+                }
+            } else {
+                write!(content, "[{text}]")?;
+            }
+        }
+
+        let content = match width {
+            Some(width) => text::truncate(&content, width, '…'),
+            None => content.into(),
+        };
+
+        let padding = match width {
+            Some(width) if pad => width.saturating_sub(text::visible_width(&content)),
+            _ => 0,
+        };
+
+        match line_style {
+            Some(line_style) => write!(
+                f,
+                "{}{content}{:padding$}{}",
+                line_style.apply(),
+                "",
+                line_style.clear()
+            ),
+            None => write!(f, "{content}{:padding$}", ""),
+        }
+    }
+
+    fn fmt_block(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        first: &mut bool,
+        removed: &[&str],
+        added: &[&str],
+    ) -> fmt::Result {
+        let words: Vec<_> = removed
+            .iter()
+            .zip(added)
+            .map(|(old, new)| word_diff(old, new))
+            .collect();
+
+        let removed_styles = (self.styles.removed, self.styles.removed_word);
+        let added_styles = (self.styles.added, self.styles.added_word);
+
+        let Some(width) = self.width else {
+            for (i, &line) in removed.iter().enumerate() {
+                let parts = match words.get(i) {
+                    Some(Some((parts, _))) => parts.as_slice(),
+                    _ => &[(false, line)],
+                };
+
+                newline(f, first)?;
+                self.fmt_line(f, '-', removed_styles, parts, None, false)?;
+            }
+
+            for (i, &line) in added.iter().enumerate() {
+                let parts = match words.get(i) {
+                    Some(Some((_, parts))) => parts.as_slice(),
+                    _ => &[(false, line)],
+                };
+
+                newline(f, first)?;
+                self.fmt_line(f, '+', added_styles, parts, None, false)?;
+            }
+
+            return Ok(());
+        };
+
+        let column = width.saturating_sub(3) / 2;
+
+        for i in 0..removed.len().max(added.len()) {
+            newline(f, first)?;
+
+            match removed.get(i) {
+                Some(&line) => {
+                    let parts = match words.get(i) {
+                        Some(Some((parts, _))) => parts.as_slice(),
+                        _ => &[(false, line)],
+                    };
+
+                    self.fmt_line(f, '-', removed_styles, parts, Some(column), true)?;
+                }
+                None => write!(f, "{:column$}", "")?,
+            }
+
+            f.write_str(" │ ")?;
+
+            if let Some(&line) = added.get(i) {
+                let parts = match words.get(i) {
+                    Some(Some((_, parts))) => parts.as_slice(),
+                    _ => &[(false, line)],
+                };
+
+                // only pad the right side if it's tinted, to avoid trailing whitespace
+                let pad = self.paint(self.styles.added).is_some();
+                self.fmt_line(f, '+', added_styles, parts, Some(column), pad)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fmt_context(&self, f: &mut fmt::Formatter<'_>, line: &str) -> fmt::Result {
+        let styles = (self.styles.context, self.styles.context);
+        let parts = &[(false, line)];
+
+        match self.width {
+            None => self.fmt_line(f, ' ', styles, parts, None, false),
+            Some(width) => {
+                let column = width.saturating_sub(3) / 2;
+                self.fmt_line(f, ' ', styles, parts, Some(column), true)?;
+                f.write_str(" │ ")?;
+                self.fmt_line(f, ' ', styles, parts, Some(column), false)
+            }
+        }
+    }
+}
+
+impl Display for Diff<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = Vec::new();
+        let (mut old, mut new) = (0, 0);
+
+        for op in edits(&self.old, &self.new) {
+            lines.push(Line { op, old, new });
+
+            match op {
+                Op::Equal => {
+                    old += 1;
+                    new += 1;
+                }
+                Op::Removed => old += 1,
+                Op::Added => new += 1,
+            }
+        }
+
+        let mut first = true;
+
+        for (start, end) in hunks(&lines, self.context) {
+            newline(f, &mut first)?;
+            self.fmt_hunk_header(f, &lines[start..end])?;
+
+            let mut i = start;
+
+            while i < end {
+                let line = lines[i];
+
+                if line.op == Op::Equal {
+                    newline(f, &mut first)?;
+                    self.fmt_context(f, self.old[line.old])?;
+                    i += 1;
+                    continue;
+                }
+
+                let block_end = lines[i..end]
+                    .iter()
+                    .position(|line| line.op == Op::Equal)
+                    .map_or(end, |len| i + len);
+
+                let block = &lines[i..block_end];
+                let removed: Vec<_> = block
+                    .iter()
+                    .filter(|line| line.op == Op::Removed)
+                    .map(|line| self.old[line.old])
+                    .collect();
+                let added: Vec<_> = block
+                    .iter()
+                    .filter(|line| line.op == Op::Added)
+                    .map(|line| self.new[line.new])
+                    .collect();
+
+                self.fmt_block(f, &mut first, &removed, &added)?;
+                i = block_end;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{string::ToString, vec, vec::Vec};
+
+    use super::{edits, Diff, DiffStyles, Op};
+    use crate::mode::Stream;
+
+    #[test]
+    fn unified() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj";
+
+        let diff = Diff::new(old, new).context(1).stream(Stream::NeverColor);
+
+        assert_eq!(
+            diff.to_string(),
+            "\
+@@ -1,3 +1,3 @@
+ a
+-b
++B
+ c
+@@ -9,1 +9,2 @@
+ i
++j"
+        );
+
+        let diff = Diff::new("", "a").stream(Stream::NeverColor);
+        assert_eq!(diff.to_string(), "@@ -0,0 +1,1 @@\n+a");
+
+        let diff = Diff::new("a\nb", "a\nb").stream(Stream::NeverColor);
+        assert_eq!(diff.to_string(), "");
+    }
+
+    #[test]
+    fn shortest_edits() {
+        let lcs = |a: &[u8], b: &[u8]| {
+            let mut lengths = vec![vec![0; b.len() + 1]; a.len() + 1];
+            for i in 0..a.len() {
+                for j in 0..b.len() {
+                    lengths[i + 1][j + 1] = if a[i] == b[j] {
+                        lengths[i][j] + 1
+                    } else {
+                        lengths[i][j + 1].max(lengths[i + 1][j])
+                    };
+                }
+            }
+            lengths[a.len()][b.len()]
+        };
+
+        let mut seed = 1_u32;
+        let mut next = |len: u32| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 16) % len
+        };
+
+        for _ in 0..500 {
+            let a: Vec<u8> = (0..next(12)).map(|_| next(3) as u8).collect();
+            let b: Vec<u8> = (0..next(12)).map(|_| next(3) as u8).collect();
+            let ops = edits(&a, &b);
+
+            // the script turns `a` into `b`, keeping a longest common subsequence
+            let (mut i, mut result) = (0, Vec::new());
+            for op in &ops {
+                match op {
+                    Op::Equal => {
+                        assert_eq!(a[i], b[result.len()], "{a:?} -> {b:?}");
+                        result.push(a[i]);
+                        i += 1;
+                    }
+                    Op::Removed => i += 1,
+                    Op::Added => result.push(b[result.len()]),
+                }
+            }
+
+            assert_eq!((i, &result), (a.len(), &b));
+            let equal = ops.iter().filter(|&&op| op == Op::Equal).count();
+            assert_eq!(equal, lcs(&a, &b), "{a:?} -> {b:?}");
+        }
+
+        // long inputs with few changes don't need a table of every pair of lines
+        let a: Vec<u32> = (0..200_000).collect();
+        let b: Vec<u32> = (0..200_000)
+            .map(|i| if i % 50_000 == 7 { 0 } else { i })
+            .collect();
+        let ops = edits(&a, &b);
+        assert_eq!(ops.iter().filter(|&&op| op == Op::Removed).count(), 4);
+        assert_eq!(ops.iter().filter(|&&op| op == Op::Added).count(), 4);
+    }
+
+    #[test]
+    fn words() {
+        let diff = Diff::new(
+            "let x = foo(1, 2);\nold line\nremoved",
+            "let y = foo(1, 3);\nsomething else",
+        )
+        .stream(Stream::NeverColor);
+
+        // whitespace between changed words is part of the change, and lines without any words in
+        // common aren't highlighted
+        assert_eq!(
+            diff.to_string(),
+            "\
+@@ -1,3 +1,2 @@
+-let [x] = foo(1, [2]);
+-old line
+-removed
++let [y] = foo(1, [3]);
++something else"
+        );
+
+        let diff = Diff::new("a b c d", "a x y d").stream(Stream::NeverColor);
+        assert_eq!(diff.to_string(), "@@ -1,1 +1,1 @@\n-a [b c] d\n+a [x y] d");
+    }
+
+    #[test]
+    fn side_by_side() {
+        let diff = Diff::new("same\nold value\ngone", "same\nnew value")
+            .side_by_side(27)
+            .stream(Stream::NeverColor);
+
+        assert_eq!(
+            diff.to_string(),
+            "\
+@@ -1,3 +1,2 @@
+ same        │  same
+-[old] value │ +[new] value
+-gone        │ "
+        );
+
+        let diff = Diff::new("a long line of text", "a long line of words")
+            .side_by_side(23)
+            .stream(Stream::NeverColor);
+
+        assert_eq!(diff.to_string(), "@@ -1,1 +1,1 @@\n-a long l… │ +a long l…");
+    }
+
+    #[test]
+    fn styled() {
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        let diff = Diff::new("a = 1", "a = 2").stream(Stream::AlwaysColor);
+
+        assert_eq!(
+            diff.to_string(),
+            "\
+\x1b[36m@@ -1,1 +1,1 @@\x1b[39m
+\x1b[48;5;52m-a = \x1b[48;5;124m1\x1b[49m\x1b[48;5;52m\x1b[49m
+\x1b[48;5;22m+a = \x1b[48;5;28m2\x1b[49m\x1b[48;5;22m\x1b[49m"
+        );
+
+        let diff = diff.styles(DiffStyles::PLAIN);
+        assert_eq!(diff.to_string(), "@@ -1,1 +1,1 @@\n-a = [1]\n+a = [2]");
+    }
+}
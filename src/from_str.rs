@@ -1,6 +1,6 @@
 use core::str::FromStr;
 
-use crate::{ansi::AnsiColor, Color};
+use crate::{ansi::AnsiColor, css::CssColor, Color, Effect, Style};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// An error type for parsing colors
@@ -13,6 +13,66 @@ pub enum ParseColorError {
     UnknownColor,
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for ParseColorError {}
+
+impl core::fmt::Display for ParseColorError {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::InvalidHexDigit => "Invalid color: invalid hex digit",
+            Self::U8Overflow => "Invalid color: xterm color codes must be in the range 0..=255",
+            Self::UnknownColor => "Invalid color: unknown color",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An error type for parsing effects
+pub struct ParseEffectError;
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseEffectError {}
+
+impl core::fmt::Display for ParseEffectError {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Invalid effect: valid options include ")?;
+
+        for (i, effect) in crate::EffectFlags::all().iter().enumerate() {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+
+            write!(f, "{:?}", effect.name())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An error type for parsing styles
+pub enum ParseStyleError {
+    /// An item which isn't an effect, a color, or a `fg:`, `bg:` or `ul:` prefixed color
+    UnknownItem,
+    /// An invalid color after a `fg:`, `bg:` or `ul:` prefix
+    InvalidColor(ParseColorError),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseStyleError {}
+
+impl core::fmt::Display for ParseStyleError {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownItem => f.write_str("Invalid style: expected an effect or a color"),
+            Self::InvalidColor(err) => err.fmt(f),
+        }
+    }
+}
+
 #[inline(always)]
 const fn parse_hex_digit(x: u8) -> Result<u8, ParseColorError> {
     match x {
@@ -48,7 +108,9 @@ impl FromStr for Color {
                 })
             }
             &[a @ b'0'..=b'9'] => Self::Xterm((a - b'0').into()),
-            &[a @ b'0'..=b'9', b @ b'0'..=b'9'] => Self::Xterm(((a - b'0') * 10 + b).into()),
+            &[a @ b'0'..=b'9', b @ b'0'..=b'9'] => {
+                Self::Xterm(((a - b'0') * 10 + (b - b'0')).into())
+            }
             &[a @ b'0'..=b'1', b @ b'0'..=b'9', c @ b'0'..=b'9']
             | &[a @ b'2', b @ b'0'..=b'4', c @ b'0'..=b'9']
             | &[a @ b'2', b @ b'5', c @ b'0'..=b'5'] => {
@@ -73,7 +135,149 @@ impl FromStr for Color {
             b"bright magenta" => Self::Ansi(AnsiColor::BrightMagenta),
             b"bright cyan" => Self::Ansi(AnsiColor::BrightCyan),
             b"bright white" => Self::Ansi(AnsiColor::BrightWhite),
-            _ => return Err(ParseColorError::UnknownColor),
+            b"default" => Self::Ansi(AnsiColor::Default),
+            _ => match CssColor::from_name(s) {
+                Some(color) => Self::Css(color),
+                None => return Err(ParseColorError::UnknownColor),
+            },
         })
     }
 }
+
+impl FromStr for Effect {
+    type Err = ParseEffectError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        crate::EffectFlags::all()
+            .iter()
+            .find(|effect| effect.name() == s)
+            .ok_or(ParseEffectError)
+    }
+}
+
+const fn brighten(color: AnsiColor) -> Option<AnsiColor> {
+    Some(match color {
+        AnsiColor::Black => AnsiColor::BrightBlack,
+        AnsiColor::Red => AnsiColor::BrightRed,
+        AnsiColor::Green => AnsiColor::BrightGreen,
+        AnsiColor::Yellow => AnsiColor::BrightYellow,
+        AnsiColor::Blue => AnsiColor::BrightBlue,
+        AnsiColor::Magenta => AnsiColor::BrightMagenta,
+        AnsiColor::Cyan => AnsiColor::BrightCyan,
+        AnsiColor::White => AnsiColor::BrightWhite,
+        _ => return None,
+    })
+}
+
+impl FromStr for Style {
+    type Err = ParseStyleError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut style = Style::new().into_runtime_style();
+        let mut words = s.split_ascii_whitespace();
+
+        while let Some(word) = words.next() {
+            let (slot, name) = match word.split_once(':') {
+                Some(("fg", name)) => (&mut style.foreground, name),
+                Some(("bg", name)) => (&mut style.background, name),
+                Some(("ul", name)) => (&mut style.underline_color, name),
+                Some(_) => return Err(ParseStyleError::UnknownItem),
+                None => match word.parse::<Effect>() {
+                    Ok(effect) => {
+                        style.effects.set(effect);
+                        continue;
+                    }
+                    Err(ParseEffectError) => (&mut style.foreground, word),
+                },
+            };
+
+            let color = if name == "bright" {
+                // `bright blue` is split across two words
+                match words.next().map(Color::from_str) {
+                    Some(Ok(Color::Ansi(color))) => brighten(color)
+                        .map(Color::Ansi)
+                        .ok_or(ParseColorError::UnknownColor),
+                    Some(Ok(_)) | None => Err(ParseColorError::UnknownColor),
+                    Some(Err(err)) => Err(err),
+                }
+            } else {
+                name.parse::<Color>()
+            };
+
+            *slot = Some(color.map_err(|err| {
+                if word.contains(':') {
+                    ParseStyleError::InvalidColor(err)
+                } else {
+                    ParseStyleError::UnknownItem
+                }
+            })?);
+        }
+
+        Ok(style)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ansi::AnsiColor, xterm::XtermColor, Color, ParseColorError, ParseStyleError, Style,
+    };
+
+    #[test]
+    fn xterm_codes() {
+        for code in 0..=255u8 {
+            let mut buf = [0; 3];
+            let digits = [code / 100, code / 10 % 10, code % 10];
+            let start = digits.iter().position(|&d| d != 0).unwrap_or(2);
+
+            for (b, d) in buf.iter_mut().zip(digits) {
+                *b = b'0' + d;
+            }
+
+            let s = core::str::from_utf8(&buf[start..]).unwrap();
+            assert_eq!(s.parse(), Ok(Color::Xterm(XtermColor::from_code(code))));
+        }
+
+        assert_eq!("256".parse::<Color>(), Err(ParseColorError::U8Overflow));
+    }
+
+    #[test]
+    fn style() {
+        let parse = |s: &str| s.parse::<Style>();
+
+        assert_eq!(parse(""), Ok(Style::new().into_runtime_style()));
+        assert_eq!(
+            parse("  bright  red\ton bold "),
+            Err(ParseStyleError::UnknownItem)
+        );
+        assert_eq!(
+            parse("  bright  red\tbold "),
+            Ok(Style::new()
+                .fg(Color::Ansi(AnsiColor::BrightRed))
+                .bold()
+                .into_runtime_style())
+        );
+        assert_eq!(
+            parse("red blue"),
+            Ok(Style::new()
+                .fg(Color::Ansi(AnsiColor::Blue))
+                .into_runtime_style())
+        );
+        assert_eq!(parse("sparkle"), Err(ParseStyleError::UnknownItem));
+        assert_eq!(parse("bright"), Err(ParseStyleError::UnknownItem));
+        assert_eq!(parse("bright bold"), Err(ParseStyleError::UnknownItem));
+        assert_eq!(
+            parse("bg:bright"),
+            Err(ParseStyleError::InvalidColor(ParseColorError::UnknownColor))
+        );
+        assert_eq!(
+            parse("ul:#12345g"),
+            Err(ParseStyleError::InvalidColor(
+                ParseColorError::InvalidHexDigit
+            ))
+        );
+        assert_eq!(parse("xx:red"), Err(ParseStyleError::UnknownItem));
+    }
+}
@@ -7,8 +7,8 @@
     clippy::missing_const_for_fn,
     clippy::missing_inline_in_public_items
 )]
-#![cfg_attr(doc, warn(rustdoc::missing_doc_code_examples))]
-#![cfg_attr(doc, feature(doc_cfg, rustdoc_missing_doc_code_examples))]
+#![cfg_attr(docsrs, warn(rustdoc::missing_doc_code_examples))]
+#![cfg_attr(docsrs, feature(doc_cfg, rustdoc_missing_doc_code_examples))]
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
@@ -19,14 +19,53 @@ extern crate std;
 pub mod xterm;
 
 pub mod ansi;
+pub mod cli;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod console;
+pub mod control;
 pub mod css;
+pub mod debug;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod diagnostic;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod diff;
 mod from_str;
+#[cfg(any(feature = "log", feature = "tracing-subscriber"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "log", feature = "tracing-subscriber"))))]
+pub mod logging;
+#[cfg(feature = "macros")]
+mod macros;
 pub mod mode;
+pub mod progress;
 pub mod rgb;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod string;
 mod style;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod table;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod testing;
+pub mod text;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod theme;
 mod value;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod wrap;
 
-pub use from_str::ParseColorError;
+pub use from_str::{ParseColorError, ParseEffectError, ParseStyleError};
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub use macros::__private;
 
 /// A styled value, created from [`Colorize`] or [`StyledValue::new`]
 ///
@@ -53,7 +92,7 @@ pub struct StyledValue<T, F = NoColor, B = NoColor, U = NoColor> {
 impl<T: ?Sized> Colorize for T {}
 pub use value::Colorize;
 
-pub use style::{Effect, EffectFlags, EffectFlagsIter, Style};
+pub use style::{Effect, EffectFlags, EffectFlagsIter, Style, StyleEscape};
 
 /// A no color placeholder type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -68,16 +107,19 @@ pub struct NoColor;
 /// * [0-9]{1,3} will parse to a `Color::Xterm` color code. Only supports values in the range 0..=255
 /// * `#xx` or `#x` - where each `x` is a hex character. This will parse to `Color::Xterm` color code,
 /// * the name of any ANSI color code case sensitive,  i.e. `red` or `bright blue` will parse to `Color::Ansi`
-///
-/// There isn't a way to parse to a `CssColor` at this time.
+///   (see [`AnsiColor::name`](ansi::AnsiColor::name)), and `default` will parse to the default color
+/// * the name of any CSS color case sensitive, i.e. `AliceBlue` or `Red` will parse to `Color::Css`
+///   (see [`CssColor::name`](css::CssColor::name))
 ///
 /// ```
-/// use colorz::{Color, xterm, ansi, rgb};
+/// use colorz::{Color, css, xterm, ansi, rgb};
 ///
 /// assert_eq!("#ff".parse::<Color>(), Ok(Color::Xterm(xterm::XtermColor::from_code(0xff))));
 /// assert_eq!("red".parse::<Color>(), Ok(Color::Ansi(ansi::AnsiColor::Red)));
 /// assert_eq!("bright blue".parse::<Color>(), Ok(Color::Ansi(ansi::AnsiColor::BrightBlue)));
 /// assert_eq!("#abcdef".parse::<Color>(), Ok(Color::Rgb(rgb::RgbColor { red: 0xab, green: 0xcd, blue: 0xef })));
+/// assert_eq!("42".parse::<Color>(), Ok(Color::Xterm(xterm::XtermColor::from_code(42))));
+/// assert_eq!("Red".parse::<Color>(), Ok(Color::Css(css::CssColor::Red)));
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Color {
@@ -171,6 +213,21 @@ impl<C: ColorSpec> WriteColor for C {
     fn fmt_underline(self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_str(self.underline_escape())
     }
+
+    #[inline]
+    fn push_foreground_args(self, escape: &mut StyleEscape) {
+        escape.push_arg(self.foreground_args())
+    }
+
+    #[inline]
+    fn push_background_args(self, escape: &mut StyleEscape) {
+        escape.push_arg(self.background_args())
+    }
+
+    #[inline]
+    fn push_underline_args(self, escape: &mut StyleEscape) {
+        escape.push_arg(self.underline_args())
+    }
 }
 
 /// A sealed trait for describing how to write ANSI color args
@@ -215,6 +272,24 @@ pub trait WriteColor: seal::Seal {
         self.fmt_underline_args(f)?;
         f.write_str("m")
     }
+
+    #[doc(hidden)]
+    #[inline]
+    fn push_foreground_args(self, escape: &mut StyleEscape) {
+        escape.write_args(self, Self::fmt_foreground_args)
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn push_background_args(self, escape: &mut StyleEscape) {
+        escape.write_args(self, Self::fmt_background_args)
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn push_underline_args(self, escape: &mut StyleEscape) {
+        escape.write_args(self, Self::fmt_underline_args)
+    }
 }
 
 impl seal::Seal for Color {}
@@ -235,7 +310,7 @@ impl WriteColor for Color {
             Color::Ansi(color) => color.fmt_foreground_args(f),
             Color::Css(color) => color.fmt_foreground_args(f),
             Color::Xterm(color) => color.fmt_foreground_args(f),
-            Color::Rgb(color) => color.fmt_background_args(f),
+            Color::Rgb(color) => color.fmt_foreground_args(f),
         }
     }
 
@@ -288,6 +363,21 @@ impl WriteColor for Color {
             Color::Rgb(color) => color.fmt_underline(f),
         }
     }
+
+    #[inline]
+    fn push_foreground_args(self, escape: &mut StyleEscape) {
+        escape.push_foreground(self)
+    }
+
+    #[inline]
+    fn push_background_args(self, escape: &mut StyleEscape) {
+        escape.push_background(self)
+    }
+
+    #[inline]
+    fn push_underline_args(self, escape: &mut StyleEscape) {
+        escape.push_underline(self)
+    }
 }
 
 impl seal::Seal for core::convert::Infallible {}
@@ -0,0 +1,511 @@
+//! Colored formatting for log records, with the `log` and `tracing-subscriber` features
+//!
+//! [`LogFormatter`] renders records as `LEVEL spans: target: message key=value`, where the level,
+//! span names, target and field keys are styled with the [`LogStyles`]. Whether these are colored
+//! is decided by [`mode::should_color`](crate::mode::should_color) for the formatter's
+//! [`stream`](LogFormatter::stream), which is [`Stream::Stderr`] by default.
+//!
+//! With the `log` feature, [`LogFormatter::format`] formats a [`log::Record`], and with the `std`
+//! feature as well, [`LogFormatter`] can be installed as the global logger with [`LogFormatter::init`].
+//!
+//! ```rust
+//! # #[cfg(feature = "log")]
+//! # fn main() {
+//! use colorz::logging::LogFormatter;
+//!
+//! let record = log::Record::builder()
+//!     .args(format_args!("hello world"))
+//!     .level(log::Level::Info)
+//!     .target("app")
+//!     .build();
+//!
+//! eprintln!("{}", LogFormatter::new().format(&record));
+//! # }
+//! # #[cfg(not(feature = "log"))]
+//! # fn main() {}
+//! ```
+//!
+//! With the `tracing-subscriber` feature, [`LogFormatter`] implements
+//! [`FormatEvent`](tracing_subscriber::fmt::FormatEvent), so it can be used with
+//! [`SubscriberBuilder::event_format`](tracing_subscriber::fmt::SubscriberBuilder::event_format).
+//! Events are only colored if the subscriber writes ANSI escapes
+//! ([`with_ansi`](tracing_subscriber::fmt::SubscriberBuilder::with_ansi), which needs
+//! `tracing-subscriber`'s `ansi` feature) and [`should_color`](crate::mode::should_color) allows
+//! it for the formatter's stream. Set the [stream](LogFormatter::stream) to [`Stream::Stdout`]
+//! if the subscriber writes to stdout.
+
+use core::fmt;
+
+use crate::{ansi, mode::Stream, Colorize, Style};
+
+/// The styles used by a [`LogFormatter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LogStyles {
+    /// The style of the `ERROR` level
+    pub error: Style,
+    /// The style of the `WARN` level
+    pub warn: Style,
+    /// The style of the `INFO` level
+    pub info: Style,
+    /// The style of the `DEBUG` level
+    pub debug: Style,
+    /// The style of the `TRACE` level
+    pub trace: Style,
+    /// The style of the target (usually the module path)
+    pub target: Style,
+    /// The style of span names
+    pub span: Style,
+    /// The style of field keys
+    pub field: Style,
+}
+
+impl LogStyles {
+    /// No styling at all
+    pub const PLAIN: Self = Self {
+        error: Style::new().const_into_runtime_style(),
+        warn: Style::new().const_into_runtime_style(),
+        info: Style::new().const_into_runtime_style(),
+        debug: Style::new().const_into_runtime_style(),
+        trace: Style::new().const_into_runtime_style(),
+        target: Style::new().const_into_runtime_style(),
+        span: Style::new().const_into_runtime_style(),
+        field: Style::new().const_into_runtime_style(),
+    };
+
+    /// Styles similar to the ones used by `tracing-subscriber`
+    pub const DEFAULT: Self = Self {
+        error: Style::new().fg(ansi::Red).bold().const_into_runtime_style(),
+        warn: Style::new()
+            .fg(ansi::Yellow)
+            .bold()
+            .const_into_runtime_style(),
+        info: Style::new().fg(ansi::Green).const_into_runtime_style(),
+        debug: Style::new().fg(ansi::Blue).const_into_runtime_style(),
+        trace: Style::new().fg(ansi::Magenta).const_into_runtime_style(),
+        target: Style::new().dimmed().const_into_runtime_style(),
+        span: Style::new().bold().const_into_runtime_style(),
+        field: Style::new().italics().const_into_runtime_style(),
+    };
+
+    #[cfg(feature = "log")]
+    const fn log_level(&self, level: log::Level) -> Style {
+        match level {
+            log::Level::Error => self.error,
+            log::Level::Warn => self.warn,
+            log::Level::Info => self.info,
+            log::Level::Debug => self.debug,
+            log::Level::Trace => self.trace,
+        }
+    }
+
+    #[cfg(feature = "tracing-subscriber")]
+    const fn tracing_level(&self, level: tracing_core::Level) -> Style {
+        match level {
+            tracing_core::Level::ERROR => self.error,
+            tracing_core::Level::WARN => self.warn,
+            tracing_core::Level::INFO => self.info,
+            tracing_core::Level::DEBUG => self.debug,
+            _ => self.trace,
+        }
+    }
+}
+
+impl Default for LogStyles {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A formatter for log records, see the [module docs](self) for details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LogFormatter {
+    /// The styles to use
+    pub styles: LogStyles,
+    /// The stream the records will be written to, which decides if the records are colored
+    ///
+    /// `tracing` events are only colored if the subscriber writes ANSI escapes as well.
+    pub stream: Stream,
+}
+
+impl Default for LogFormatter {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogFormatter {
+    /// Create a formatter with the [default styles](LogStyles::DEFAULT), writing to [`Stream::Stderr`]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            styles: LogStyles::DEFAULT,
+            stream: Stream::Stderr,
+        }
+    }
+
+    /// Use the given styles
+    #[inline]
+    pub const fn styles(mut self, styles: LogStyles) -> Self {
+        self.styles = styles;
+        self
+    }
+
+    /// Set the stream the records will be written to
+    ///
+    /// When installed with [`init`](Self::init), records are printed to stdout for [`Stream::Stdout`],
+    /// and to stderr otherwise.
+    #[inline]
+    pub const fn stream(mut self, stream: Stream) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    fn fmt_level(&self, f: &mut dyn fmt::Write, level: &str, style: Style) -> fmt::Result {
+        write!(f, "{:>5}", level.style_with(style).stream(self.stream))
+    }
+
+    fn fmt_target(&self, f: &mut dyn fmt::Write, target: &str) -> fmt::Result {
+        write!(
+            f,
+            "{}:",
+            target.style_with(self.styles.target).stream(self.stream)
+        )
+    }
+
+    /// Format a [`log::Record`]
+    ///
+    /// ```rust
+    /// use colorz::{logging::LogFormatter, mode::Stream};
+    ///
+    /// let record = log::Record::builder()
+    ///     .args(format_args!("hello world"))
+    ///     .level(log::Level::Warn)
+    ///     .target("app")
+    ///     .build();
+    ///
+    /// let formatter = LogFormatter::new().stream(Stream::NeverColor);
+    ///
+    /// assert_eq!(formatter.format(&record).to_string(), " WARN app: hello world");
+    /// ```
+    #[inline]
+    #[cfg(feature = "log")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "log")))]
+    pub fn format<'a>(
+        &'a self,
+        record: &'a log::Record<'a>,
+    ) -> impl fmt::Display + fmt::Debug + 'a {
+        struct Format<'a> {
+            formatter: &'a LogFormatter,
+            record: &'a log::Record<'a>,
+        }
+
+        impl fmt::Debug for Format<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(self, f)
+            }
+        }
+
+        impl fmt::Display for Format<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let Self { formatter, record } = *self;
+                let level = record.level();
+
+                formatter.fmt_level(f, level.as_str(), formatter.styles.log_level(level))?;
+                f.write_str(" ")?;
+                formatter.fmt_target(f, record.target())?;
+                write!(f, " {}", record.args())
+            }
+        }
+
+        Format {
+            formatter: self,
+            record,
+        }
+    }
+
+    /// Install this formatter as the global logger, and set the maximum log level
+    ///
+    /// ```rust
+    /// use colorz::logging::LogFormatter;
+    ///
+    /// LogFormatter::new().init(log::LevelFilter::Info).unwrap();
+    ///
+    /// log::info!("hello world");
+    /// ```
+    #[inline]
+    #[cfg(all(feature = "log", feature = "std"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "log", feature = "std"))))]
+    pub fn init(self, max_level: log::LevelFilter) -> Result<(), log::SetLoggerError> {
+        log::set_boxed_logger(std::boxed::Box::new(self))?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "log", feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "log", feature = "std"))))]
+impl log::Log for LogFormatter {
+    #[inline]
+    fn enabled(&self, _metadata: &log::Metadata<'_>) -> bool {
+        true
+    }
+
+    #[inline]
+    fn log(&self, record: &log::Record<'_>) {
+        use std::io::Write;
+
+        // there is nowhere to report errors while logging, so they are ignored
+        let _ = match self.stream {
+            Stream::Stdout => writeln!(std::io::stdout().lock(), "{}", self.format(record)),
+            _ => writeln!(std::io::stderr().lock(), "{}", self.format(record)),
+        };
+    }
+
+    #[inline]
+    fn flush(&self) {
+        use std::io::Write;
+
+        let _ = match self.stream {
+            Stream::Stdout => std::io::stdout().flush(),
+            _ => std::io::stderr().flush(),
+        };
+    }
+}
+
+#[cfg(feature = "tracing-subscriber")]
+struct FieldVisitor<'a, 'w> {
+    formatter: &'a LogFormatter,
+    writer: &'a mut tracing_subscriber::fmt::format::Writer<'w>,
+    result: fmt::Result,
+}
+
+#[cfg(feature = "tracing-subscriber")]
+impl tracing_core::field::Visit for FieldVisitor<'_, '_> {
+    fn record_str(&mut self, field: &tracing_core::Field, value: &str) {
+        if field.name() == "message" {
+            self.record_debug(field, &format_args!("{value}"))
+        } else {
+            self.record_debug(field, &value)
+        }
+    }
+
+    fn record_debug(&mut self, field: &tracing_core::Field, value: &dyn fmt::Debug) {
+        if self.result.is_err() {
+            return;
+        }
+
+        self.result = match field.name() {
+            "message" => write!(self.writer, " {value:?}"),
+            name => {
+                // raw identifiers may be recorded with their `r#` prefix
+                let name = name.strip_prefix("r#").unwrap_or(name);
+                let name = name
+                    .style_with(self.formatter.styles.field)
+                    .stream(self.formatter.stream);
+
+                write!(self.writer, " {name}={value:?}")
+            }
+        };
+    }
+}
+
+#[cfg(feature = "tracing-subscriber")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tracing-subscriber")))]
+impl<S, N> tracing_subscriber::fmt::FormatEvent<S, N> for LogFormatter
+where
+    S: tracing_core::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+{
+    #[inline]
+    fn format_event(
+        &self,
+        ctx: &tracing_subscriber::fmt::FmtContext<'_, S, N>,
+        mut writer: tracing_subscriber::fmt::format::Writer<'_>,
+        event: &tracing_core::Event<'_>,
+    ) -> fmt::Result {
+        // the subscriber decides if ANSI escapes may be written (`with_ansi`), then the stream
+        let this = &LogFormatter {
+            stream: if writer.has_ansi_escapes() {
+                self.stream
+            } else {
+                Stream::NeverColor
+            },
+            ..*self
+        };
+
+        let metadata = event.metadata();
+        let level = *metadata.level();
+
+        this.fmt_level(
+            &mut writer,
+            level.as_str(),
+            this.styles.tracing_level(level),
+        )?;
+
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                write!(
+                    writer,
+                    " {}",
+                    span.name().style_with(this.styles.span).stream(this.stream)
+                )?;
+
+                let extensions = span.extensions();
+                if let Some(fields) =
+                    extensions.get::<tracing_subscriber::fmt::FormattedFields<N>>()
+                {
+                    if !fields.is_empty() {
+                        write!(writer, "{{{fields}}}")?;
+                    }
+                }
+
+                writer.write_char(':')?;
+            }
+        }
+
+        writer.write_char(' ')?;
+        this.fmt_target(&mut writer, metadata.target())?;
+
+        let mut visitor = FieldVisitor {
+            formatter: this,
+            writer: &mut writer,
+            result: Ok(()),
+        };
+        event.record(&mut visitor);
+        visitor.result?;
+
+        writeln!(writer)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::{LogFormatter, LogStyles};
+    use crate::{ansi, mode::Stream, Style};
+
+    const STYLES: LogStyles = LogStyles {
+        error: Style::new().fg(ansi::Red).const_into_runtime_style(),
+        target: Style::new().bold().const_into_runtime_style(),
+        span: Style::new().fg(ansi::Blue).const_into_runtime_style(),
+        field: Style::new().italics().const_into_runtime_style(),
+        ..LogStyles::PLAIN
+    };
+
+    #[test]
+    #[cfg(feature = "log")]
+    fn log_record() {
+        use std::string::ToString;
+
+        let record = log::Record::builder()
+            .args(format_args!("hello world"))
+            .level(log::Level::Error)
+            .target("app::module")
+            .build();
+
+        let formatter = LogFormatter::new().styles(STYLES);
+
+        assert_eq!(
+            formatter
+                .stream(Stream::NeverColor)
+                .format(&record)
+                .to_string(),
+            "ERROR app::module: hello world"
+        );
+
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        assert_eq!(
+            formatter
+                .stream(Stream::AlwaysColor)
+                .format(&record)
+                .to_string(),
+            "\x1b[31mERROR\x1b[39m \x1b[1mapp::module\x1b[22m: hello world"
+        );
+
+        let record = log::Record::builder()
+            .args(format_args!("hello"))
+            .level(log::Level::Info)
+            .target("app")
+            .build();
+
+        assert_eq!(
+            formatter
+                .stream(Stream::AlwaysColor)
+                .format(&record)
+                .to_string(),
+            " INFO \x1b[1mapp\x1b[22m: hello"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tracing-subscriber")]
+    fn tracing_event() {
+        use std::{
+            string::String,
+            sync::{Arc, Mutex},
+        };
+
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<std::vec::Vec<u8>>>);
+
+        impl std::io::Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let render = |formatter: LogFormatter, ansi| {
+            let buffer = Buffer::default();
+            let subscriber = tracing_subscriber::fmt()
+                .with_ansi(ansi)
+                .event_format(formatter.styles(STYLES))
+                .with_writer({
+                    let buffer = buffer.clone();
+                    move || buffer.clone()
+                })
+                .with_max_level(tracing_core::Level::TRACE)
+                .finish();
+
+            tracing::subscriber::with_default(subscriber, || {
+                let span = tracing::error_span!("request", id = 7);
+                let _guard = span.enter();
+                tracing::error!(r#type = "io", path = "a.txt", "could not {}", "open");
+            });
+
+            let output = buffer.0.lock().unwrap().clone();
+            String::from_utf8(output).unwrap()
+        };
+
+        let plain = "ERROR request{id=7}: colorz::logging::test: could not open type=\"io\" path=\"a.txt\"\n";
+        assert_eq!(
+            render(LogFormatter::new().stream(Stream::AlwaysColor), false),
+            plain
+        );
+
+        assert_eq!(render(LogFormatter::new(), false), plain);
+
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        // events are colored for stderr by default, whatever the subscriber writes to
+        assert_eq!(
+            render(LogFormatter::new(), true),
+            render(LogFormatter::new().stream(Stream::Stderr), true)
+        );
+
+        // span fields are formatted by the subscriber's `DefaultFields`, which has its own styles
+        assert_eq!(
+            render(LogFormatter::new().stream(Stream::AlwaysColor), true),
+            "\x1b[31mERROR\x1b[39m \x1b[34mrequest\x1b[39m{\x1b[3mid\x1b[0m\x1b[2m=\x1b[0m7}: \x1b[1mcolorz::logging::test\x1b[22m: could not open \x1b[3mtype\x1b[23m=\"io\" \x1b[3mpath\x1b[23m=\"a.txt\"\n"
+        );
+    }
+}
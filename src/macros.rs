@@ -0,0 +1,189 @@
+//! Compile-time styles and format strings with style markup
+
+use core::fmt;
+
+use crate::{mode, mode::Stream, style::StyleEscape, OptionalColor, Style};
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "alloc")]
+    pub extern crate alloc;
+    #[cfg(feature = "std")]
+    pub extern crate std;
+
+    pub use colorz_macros::{format_markup, style};
+    pub use core::option::Option::{None, Some};
+
+    pub use super::Transition;
+}
+
+/// Switches from one style to another, written if [`mode::should_color`] allows both styles
+///
+/// The escape sequence is built when the transition is created, which is at compile time in the
+/// code generated by the macros.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct Transition {
+    from: Style,
+    to: Style,
+    escape: StyleEscape,
+    stream: Option<Stream>,
+}
+
+impl Transition {
+    #[inline]
+    pub const fn new(from: Style, to: Style, stream: Option<Stream>) -> Self {
+        Self {
+            from,
+            to,
+            escape: crate::style::transition_escape(from, to),
+            stream,
+        }
+    }
+}
+
+impl fmt::Display for Transition {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kinds = [
+            self.from.foreground.color_kind(),
+            self.from.background.color_kind(),
+            self.from.underline_color.color_kind(),
+            self.to.foreground.color_kind(),
+            self.to.background.color_kind(),
+            self.to.underline_color.color_kind(),
+        ];
+
+        if mode::should_color(self.stream, &kinds) {
+            f.write_str(self.escape.as_str())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Create a runtime [`Style`] at compile time, from the same syntax as [`Style`'s `FromStr` impl](Style#fromstr)
+///
+/// Invalid styles are compile errors, and the style can be used in a `const`.
+///
+/// ```rust
+/// use colorz::{ansi, Style};
+///
+/// const ERROR: Style = colorz::style!("bold red bg:#202020");
+///
+/// assert_eq!(ERROR, "bold red bg:#202020".parse().unwrap());
+/// ```
+#[macro_export]
+#[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
+macro_rules! style {
+    ($style:literal) => {
+        $crate::__private::style!([$crate] $style)
+    };
+}
+
+/// Like [`format!`](alloc::format), but with style markup in the format string
+///
+/// Tags like `<bold red>` apply a style, using the same syntax as
+/// [`Style`'s `FromStr` impl](Style#fromstr), and `</>` goes back to the style before the last tag.
+/// Tags can be nested, and any tags which are still open at the end of the string are closed.
+/// Write `<<` for a literal `<`. Format arguments like `{:<4}` aren't treated as tags.
+///
+/// The tags are parsed at compile time into constant escape sequences, which only write the
+/// attributes which change at each tag. They're written if [`mode::should_color`] allows it for the
+/// [default stream](mode::get_default_stream).
+///
+/// ```rust
+/// use colorz::mode::{self, Mode};
+///
+/// mode::set_coloring_mode(Mode::Always);
+///
+/// let msg = "file not found";
+/// let s = colorz::cformat!("<bold><red>error</>: {msg}</> <<{:>4}>", 42);
+///
+/// # #[cfg(not(feature = "strip-colors"))]
+/// assert_eq!(s, "\x1b[1m\x1b[31merror\x1b[39m: file not found\x1b[22m <  42>");
+/// ```
+#[macro_export]
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "macros", feature = "alloc"))))]
+macro_rules! cformat {
+    ($($args:tt)*) => {
+        $crate::__private::format_markup!(
+            [$crate] [$crate::__private::alloc::format] [$crate::__private::None]
+            $($args)*
+        )
+    };
+}
+
+/// Like [`print!`](std::print), but with style markup in the format string, see [`cformat!`]
+///
+/// The styles are written if [`mode::should_color`] allows it for [`Stream::Stdout`]
+#[macro_export]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "macros", feature = "std"))))]
+macro_rules! cprint {
+    ($($args:tt)*) => {
+        $crate::__private::format_markup!(
+            [$crate] [$crate::__private::std::print]
+            [$crate::__private::Some($crate::mode::Stream::Stdout)]
+            $($args)*
+        )
+    };
+}
+
+/// Like [`println!`](std::println), but with style markup in the format string, see [`cformat!`]
+///
+/// The styles are written if [`mode::should_color`] allows it for [`Stream::Stdout`]
+///
+/// ```rust
+/// colorz::cprintln!("<green bold>ok</> built in {:.2}s", 1.5);
+/// ```
+#[macro_export]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "macros", feature = "std"))))]
+macro_rules! cprintln {
+    ($($args:tt)*) => {
+        $crate::__private::format_markup!(
+            [$crate] [$crate::__private::std::println]
+            [$crate::__private::Some($crate::mode::Stream::Stdout)]
+            $($args)*
+        )
+    };
+}
+
+/// Like [`eprint!`](std::eprint), but with style markup in the format string, see [`cformat!`]
+///
+/// The styles are written if [`mode::should_color`] allows it for [`Stream::Stderr`]
+#[macro_export]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "macros", feature = "std"))))]
+macro_rules! ceprint {
+    ($($args:tt)*) => {
+        $crate::__private::format_markup!(
+            [$crate] [$crate::__private::std::eprint]
+            [$crate::__private::Some($crate::mode::Stream::Stderr)]
+            $($args)*
+        )
+    };
+}
+
+/// Like [`eprintln!`](std::eprintln), but with style markup in the format string, see [`cformat!`]
+///
+/// The styles are written if [`mode::should_color`] allows it for [`Stream::Stderr`]
+///
+/// ```rust
+/// let path = "Cargo.toml";
+/// colorz::ceprintln!("<bold red>error</>: <underline>{path}</> not found");
+/// ```
+#[macro_export]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "macros", feature = "std"))))]
+macro_rules! ceprintln {
+    ($($args:tt)*) => {
+        $crate::__private::format_markup!(
+            [$crate] [$crate::__private::std::eprintln]
+            [$crate::__private::Some($crate::mode::Stream::Stderr)]
+            $($args)*
+        )
+    };
+}
@@ -2,6 +2,7 @@
 //!
 //! There are three levels, in order of precedence
 //! * feature flags - compile time (`strip-colors`)
+//! * per thread - runtime [`with_coloring_mode`], [`override_coloring_mode`] (requires `std`)
 //! * global - runtime [`set_coloring_mode`], [`set_coloring_mode_from_env`]
 //! * per value - runtime [`StyledValue::stream`]
 //!
@@ -18,14 +19,41 @@
 #[cfg(doc)]
 use crate::StyledValue;
 
-use core::{str::FromStr, sync::atomic::AtomicU8};
+use core::{
+    str::FromStr,
+    sync::atomic::{AtomicU32, AtomicU8},
+};
+
+#[cfg(feature = "std")]
+mod detect;
+
+#[cfg(feature = "std")]
+pub use detect::{EnvPolicy, Environment, ProcessEnv};
 
 static COLORING_MODE: AtomicU8 = AtomicU8::new(Mode::DETECT);
-static DEFAULT_STREAM: AtomicU8 = AtomicU8::new(Stream::AlwaysColor.encode());
+static DEFAULT_STREAM: AtomicU32 = AtomicU32::new(Stream::AlwaysColor.encode());
 #[cfg(any(feature = "std", feature = "supports-color"))]
 static STDOUT_SUPPORT: AtomicU8 = AtomicU8::new(ColorSupport::DETECT);
 #[cfg(any(feature = "std", feature = "supports-color"))]
 static STDERR_SUPPORT: AtomicU8 = AtomicU8::new(ColorSupport::DETECT);
+#[cfg(any(feature = "std", feature = "supports-color"))]
+static ALWAYS_SUPPORT: AtomicU8 = AtomicU8::new(ColorSupport::ALL.encode());
+#[cfg(feature = "std")]
+static HANDLE_IN_USE: [core::sync::atomic::AtomicBool; Handle::MAX] =
+    [const { core::sync::atomic::AtomicBool::new(false) }; Handle::MAX];
+#[cfg(feature = "std")]
+static HANDLE_SUPPORT: [AtomicU8; Handle::MAX] =
+    [const { AtomicU8::new(ColorSupport::DETECT) }; Handle::MAX];
+#[cfg(feature = "std")]
+static HANDLE_GENERATION: [AtomicU32; Handle::MAX] = [const { AtomicU32::new(0) }; Handle::MAX];
+#[cfg(feature = "std")]
+std::thread_local! {
+    static MODE_OVERRIDE: core::cell::Cell<Option<Mode>> = const { core::cell::Cell::new(None) };
+    static STREAM_OVERRIDE: core::cell::Cell<Option<Stream>> = const { core::cell::Cell::new(None) };
+}
+#[cfg(feature = "std")]
+static HANDLE_IS_TERMINAL: [core::sync::atomic::AtomicBool; Handle::MAX] =
+    [const { core::sync::atomic::AtomicBool::new(false) }; Handle::MAX];
 
 /// The coloring mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -103,6 +131,96 @@ pub enum Stream {
     AlwaysColor,
     /// Never color, used to pick the coloring mode at runtime for a particular value
     NeverColor,
+    /// Detect via a handle registered with [`register_handle`] or [`open_tty`]
+    ///
+    /// The handle is checked once, when it is registered, and the result is cached
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    Handle(Handle),
+}
+
+/// An opaque identifier for a handle registered with [`register_handle`]
+///
+/// Up to 252 handles may be registered at the same time. Once its [`HandleRegistration`] is dropped,
+/// the handle is stale and is never colored, even if its slot is reused by another handle.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    index: u8,
+    generation: u32,
+}
+
+#[cfg(feature = "std")]
+impl Handle {
+    // the first four values of the stream encoding are taken by the other variants
+    const MAX: usize = 256 - 4;
+    // the generation is encoded above the index in the stream encoding
+    const GENERATION_MASK: u32 = u32::MAX >> 8;
+
+    const fn index(self) -> usize {
+        self.index as usize
+    }
+
+    /// Whether the registration for this handle is still alive
+    fn is_live(self) -> bool {
+        HANDLE_GENERATION[self.index()].load(core::sync::atomic::Ordering::Acquire)
+            & Self::GENERATION_MASK
+            == self.generation
+    }
+}
+
+/// An error if there is no more space to register a handle
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandleRegistryFull;
+
+#[cfg(feature = "std")]
+impl std::error::Error for HandleRegistryFull {}
+
+#[cfg(feature = "std")]
+impl core::fmt::Display for HandleRegistryFull {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Too many handles registered: at most 252 handles may be registered at once")
+    }
+}
+
+/// A handle registered with [`register_handle`], which frees its slot in the registry when dropped
+///
+/// The [`Stream`] from [`stream`](Self::stream) is never colored after the registration is dropped,
+/// even if the slot is reused by another handle.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, PartialEq, Eq, Hash)]
+#[must_use = "the handle is unregistered when the registration is dropped"]
+pub struct HandleRegistration {
+    handle: Handle,
+}
+
+#[cfg(feature = "std")]
+impl HandleRegistration {
+    /// The stream which detects coloring on the registered handle
+    #[inline]
+    pub const fn stream(&self) -> Stream {
+        Stream::Handle(self.handle)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for HandleRegistration {
+    #[inline]
+    fn drop(&mut self) {
+        use core::sync::atomic::Ordering;
+
+        let index = self.handle.index();
+        // bump the generation first, so the stale handle is never colored by the next handle in this slot
+        HANDLE_GENERATION[index].fetch_add(1, Ordering::AcqRel);
+        HANDLE_IS_TERMINAL[index].store(false, Ordering::Relaxed);
+        HANDLE_SUPPORT[index].store(ColorSupport::NONE.encode(), Ordering::Release);
+        HANDLE_IN_USE[index].store(false, Ordering::Release);
+    }
 }
 
 /// An error if deserializing a mode from a string fails
@@ -170,32 +288,79 @@ pub enum ColorKind {
     NoColor,
 }
 
-#[cfg(any(feature = "std", feature = "supports-color"))]
+/// Which [color kinds](ColorKind) a stream supports
+///
+/// This is detected automatically if the `std` or `supports-color` features are enabled,
+/// and can be inspected with [`color_support`] or overridden with [`set_color_support`].
+///
+/// ```rust
+/// use colorz::mode::{ColorKind, ColorSupport};
+///
+/// assert!(ColorSupport::XTERM.supports(ColorKind::Ansi));
+/// assert!(ColorSupport::XTERM.supports(ColorKind::Xterm));
+/// assert!(!ColorSupport::XTERM.supports(ColorKind::Rgb));
+/// ```
 #[non_exhaustive]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct ColorSupport {
-    ansi: bool,
-    xterm: bool,
-    rgb: bool,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ColorSupport {
+    /// Are basic ANSI colors supported
+    pub ansi: bool,
+    /// Are Xterm (256) colors supported
+    pub xterm: bool,
+    /// Are Rgb (48-bit) colors supported
+    pub rgb: bool,
+}
+
+impl ColorSupport {
+    /// No colors are supported
+    pub const NONE: Self = Self::new(false, false, false);
+    /// Only basic ANSI colors are supported
+    pub const ANSI: Self = Self::new(true, false, false);
+    /// ANSI and Xterm colors are supported
+    pub const XTERM: Self = Self::new(true, true, false);
+    /// All colors are supported
+    pub const ALL: Self = Self::new(true, true, true);
+
+    /// Create a new color support value
+    #[inline]
+    pub const fn new(ansi: bool, xterm: bool, rgb: bool) -> Self {
+        Self { ansi, xterm, rgb }
+    }
+
+    /// Is the given color kind supported
+    ///
+    /// [`ColorKind::NoColor`] is always supported
+    #[inline]
+    pub const fn supports(self, kind: ColorKind) -> bool {
+        match kind {
+            ColorKind::Ansi => self.ansi,
+            ColorKind::Xterm => self.xterm,
+            ColorKind::Rgb => self.rgb,
+            ColorKind::NoColor => true,
+        }
+    }
 }
 
 #[cfg(any(feature = "std", feature = "supports-color"))]
 impl ColorSupport {
     const DETECT: u8 = 0x80;
 
-    #[cfg(feature = "supports-color")]
-    fn encode(self) -> u8 {
-        u8::from(self.ansi) | u8::from(self.xterm) << 1 | u8::from(self.rgb) << 2
+    const fn encode(self) -> u8 {
+        self.ansi as u8 | (self.xterm as u8) << 1 | (self.rgb as u8) << 2
     }
 
-    #[cfg(feature = "supports-color")]
-    fn decode(x: u8) -> Self {
+    const fn decode(x: u8) -> Self {
         Self {
             ansi: x & 0b001 != 0,
             xterm: x & 0b010 != 0,
             rgb: x & 0b100 != 0,
         }
     }
+
+    fn supports_all(self, kinds: &[ColorKind]) -> bool {
+        // if no colors are supported, then effects shouldn't be written either
+        self != Self::NONE && kinds.iter().all(|&kind| self.supports(kind))
+    }
 }
 
 impl Mode {
@@ -219,55 +384,145 @@ impl Mode {
 
     /// Reads the current mode from the environment
     ///
-    /// * If `NO_COLOR` is set to a non-zero value, [`Mode::Never`] is returned
-    ///
-    /// * If `ALWAYS_COLOR`, `CLICOLOR_FORCE`, `FORCE_COLOR` is set to a non-zero value, [`Mode::Always`] is returned
-    ///
-    /// * otherwise None is returned
+    /// This is the [`mode`](EnvPolicy::mode) of [`EnvPolicy::from_env`], see it for details
+    /// on which environment variables are supported.
     #[cfg(feature = "std")]
-    #[cfg_attr(doc, doc(cfg(feature = "std")))]
+    #[inline]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
     pub fn from_env() -> Option<Self> {
-        if std::env::var_os("NO_COLOR").is_some_and(|x| x != "0") {
-            return Some(Self::Never);
-        }
-
-        if std::env::var_os("ALWAYS_COLOR").is_some_and(|x| x != "0") {
-            return Some(Self::Always);
-        }
-
-        if std::env::var_os("CLICOLOR_FORCE").is_some_and(|x| x != "0") {
-            return Some(Self::Always);
-        }
-
-        if std::env::var_os("FORCE_COLOR").is_some_and(|x| x != "0") {
-            return Some(Self::Always);
-        }
+        EnvPolicy::from_env().mode
+    }
 
-        None
+    /// Reads the mode from the given environment
+    ///
+    /// This is the [`mode`](EnvPolicy::mode) of [`EnvPolicy::from_env_source`], see it for details
+    /// on which environment variables are supported.
+    ///
+    /// ```rust
+    /// use colorz::mode::Mode;
+    ///
+    /// assert_eq!(Mode::from_env_source(&[("NO_COLOR", "1")]), Some(Mode::Never));
+    /// assert_eq!(Mode::from_env_source(&[("NO_COLOR", "")]), None);
+    /// assert_eq!(Mode::from_env_source(&[("CLICOLOR_FORCE", "1")]), Some(Mode::Always));
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn from_env_source(env: &(impl Environment + ?Sized)) -> Option<Self> {
+        EnvPolicy::from_env_source(env).mode
     }
 }
 
 impl Stream {
-    const fn encode(self) -> u8 {
+    const fn encode(self) -> u32 {
         match self {
             Stream::Stdout => 0,
             Stream::Stderr => 1,
             Stream::AlwaysColor => 2,
             Stream::NeverColor => 3,
+            #[cfg(feature = "std")]
+            Stream::Handle(handle) => (4 + handle.index as u32) | handle.generation << 8,
         }
     }
 
-    const fn decode(x: u8) -> Self {
+    const fn decode(x: u32) -> Self {
         match x {
             0 => Self::Stdout,
             1 => Self::Stderr,
             2 => Self::AlwaysColor,
             3 => Self::NeverColor,
+            #[cfg(feature = "std")]
+            _ => Self::Handle(Handle {
+                index: (x & 0xff) as u8 - 4,
+                generation: x >> 8,
+            }),
+            #[cfg(not(feature = "std"))]
             _ => unreachable!(),
         }
     }
 }
 
+/// Register a handle to detect coloring on, and get a [`Stream`] which refers to it
+///
+/// The handle is checked for whether it is a terminal right away, and the result is used
+/// for every value written with the returned stream. Any type implementing [`std::io::IsTerminal`]
+/// can be registered, including [`std::fs::File`] and borrowed file descriptors/handles, so ptys and
+/// sockets can be registered via `AsFd::as_fd`/`AsHandle::as_handle`.
+///
+/// The handle stays registered until the returned [`HandleRegistration`] is dropped, so a
+/// long-running program can register each connection as it's accepted. After that, the
+/// stream is never colored.
+///
+/// ```rust
+/// use colorz::{Colorize, mode};
+///
+/// let file = std::fs::File::open(if cfg!(windows) { "NUL" } else { "/dev/null" }).unwrap();
+/// let registration = mode::register_handle(&file).unwrap();
+/// let stream = registration.stream();
+///
+/// // `/dev/null` isn't a terminal, so this won't be colored
+/// assert!(!mode::should_color(Some(stream), &[]));
+/// println!("{}", "hello world".red().stream(stream));
+/// ```
+///
+/// # Errors
+///
+/// At most 252 handles may be registered at the same time, after that [`HandleRegistryFull`] is returned
+#[inline]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn register_handle<H: std::io::IsTerminal>(
+    handle: &H,
+) -> Result<HandleRegistration, HandleRegistryFull> {
+    use core::sync::atomic::Ordering;
+
+    let index = HANDLE_IN_USE
+        .iter()
+        .position(|in_use| {
+            in_use
+                .compare_exchange(false, true, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+        })
+        .ok_or(HandleRegistryFull)?;
+
+    // `index < Handle::MAX`, so it fits in a `u8`
+    let handle_ref = Handle {
+        index: index as u8,
+        generation: HANDLE_GENERATION[index].load(Ordering::Acquire) & Handle::GENERATION_MASK,
+    };
+    let is_terminal = handle.is_terminal();
+    let support = detect::detect(&ProcessEnv, is_terminal);
+    HANDLE_IS_TERMINAL[index].store(is_terminal, Ordering::Relaxed);
+    HANDLE_SUPPORT[index].store(support.encode(), Ordering::Release);
+
+    Ok(HandleRegistration { handle: handle_ref })
+}
+
+/// Open the controlling terminal (`/dev/tty`) for writing, and register it via [`register_handle`]
+///
+/// This is useful for writing colored output directly to the user's terminal, even
+/// if stdout and stderr are redirected.
+///
+/// ```rust,no_run
+/// use std::io::Write;
+/// use colorz::{Colorize, mode};
+///
+/// let (mut tty, registration) = mode::open_tty().unwrap();
+/// writeln!(tty, "{}", "hello world".red().stream(registration.stream())).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// If `/dev/tty` cannot be opened, or if the handle registry is full
+#[inline]
+#[cfg(all(feature = "std", unix))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "std", unix))))]
+pub fn open_tty() -> std::io::Result<(std::fs::File, HandleRegistration)> {
+    let tty = std::fs::OpenOptions::new().write(true).open("/dev/tty")?;
+    let registration = register_handle(&tty).map_err(std::io::Error::other)?;
+    Ok((tty, registration))
+}
+
 #[inline]
 /// Set the global coloring mode (this allows forcing colors on or off despite stream preferences)
 pub fn set_coloring_mode(mode: Mode) {
@@ -282,24 +537,20 @@ pub fn set_coloring_mode(mode: Mode) {
 ///
 /// if no relevant environment variables are set, then the coloring mode is left unchanged
 ///
-/// see [`Mode::from_env`] for details on which env vars are supported
+/// see [`EnvPolicy::from_env`] for details on which env vars are supported, and
+/// [`EnvPolicy::apply`] for details on how they are applied
 #[inline]
 #[cfg(feature = "std")]
-#[cfg_attr(doc, doc(cfg(feature = "std")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
 pub fn set_coloring_mode_from_env() {
-    if cfg!(feature = "strip-colors") {
-        return;
-    }
-
-    if let Some(mode) = Mode::from_env() {
-        set_coloring_mode(mode)
-    }
+    EnvPolicy::from_env().apply()
 }
 
-/// Get the global coloring mode
+/// Get the coloring mode
 ///
 /// This can be set from [`set_coloring_mode`], [`set_coloring_mode_from_env`]
-/// or the feature flag `strip-colors`
+/// or the feature flag `strip-colors`, and overridden for the current thread with
+/// [`with_coloring_mode`] or [`override_coloring_mode`]
 ///
 /// If it is not set, this returns a value of `Mode::Detect`
 #[inline]
@@ -308,6 +559,11 @@ pub fn get_coloring_mode() -> Mode {
         return Mode::Never;
     }
 
+    #[cfg(feature = "std")]
+    if let Some(mode) = MODE_OVERRIDE.try_with(core::cell::Cell::get).ok().flatten() {
+        return mode;
+    }
+
     Mode::decode(COLORING_MODE.load(core::sync::atomic::Ordering::Acquire))
 }
 
@@ -330,12 +586,135 @@ pub fn set_default_stream(stream: Stream) {
 /// Get the default stream
 ///
 /// if one was not set by [`set_default_stream`], then this returns [`Stream::AlwaysColor`]. Otherwise return
-/// the value specified in [`set_default_stream`]
+/// the value specified in [`set_default_stream`], unless it's overridden for the current thread with
+/// [`with_default_stream`] or [`override_default_stream`]
 #[inline]
 pub fn get_default_stream() -> Stream {
+    #[cfg(feature = "std")]
+    if let Some(stream) = STREAM_OVERRIDE
+        .try_with(core::cell::Cell::get)
+        .ok()
+        .flatten()
+    {
+        return stream;
+    }
+
     Stream::decode(DEFAULT_STREAM.load(core::sync::atomic::Ordering::Acquire))
 }
 
+/// Overrides the coloring mode on the current thread until it's dropped, see [`override_coloring_mode`]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+#[must_use = "the override is removed when the guard is dropped"]
+pub struct ModeOverride {
+    previous: Option<Mode>,
+    // the override is thread local, so it has to be removed on the same thread
+    _not_send: core::marker::PhantomData<*const ()>,
+}
+
+/// Overrides the default stream on the current thread until it's dropped, see [`override_default_stream`]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+#[must_use = "the override is removed when the guard is dropped"]
+pub struct StreamOverride {
+    previous: Option<Stream>,
+    // the override is thread local, so it has to be removed on the same thread
+    _not_send: core::marker::PhantomData<*const ()>,
+}
+
+#[cfg(feature = "std")]
+impl Drop for ModeOverride {
+    #[inline]
+    fn drop(&mut self) {
+        let _ = MODE_OVERRIDE.try_with(|mode| mode.set(self.previous));
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for StreamOverride {
+    #[inline]
+    fn drop(&mut self) {
+        let _ = STREAM_OVERRIDE.try_with(|stream| stream.set(self.previous));
+    }
+}
+
+/// Override the coloring mode on the current thread, until the returned guard is dropped
+///
+/// The override takes the place of the global coloring mode from [`set_coloring_mode`] for
+/// everything formatted on this thread, so tests running in parallel can each pick a mode.
+/// Overrides can be nested, dropping a guard restores the override it replaced.
+/// The feature flag `strip-colors` still disables coloring.
+///
+/// ```rust
+/// use colorz::{mode::{self, Mode}, Colorize};
+///
+/// let _guard = mode::override_coloring_mode(Mode::Never);
+///
+/// assert_eq!(mode::get_coloring_mode(), Mode::Never);
+/// assert_eq!("error".red().to_string(), "error");
+/// ```
+#[inline]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn override_coloring_mode(mode: Mode) -> ModeOverride {
+    ModeOverride {
+        previous: MODE_OVERRIDE.with(|current| current.replace(Some(mode))),
+        _not_send: core::marker::PhantomData,
+    }
+}
+
+/// Run `f` with the coloring mode overridden on the current thread, see [`override_coloring_mode`]
+///
+/// ```rust
+/// use colorz::{mode::{self, Mode}, Colorize};
+///
+/// let s = mode::with_coloring_mode(Mode::Always, || "error".red().to_string());
+///
+/// # #[cfg(not(feature = "strip-colors"))]
+/// assert_eq!(s, "\x1b[31merror\x1b[39m");
+/// ```
+#[inline]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn with_coloring_mode<R>(mode: Mode, f: impl FnOnce() -> R) -> R {
+    let _guard = override_coloring_mode(mode);
+    f()
+}
+
+/// Override the default stream on the current thread, until the returned guard is dropped
+///
+/// The override takes the place of the stream from [`set_default_stream`] for everything
+/// formatted on this thread. Overrides can be nested, dropping a guard restores the override
+/// it replaced.
+///
+/// ```rust
+/// use colorz::mode::{self, Stream};
+///
+/// let _guard = mode::override_default_stream(Stream::NeverColor);
+///
+/// assert_eq!(mode::get_default_stream(), Stream::NeverColor);
+/// ```
+#[inline]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn override_default_stream(stream: Stream) -> StreamOverride {
+    StreamOverride {
+        previous: STREAM_OVERRIDE.with(|current| current.replace(Some(stream))),
+        _not_send: core::marker::PhantomData,
+    }
+}
+
+/// Run `f` with the default stream overridden on the current thread, see [`override_default_stream`]
+#[inline]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn with_default_stream<R>(stream: Stream, f: impl FnOnce() -> R) -> R {
+    let _guard = override_default_stream(stream);
+    f()
+}
+
 /// Should the given stream and color kinds be colored based on the coloring mode.
 ///
 /// for example, you can use this to decide if you need to color based on ANSI
@@ -363,18 +742,21 @@ pub fn get_default_stream() -> Stream {
 /// down the list, and the first element that applies will be selected.
 ///
 /// * if the feature flag `strip-colors` is enabled -> NO COLOR
-/// * if the global coloring mode is `Mode::Always` -> DO COLOR
-/// * if the global coloring mode is `Mode::NEVER`  -> NO COLOR
+/// * if the coloring mode is `Mode::Always` -> DO COLOR, if the colors are in the `Stream::AlwaysColor` color support (all colors unless overridden)
+/// * if the coloring mode is `Mode::NEVER`  -> NO COLOR
 /// * if the per-value stream if set to
-///     * `Stream::AlwaysColor` -> DO COLOR
+///     * `Stream::AlwaysColor` -> DO COLOR, if the colors are in the `Stream::AlwaysColor` color support (all colors unless overridden)
 ///     * `Stream::NeverColor` -> NO COLOR
-///     * `Stream::Stdout`/`Stream::Stderr` -> detect coloring using `std` or `support-color` (see docs on feature flags for details)
-/// * if global stream is set to
-///     * `Stream::AlwaysColor` -> DO COLOR
+///     * `Stream::Stdout`/`Stream::Stderr`/`Stream::Handle` -> detect coloring using `std` or `support-color` (see docs on feature flags for details)
+/// * if the default stream is set to
+///     * `Stream::AlwaysColor` -> DO COLOR, if the colors are in the `Stream::AlwaysColor` color support (all colors unless overridden)
 ///     * `Stream::NeverColor` -> NO COLOR
-///     * `Stream::Stdout`/`Stream::Stderr` -> detect coloring using `std` or `support-color` (see docs on feature flags for details)
+///     * `Stream::Stdout`/`Stream::Stderr`/`Stream::Handle` -> detect coloring using `std` or `support-color` (see docs on feature flags for details)
+///
+/// The coloring mode and default stream are the global ones, unless they're overridden for the
+/// current thread with [`override_coloring_mode`] or [`override_default_stream`].
 ///
-/// The global stream is always set to one of the possible `Stream` values,
+/// The default stream is always set to one of the possible `Stream` values,
 /// so one option on the list will always be chosen.
 ///
 /// NOTE that setting the coloring mode from the environment sets the global coloring mode,
@@ -405,121 +787,218 @@ pub fn should_color(stream: Option<Stream>, kinds: &[ColorKind]) -> bool {
     }
 
     match get_coloring_mode() {
-        Mode::Always => return true,
+        Mode::Always => return forced_color(kinds),
         Mode::Never => return false,
         Mode::Detect => (),
     }
 
     let stream = stream.unwrap_or_else(get_default_stream);
 
-    let is_stdout = match stream {
-        Stream::Stdout => true,
-        Stream::Stderr => false,
-        Stream::AlwaysColor => return true,
+    match stream {
+        Stream::AlwaysColor => return forced_color(kinds),
         Stream::NeverColor => return false,
-    };
+        Stream::Stdout | Stream::Stderr => (),
+        #[cfg(feature = "std")]
+        Stream::Handle(_) => (),
+    }
 
-    should_color_slow(is_stdout, kinds)
+    should_color_slow(stream, kinds)
+}
+
+/// Forced coloring writes the colors which [`Stream::AlwaysColor`] supports, which is all of them
+/// unless it was [overridden](set_color_support)
+#[inline]
+#[allow(clippy::missing_const_for_fn)]
+fn forced_color(kinds: &[ColorKind]) -> bool {
+    #[cfg(any(feature = "std", feature = "supports-color"))]
+    {
+        color_support(Stream::AlwaysColor).supports_all(kinds)
+    }
+
+    #[cfg(all(not(feature = "std"), not(feature = "supports-color")))]
+    {
+        let _ = kinds;
+        true
+    }
 }
 
 #[inline]
 #[allow(clippy::missing_const_for_fn)]
 #[cfg(all(not(feature = "std"), not(feature = "supports-color")))]
-fn should_color_slow(_is_stdout: bool, _kinds: &[ColorKind]) -> bool {
+fn should_color_slow(_stream: Stream, _kinds: &[ColorKind]) -> bool {
     true
 }
 
 #[cold]
-#[cfg(all(feature = "std", not(feature = "supports-color")))]
-fn should_color_slow(is_stdout: bool, _kinds: &[ColorKind]) -> bool {
+#[cfg(any(feature = "std", feature = "supports-color"))]
+fn should_color_slow(stream: Stream, kinds: &[ColorKind]) -> bool {
+    color_support(stream).supports_all(kinds)
+}
+
+#[cfg(any(feature = "std", feature = "supports-color"))]
+fn support_ref(stream: Stream) -> Option<&'static AtomicU8> {
+    match stream {
+        Stream::Stdout => Some(&STDOUT_SUPPORT),
+        Stream::Stderr => Some(&STDERR_SUPPORT),
+        #[cfg(feature = "std")]
+        Stream::Handle(handle) if handle.is_live() => Some(&HANDLE_SUPPORT[handle.index()]),
+        #[cfg(feature = "std")]
+        Stream::Handle(_) => None,
+        Stream::AlwaysColor => Some(&ALWAYS_SUPPORT),
+        Stream::NeverColor => None,
+    }
+}
+
+/// Get which color kinds are supported by the given stream
+///
+/// If the stream hasn't been checked yet, this detects support for it (see the crate docs
+/// on feature flags for details). The result is cached, so the same value is returned
+/// until [`set_color_support`] or [`reset_detection`] is called.
+///
+/// [`Stream::AlwaysColor`] supports all colors (unless overridden) and [`Stream::NeverColor`] supports no colors.
+/// The support of [`Stream::AlwaysColor`] is also used when the coloring mode is [`Mode::Always`].
+///
+/// NOTE: this doesn't take the global coloring mode into account, use [`should_color`] for that.
+///
+/// ```rust
+/// use colorz::mode::{self, ColorSupport, Stream};
+///
+/// assert_eq!(mode::color_support(Stream::AlwaysColor), ColorSupport::ALL);
+/// assert_eq!(mode::color_support(Stream::NeverColor), ColorSupport::NONE);
+/// ```
+#[inline]
+#[cfg(any(feature = "std", feature = "supports-color"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "supports-color"))))]
+pub fn color_support(stream: Stream) -> ColorSupport {
     use core::sync::atomic::Ordering;
-    use std::io::IsTerminal;
 
-    let support_ref = match is_stdout {
-        true => &STDOUT_SUPPORT,
-        false => &STDERR_SUPPORT,
+    let Some(support_ref) = support_ref(stream) else {
+        return ColorSupport::NONE;
     };
 
     #[cold]
     #[inline(never)]
-    fn detect(is_stdout: bool, support: &AtomicU8) -> bool {
-        let s = if is_stdout {
-            std::io::stdout().is_terminal()
-        } else {
-            std::io::stderr().is_terminal()
-        };
+    fn detect(stream: Stream, support: &AtomicU8) -> ColorSupport {
+        let s = detect_support(stream);
 
-        support.store(s as u8, Ordering::Relaxed);
+        support.store(s.encode(), Ordering::Relaxed);
 
         core::sync::atomic::fence(Ordering::SeqCst);
 
         s
     }
 
-    match support_ref.load(Ordering::Acquire) {
-        ColorSupport::DETECT => detect(is_stdout, support_ref),
-        0 => false,
-        _ => true,
-    }
-}
-
-#[cold]
-#[cfg(feature = "supports-color")]
-fn should_color_slow(is_stdout: bool, kinds: &[ColorKind]) -> bool {
-    use core::sync::atomic::Ordering;
-
-    use supports_color::Stream;
-
-    let (stream, support_ref) = match is_stdout {
-        true => (Stream::Stdout, &STDOUT_SUPPORT),
-        false => (Stream::Stderr, &STDERR_SUPPORT),
+    let support = match support_ref.load(Ordering::Acquire) {
+        ColorSupport::DETECT => detect(stream, support_ref),
+        support => ColorSupport::decode(support),
     };
 
-    let support = support_ref.load(Ordering::Acquire);
+    // the handle's registration may have been dropped while its support was read
+    #[cfg(feature = "std")]
+    if let Stream::Handle(handle) = stream {
+        if !handle.is_live() {
+            return ColorSupport::NONE;
+        }
+    }
 
-    #[cold]
-    #[inline(never)]
-    fn detect(s: Stream, support: &AtomicU8) -> ColorSupport {
-        let s = supports_color::on(s).map_or(
-            ColorSupport {
-                ansi: false,
-                xterm: false,
-                rgb: false,
-            },
-            |level| ColorSupport {
-                ansi: level.has_basic,
-                xterm: level.has_256,
-                rgb: level.has_16m,
-            },
-        );
+    support
+}
 
-        support.store(s.encode(), Ordering::Relaxed);
+/// Override which color kinds are supported by the given stream
+///
+/// This replaces whatever was detected (or would have been detected) for the stream, until
+/// this is called again, or [`reset_detection`] is called.
+///
+/// Setting the support for [`Stream::AlwaysColor`] limits which colors are written when coloring
+/// is forced on, by [`Stream::AlwaysColor`] or [`Mode::Always`] (for example from `FORCE_COLOR=1`),
+/// so colors it doesn't support are left out. Setting the support for [`Stream::NeverColor`]
+/// has no effect.
+///
+/// ```rust
+/// use colorz::{Colorize, xterm, rgb, mode::{self, ColorSupport, Stream}};
+///
+/// // only allow 256 colors on stdout
+/// mode::set_color_support(Stream::Stdout, ColorSupport::XTERM);
+///
+/// assert_eq!(mode::color_support(Stream::Stdout), ColorSupport::XTERM);
+///
+/// // may be colored
+/// println!("{}", "hello world".fg(xterm::Aqua).stream(Stream::Stdout));
+/// // will not be colored
+/// println!("{}", "hello world".fg(rgb::Rgb::<0, 128, 255>).stream(Stream::Stdout));
+/// ```
+#[inline]
+#[cfg(any(feature = "std", feature = "supports-color"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "supports-color"))))]
+pub fn set_color_support(stream: Stream, support: ColorSupport) {
+    if let Some(support_ref) = support_ref(stream) {
+        support_ref.store(support.encode(), core::sync::atomic::Ordering::Release)
+    }
+}
 
-        core::sync::atomic::fence(Ordering::SeqCst);
+/// Forget all detected (and overridden) color support, so that it's detected again
+/// the next time each stream is used
+///
+/// This is useful if the environment changed, for example if the user switched terminals.
+/// Registered handles are re-detected based on whether they were a terminal when they were registered.
+///
+/// ```rust
+/// use colorz::mode::{self, ColorSupport, Stream};
+///
+/// mode::set_color_support(Stream::Stderr, ColorSupport::NONE);
+/// mode::reset_detection();
+/// ```
+#[inline]
+#[cfg(any(feature = "std", feature = "supports-color"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "supports-color"))))]
+pub fn reset_detection() {
+    use core::sync::atomic::Ordering;
 
-        s
+    STDOUT_SUPPORT.store(ColorSupport::DETECT, Ordering::Release);
+    STDERR_SUPPORT.store(ColorSupport::DETECT, Ordering::Release);
+    ALWAYS_SUPPORT.store(ColorSupport::ALL.encode(), Ordering::Release);
+
+    #[cfg(feature = "std")]
+    for (support, in_use) in HANDLE_SUPPORT.iter().zip(&HANDLE_IN_USE) {
+        if in_use.load(Ordering::Acquire) {
+            support.store(ColorSupport::DETECT, Ordering::Release);
+        }
     }
+}
 
-    let support = if support == ColorSupport::DETECT {
-        detect(stream, support_ref)
-    } else {
-        ColorSupport::decode(support)
+#[cfg(all(feature = "std", not(feature = "supports-color")))]
+fn detect_support(stream: Stream) -> ColorSupport {
+    use std::io::IsTerminal;
+
+    let is_terminal = match stream {
+        Stream::Stdout => std::io::stdout().is_terminal(),
+        Stream::Stderr => std::io::stderr().is_terminal(),
+        Stream::Handle(handle) => {
+            HANDLE_IS_TERMINAL[handle.index()].load(core::sync::atomic::Ordering::Relaxed)
+        }
+        Stream::AlwaysColor | Stream::NeverColor => false,
     };
 
-    for &kind in kinds {
-        let supported = match kind {
-            ColorKind::Ansi => support.ansi,
-            ColorKind::Xterm => support.xterm,
-            ColorKind::Rgb => support.rgb,
-            ColorKind::NoColor => continue,
-        };
+    detect::detect(&ProcessEnv, is_terminal)
+}
 
-        if !supported {
-            return false;
+#[cfg(feature = "supports-color")]
+fn detect_support(stream: Stream) -> ColorSupport {
+    let stream = match stream {
+        Stream::Stdout => supports_color::Stream::Stdout,
+        Stream::Stderr => supports_color::Stream::Stderr,
+        #[cfg(feature = "std")]
+        Stream::Handle(handle) => {
+            let is_terminal =
+                HANDLE_IS_TERMINAL[handle.index()].load(core::sync::atomic::Ordering::Relaxed);
+            return detect::detect(&ProcessEnv, is_terminal);
         }
-    }
+        Stream::AlwaysColor | Stream::NeverColor => return ColorSupport::NONE,
+    };
 
-    true
+    supports_color::on(stream).map_or(ColorSupport::NONE, |level| {
+        ColorSupport::new(level.has_basic, level.has_256, level.has_16m)
+    })
 }
 
 #[cfg(test)]
@@ -592,4 +1071,176 @@ mod test {
     fn stream_from_str_stderr() {
         test_case_insensitive_stream_from_str(*b"stderr", Stream::Stderr);
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn registered_handle() {
+        let file = std::fs::File::open(if cfg!(windows) { "NUL" } else { "/dev/null" }).unwrap();
+        let registration = super::register_handle(&file).unwrap();
+        let stream = registration.stream();
+
+        assert!(matches!(stream, Stream::Handle(_)));
+        assert_eq!(Stream::decode(stream.encode()), stream);
+        assert!(!super::should_color_slow(stream, &[super::ColorKind::Ansi]));
+        assert_ne!(super::register_handle(&file).unwrap().stream(), stream);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn dropped_handles_are_reused() {
+        let file = std::fs::File::open(if cfg!(windows) { "NUL" } else { "/dev/null" }).unwrap();
+
+        // far more registrations than fit in the registry at once
+        for _ in 0..super::Handle::MAX * 4 {
+            let registration = super::register_handle(&file).unwrap();
+            assert!(!super::should_color_slow(
+                registration.stream(),
+                &[super::ColorKind::Ansi]
+            ));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn stale_handles_are_never_colored() {
+        use super::{color_support, set_color_support, ColorKind, ColorSupport};
+
+        let _lock = lock_color_support();
+        let file = std::fs::File::open(if cfg!(windows) { "NUL" } else { "/dev/null" }).unwrap();
+
+        let registration = super::register_handle(&file).unwrap();
+        let stale = registration.stream();
+        let Stream::Handle(stale_handle) = stale else {
+            unreachable!()
+        };
+        drop(registration);
+
+        // other tests may take the freed slot for a moment, so register until it's reused
+        let registration = core::iter::repeat_with(|| super::register_handle(&file).unwrap())
+            .find(|registration| {
+                matches!(registration.stream(), Stream::Handle(handle) if handle.index() == stale_handle.index())
+            })
+            .unwrap();
+        let stream = registration.stream();
+        set_color_support(stream, ColorSupport::ALL);
+        set_color_support(stale, ColorSupport::ALL);
+
+        assert_ne!(stream, stale);
+        assert_eq!(Stream::decode(stale.encode()), stale);
+        assert_eq!(color_support(stream), ColorSupport::ALL);
+        assert_eq!(color_support(stale), ColorSupport::NONE);
+        assert!(super::should_color_slow(stream, &[ColorKind::Ansi]));
+        assert!(!super::should_color_slow(stale, &[]));
+    }
+
+    /// Held by tests which override color support, because [`reset_detection`](super::reset_detection)
+    /// resets every stream, including the ones private to other tests
+    #[cfg(feature = "std")]
+    static COLOR_SUPPORT: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[cfg(feature = "std")]
+    fn lock_color_support() -> std::sync::MutexGuard<'static, ()> {
+        COLOR_SUPPORT
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn override_color_support() {
+        use super::{ColorKind, ColorSupport};
+
+        let _lock = lock_color_support();
+
+        let file = std::fs::File::open(if cfg!(windows) { "NUL" } else { "/dev/null" }).unwrap();
+        let registration = super::register_handle(&file).unwrap();
+        let stream = registration.stream();
+
+        assert_eq!(super::color_support(stream), ColorSupport::NONE);
+
+        super::set_color_support(stream, ColorSupport::XTERM);
+        assert_eq!(super::color_support(stream), ColorSupport::XTERM);
+        assert!(super::should_color_slow(
+            stream,
+            &[ColorKind::Ansi, ColorKind::Xterm]
+        ));
+        assert!(!super::should_color_slow(stream, &[ColorKind::Rgb]));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn reset_detection() {
+        use super::ColorSupport;
+
+        let _lock = lock_color_support();
+
+        let file = std::fs::File::open(if cfg!(windows) { "NUL" } else { "/dev/null" }).unwrap();
+        let registration = super::register_handle(&file).unwrap();
+        let stream = registration.stream();
+
+        // the shared streams are restored afterwards, so other tests see the detected support
+        let shared = [Stream::Stdout, Stream::Stderr, Stream::AlwaysColor];
+        let saved = shared.map(super::color_support);
+
+        super::set_color_support(stream, ColorSupport::XTERM);
+        super::reset_detection();
+        assert_eq!(super::color_support(stream), ColorSupport::NONE);
+        assert_eq!(super::color_support(Stream::AlwaysColor), ColorSupport::ALL);
+
+        for (stream, support) in shared.into_iter().zip(saved) {
+            super::set_color_support(stream, support);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn nested_overrides() {
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        let global = super::get_coloring_mode();
+
+        let outer = super::override_coloring_mode(Mode::Never);
+        super::with_coloring_mode(Mode::Always, || {
+            assert_eq!(super::get_coloring_mode(), Mode::Always);
+            assert!(super::should_color(Some(Stream::NeverColor), &[]));
+        });
+        assert_eq!(super::get_coloring_mode(), Mode::Never);
+        assert!(!super::should_color(Some(Stream::AlwaysColor), &[]));
+
+        drop(outer);
+        assert_eq!(super::get_coloring_mode(), global);
+
+        super::with_default_stream(Stream::NeverColor, || {
+            super::with_coloring_mode(Mode::Detect, || {
+                assert!(!super::should_color(None, &[]));
+                assert!(super::should_color(Some(Stream::AlwaysColor), &[]));
+            });
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn overrides_are_per_thread() {
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        let barrier = std::sync::Barrier::new(2);
+
+        std::thread::scope(|scope| {
+            for mode in [Mode::Always, Mode::Never] {
+                let barrier = &barrier;
+                scope.spawn(move || {
+                    super::with_coloring_mode(mode, || {
+                        // both overrides are active at the same time
+                        barrier.wait();
+                        assert_eq!(super::get_coloring_mode(), mode);
+                        barrier.wait();
+                    })
+                });
+            }
+        });
+    }
 }
@@ -0,0 +1,625 @@
+//! Environment variable handling, and terminal capability detection from the environment
+//! and the terminfo database
+
+use std::{ffi::OsString, path::PathBuf, vec::Vec};
+
+use super::{ColorSupport, Mode, Stream};
+
+/// A source of environment variables
+///
+/// This is used by the built-in color detection, and can be implemented to
+/// inject a fake environment (for example in tests).
+///
+/// ```rust
+/// use colorz::mode::Environment;
+///
+/// let env = [("TERM", "xterm-256color"), ("COLORTERM", "truecolor")];
+///
+/// assert_eq!(env.var("TERM"), Some("xterm-256color".into()));
+/// assert_eq!(env.var("NO_COLOR"), None);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub trait Environment {
+    /// Get the value of the environment variable `name`, if it is set
+    fn var(&self, name: &str) -> Option<OsString>;
+}
+
+/// The environment of the current process, read via [`std::env::var_os`]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProcessEnv;
+
+impl Environment for ProcessEnv {
+    #[inline]
+    fn var(&self, name: &str) -> Option<OsString> {
+        std::env::var_os(name)
+    }
+}
+
+impl Environment for [(&str, &str)] {
+    #[inline]
+    fn var(&self, name: &str) -> Option<OsString> {
+        self.iter()
+            .find(|&&(key, _)| key == name)
+            .map(|&(_, value)| value.into())
+    }
+}
+
+impl<const N: usize> Environment for [(&str, &str); N] {
+    #[inline]
+    fn var(&self, name: &str) -> Option<OsString> {
+        self[..].var(name)
+    }
+}
+
+impl<E: Environment + ?Sized> Environment for &E {
+    #[inline]
+    fn var(&self, name: &str) -> Option<OsString> {
+        E::var(self, name)
+    }
+}
+
+/// The coloring policy specified by the environment
+///
+/// This follows the [`NO_COLOR`](https://no-color.org), [`CLICOLOR`](https://bixense.com/clicolors/)
+/// and `FORCE_COLOR` conventions. The first rule that applies is used:
+///
+/// * `NO_COLOR` set to any non-empty value -> [`Mode::Never`]
+/// * `FORCE_COLOR`
+///     * `0` or `false` -> [`Mode::Never`]
+///     * `2` -> [`Mode::Always`] with 256 colors ([`ColorSupport::XTERM`])
+///     * `3` -> [`Mode::Always`] with all colors ([`ColorSupport::ALL`])
+///     * anything else (including empty, `1` or `true`) -> [`Mode::Always`] with basic ANSI colors ([`ColorSupport::ANSI`])
+/// * `CLICOLOR_FORCE` or `ALWAYS_COLOR` set to a non-empty value other than `0` -> [`Mode::Always`]
+/// * `CLICOLOR=0` -> [`Mode::Never`]
+/// * `TERM=dumb` -> [`Mode::Never`]
+///
+/// When the color support is [applied](Self::apply), only those colors are written while coloring
+/// is forced on.
+///
+/// ```rust
+/// use colorz::mode::{ColorSupport, EnvPolicy, Mode};
+///
+/// let policy = EnvPolicy::from_env_source(&[("FORCE_COLOR", "2")]);
+///
+/// assert_eq!(policy.mode, Some(Mode::Always));
+/// assert_eq!(policy.support, Some(ColorSupport::XTERM));
+///
+/// // `NO_COLOR` takes precedence
+/// let policy = EnvPolicy::from_env_source(&[("FORCE_COLOR", "2"), ("NO_COLOR", "1")]);
+///
+/// assert_eq!(policy.mode, Some(Mode::Never));
+/// assert_eq!(policy.support, None);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EnvPolicy {
+    /// The coloring mode, if the environment forces coloring on or off
+    pub mode: Option<Mode>,
+    /// Which colors the terminal supports when coloring is forced on, if the environment specifies it
+    pub support: Option<ColorSupport>,
+}
+
+impl EnvPolicy {
+    const fn mode(mode: Mode) -> Self {
+        Self {
+            mode: Some(mode),
+            support: None,
+        }
+    }
+
+    const fn forced(support: ColorSupport) -> Self {
+        Self {
+            mode: Some(Mode::Always),
+            support: Some(support),
+        }
+    }
+
+    /// Read the policy from the environment of the current process
+    #[inline]
+    pub fn from_env() -> Self {
+        Self::from_env_source(&ProcessEnv)
+    }
+
+    /// Read the policy from the given environment
+    #[inline]
+    pub fn from_env_source(env: &(impl Environment + ?Sized)) -> Self {
+        let non_empty = |name| env.var(name).filter(|value| !value.is_empty());
+
+        if non_empty("NO_COLOR").is_some() {
+            return Self::mode(Mode::Never);
+        }
+
+        if let Some(force) = env.var("FORCE_COLOR") {
+            return match force.to_str() {
+                Some("0" | "false") => Self::mode(Mode::Never),
+                Some("2") => Self::forced(ColorSupport::XTERM),
+                Some("3") => Self::forced(ColorSupport::ALL),
+                _ => Self::forced(ColorSupport::ANSI),
+            };
+        }
+
+        if non_empty("CLICOLOR_FORCE").is_some_and(|value| value != "0")
+            || non_empty("ALWAYS_COLOR").is_some_and(|value| value != "0")
+        {
+            return Self::mode(Mode::Always);
+        }
+
+        if env.var("CLICOLOR").is_some_and(|value| value == "0") {
+            return Self::mode(Mode::Never);
+        }
+
+        if env.var("TERM").is_some_and(|value| value == "dumb") {
+            return Self::mode(Mode::Never);
+        }
+
+        Self::default()
+    }
+
+    /// Apply this policy
+    ///
+    /// If a [mode](Self::mode) was specified, then it is set as the global coloring mode.
+    /// If a [color support](Self::support) was specified, then it is recorded as the color support
+    /// of [`Stream::AlwaysColor`] (see [`set_color_support`](super::set_color_support)), so colors
+    /// which it doesn't include aren't written when coloring is forced on.
+    ///
+    /// ```rust
+    /// use colorz::mode::{self, ColorSupport, EnvPolicy, Mode, Stream};
+    ///
+    /// EnvPolicy::from_env_source(&[("FORCE_COLOR", "1")]).apply();
+    ///
+    /// # if !cfg!(feature = "strip-colors") {
+    /// assert_eq!(mode::get_coloring_mode(), Mode::Always);
+    /// assert_eq!(mode::color_support(Stream::AlwaysColor), ColorSupport::ANSI);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn apply(self) {
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        if let Some(mode) = self.mode {
+            super::set_coloring_mode(mode);
+        }
+
+        if let Some(support) = self.support {
+            super::set_color_support(Stream::AlwaysColor, support);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    None,
+    Ansi,
+    Xterm,
+    Rgb,
+}
+
+impl Level {
+    const fn from_colors(colors: u32) -> Self {
+        match colors {
+            0..=7 => Self::None,
+            8..=255 => Self::Ansi,
+            256..=16_777_215 => Self::Xterm,
+            _ => Self::Rgb,
+        }
+    }
+
+    const fn support(self) -> ColorSupport {
+        match self {
+            Level::None => ColorSupport::NONE,
+            Level::Ansi => ColorSupport::ANSI,
+            Level::Xterm => ColorSupport::XTERM,
+            Level::Rgb => ColorSupport::ALL,
+        }
+    }
+}
+
+/// CI providers which render ANSI colors in their logs, even though the output isn't a terminal
+const CI_PROVIDERS: &[(&str, Level)] = &[
+    ("GITHUB_ACTIONS", Level::Rgb),
+    ("GITEA_ACTIONS", Level::Rgb),
+    ("GITLAB_CI", Level::Ansi),
+    ("TRAVIS", Level::Ansi),
+    ("CIRCLECI", Level::Ansi),
+    ("APPVEYOR", Level::Ansi),
+    ("BUILDKITE", Level::Ansi),
+    ("DRONE", Level::Ansi),
+];
+
+/// Detect which color kinds are supported by a stream, based on the environment
+pub(super) fn detect(env: &(impl Environment + ?Sized), is_terminal: bool) -> ColorSupport {
+    detect_level(env, is_terminal).support()
+}
+
+fn detect_level(env: &(impl Environment + ?Sized), is_terminal: bool) -> Level {
+    let term = env.var("TERM");
+    let term = term.as_deref().and_then(|term| term.to_str());
+
+    if term == Some("dumb") {
+        return Level::None;
+    }
+
+    if !is_terminal {
+        return match env.var("CI") {
+            Some(_) => CI_PROVIDERS
+                .iter()
+                .find(|(name, _)| env.var(name).is_some())
+                .map_or(Level::None, |&(_, level)| level),
+            None => Level::None,
+        };
+    }
+
+    if env
+        .var("COLORTERM")
+        .is_some_and(|x| x == "truecolor" || x == "24bit")
+    {
+        return Level::Rgb;
+    }
+
+    let term_program = env.var("TERM_PROGRAM");
+    match term_program.as_deref().and_then(|x| x.to_str()) {
+        Some("iTerm.app" | "WezTerm" | "vscode" | "Hyper" | "ghostty") => return Level::Rgb,
+        Some("Apple_Terminal") => return Level::Xterm,
+        _ => (),
+    }
+
+    let Some(term) = term.filter(|term| !term.is_empty()) else {
+        // Windows terminals don't set `TERM`, but have supported VT sequences since Windows 10
+        return if cfg!(windows) {
+            Level::Rgb
+        } else {
+            Level::None
+        };
+    };
+
+    if term.ends_with("-direct") || term.contains("truecolor") || term.contains("24bit") {
+        return Level::Rgb;
+    }
+
+    if let Some(colors) = terminfo_colors(env, term) {
+        return Level::from_colors(colors);
+    }
+
+    if term.ends_with("256") || term.ends_with("256color") {
+        return Level::Xterm;
+    }
+
+    const ANSI_TERMS: &[&str] = &[
+        "xterm",
+        "screen",
+        "tmux",
+        "vt100",
+        "vt220",
+        "rxvt",
+        "ansi",
+        "cygwin",
+        "linux",
+        "konsole",
+        "alacritty",
+        "kitty",
+        "foot",
+    ];
+
+    if term.contains("color") || ANSI_TERMS.iter().any(|name| term.starts_with(name)) {
+        return Level::Ansi;
+    }
+
+    Level::None
+}
+
+/// The directories that are searched for terminfo entries, in order
+///
+/// Like ncurses, `TERMINFO_DIRS` replaces the system directories, and an empty entry in it stands for them
+fn terminfo_dirs(env: &(impl Environment + ?Sized)) -> Vec<PathBuf> {
+    const DEFAULT_DIRS: &[&str] = &[
+        "/etc/terminfo",
+        "/lib/terminfo",
+        "/usr/share/terminfo",
+        "/usr/lib/terminfo",
+    ];
+
+    let mut dirs = Vec::new();
+
+    if let Some(dir) = env.var("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+
+    if let Some(home) = env.var("HOME") {
+        dirs.push(PathBuf::from(home).join(".terminfo"));
+    }
+
+    match env.var("TERMINFO_DIRS") {
+        Some(list) => {
+            for dir in std::env::split_paths(&list) {
+                if dir.as_os_str().is_empty() {
+                    dirs.extend(DEFAULT_DIRS.iter().map(PathBuf::from));
+                } else {
+                    dirs.push(dir);
+                }
+            }
+        }
+        None => dirs.extend(DEFAULT_DIRS.iter().map(PathBuf::from)),
+    }
+
+    dirs
+}
+
+/// Lookup the `colors` capability of the given terminal in the terminfo database
+fn terminfo_colors(env: &(impl Environment + ?Sized), term: &str) -> Option<u32> {
+    // a terminfo name can never contain a path separator
+    if term.contains(['/', '\\']) || term.starts_with('.') {
+        return None;
+    }
+
+    let first = term.chars().next()?;
+    let hex = std::format!("{:02x}", u32::from(first));
+
+    terminfo_dirs(env).into_iter().find_map(|dir| {
+        // Linux uses the first character as the sub-directory, macOS uses it's hex code
+        let data = std::fs::read(dir.join(first.encode_utf8(&mut [0; 4])).join(term))
+            .or_else(|_| std::fs::read(dir.join(&hex).join(term)))
+            .ok()?;
+
+        parse_terminfo_colors(&data)
+    })
+}
+
+/// Parse the `colors` numeric capability out of a compiled terminfo entry
+///
+/// see `man 5 term` for details on the format
+fn parse_terminfo_colors(data: &[u8]) -> Option<u32> {
+    const MAGIC_LEGACY: u16 = 0o432;
+    const MAGIC_32_BIT: u16 = 0o1036;
+    // the index of `colors` in the numeric capabilities
+    const COLORS: usize = 13;
+
+    let header = |i: usize| -> Option<usize> {
+        let bytes = data.get(i * 2..i * 2 + 2)?;
+        let value = i16::from_le_bytes([bytes[0], bytes[1]]);
+        usize::try_from(value).ok()
+    };
+
+    let number_size = match u16::from_le_bytes([*data.first()?, *data.get(1)?]) {
+        MAGIC_LEGACY => 2,
+        MAGIC_32_BIT => 4,
+        _ => return None,
+    };
+
+    let names_size = header(1)?;
+    let bools_count = header(2)?;
+    let numbers_count = header(3)?;
+
+    if numbers_count <= COLORS {
+        return None;
+    }
+
+    let mut offset = 12 + names_size + bools_count;
+    // the numbers section is aligned to an even byte boundary
+    offset += offset % 2;
+    offset += COLORS * number_size;
+
+    let bytes = data.get(offset..offset + number_size)?;
+    let colors = match *bytes {
+        [a, b] => i32::from(i16::from_le_bytes([a, b])),
+        [a, b, c, d] => i32::from_le_bytes([a, b, c, d]),
+        _ => return None,
+    };
+
+    // negative values mean that the capability is absent or cancelled
+    u32::try_from(colors).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{path::PathBuf, string::String, vec::Vec};
+
+    use super::{detect_level, parse_terminfo_colors, EnvPolicy, Level};
+    use crate::mode::{ColorSupport, Mode};
+
+    #[test]
+    fn env_policy() {
+        let mode = |env: &[(&str, &str)]| EnvPolicy::from_env_source(env).mode;
+        let support = |env: &[(&str, &str)]| EnvPolicy::from_env_source(env).support;
+
+        assert_eq!(mode(&[]), None);
+        assert_eq!(mode(&[("NO_COLOR", "1")]), Some(Mode::Never));
+        assert_eq!(mode(&[("NO_COLOR", "0")]), Some(Mode::Never));
+        assert_eq!(mode(&[("NO_COLOR", "")]), None);
+        assert_eq!(
+            mode(&[("NO_COLOR", ""), ("FORCE_COLOR", "1")]),
+            Some(Mode::Always)
+        );
+        assert_eq!(
+            mode(&[("NO_COLOR", "1"), ("FORCE_COLOR", "1")]),
+            Some(Mode::Never)
+        );
+
+        assert_eq!(mode(&[("FORCE_COLOR", "0")]), Some(Mode::Never));
+        assert_eq!(mode(&[("FORCE_COLOR", "false")]), Some(Mode::Never));
+        assert_eq!(mode(&[("FORCE_COLOR", "yes")]), Some(Mode::Always));
+        assert_eq!(support(&[("FORCE_COLOR", "yes")]), Some(ColorSupport::ANSI));
+        assert_eq!(support(&[("FORCE_COLOR", "")]), Some(ColorSupport::ANSI));
+        assert_eq!(
+            support(&[("FORCE_COLOR", "true")]),
+            Some(ColorSupport::ANSI)
+        );
+        assert_eq!(support(&[("FORCE_COLOR", "1")]), Some(ColorSupport::ANSI));
+        assert_eq!(support(&[("FORCE_COLOR", "2")]), Some(ColorSupport::XTERM));
+        assert_eq!(support(&[("FORCE_COLOR", "3")]), Some(ColorSupport::ALL));
+
+        assert_eq!(mode(&[("CLICOLOR_FORCE", "1")]), Some(Mode::Always));
+        assert_eq!(mode(&[("CLICOLOR_FORCE", "0")]), None);
+        assert_eq!(mode(&[("CLICOLOR_FORCE", "")]), None);
+        assert_eq!(mode(&[("ALWAYS_COLOR", "1")]), Some(Mode::Always));
+        assert_eq!(mode(&[("CLICOLOR", "0")]), Some(Mode::Never));
+        assert_eq!(mode(&[("CLICOLOR", "1")]), None);
+        assert_eq!(
+            mode(&[("CLICOLOR", "0"), ("CLICOLOR_FORCE", "1")]),
+            Some(Mode::Always)
+        );
+
+        assert_eq!(mode(&[("TERM", "dumb")]), Some(Mode::Never));
+        assert_eq!(mode(&[("TERM", "xterm")]), None);
+        assert_eq!(
+            mode(&[("TERM", "dumb"), ("FORCE_COLOR", "3")]),
+            Some(Mode::Always)
+        );
+    }
+
+    fn terminfo(magic: u16, names: &str, colors: i32) -> Vec<u8> {
+        let number_size = if magic == 0o432 { 2 } else { 4 };
+        let mut data = Vec::new();
+        for value in [magic as i16, names.len() as i16 + 1, 1, 14, 0, 0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(names.as_bytes());
+        data.push(0);
+        // a single boolean capability
+        data.push(1);
+        if data.len() % 2 != 0 {
+            data.push(0);
+        }
+        for i in 0..14 {
+            let value = if i == 13 { colors } else { -1 };
+            data.extend_from_slice(&value.to_le_bytes()[..number_size]);
+        }
+        data
+    }
+
+    #[test]
+    fn parse_terminfo() {
+        assert_eq!(parse_terminfo_colors(&terminfo(0o432, "xterm", 8)), Some(8));
+        assert_eq!(
+            parse_terminfo_colors(&terminfo(0o432, "xterm-256", 256)),
+            Some(256)
+        );
+        assert_eq!(parse_terminfo_colors(&terminfo(0o432, "none", -1)), None);
+        assert_eq!(
+            parse_terminfo_colors(&terminfo(0o1036, "xterm-direct", 0x100_0000)),
+            Some(0x100_0000)
+        );
+        assert_eq!(parse_terminfo_colors(&[0x1a, 0x01, 0x00]), None);
+        assert_eq!(parse_terminfo_colors(b"not a terminfo file"), None);
+    }
+
+    /// Create a terminfo database with the given entries (`sub-directory/name`), private to the test `name`
+    fn terminfo_fixture(name: &str, entries: &[(&str, Vec<u8>)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(std::format!(
+            "colorz-terminfo-{name}-{}",
+            std::process::id()
+        ));
+
+        for (path, data) in entries {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, data).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn terminfo_lookup() {
+        let dir = terminfo_fixture(
+            "lookup",
+            &[
+                ("f/fake-term", terminfo(0o432, "fake-term", 256)),
+                ("62/bogus", terminfo(0o1036, "bogus", 1 << 24)),
+            ],
+        );
+
+        let terminfo = String::from(dir.to_str().unwrap());
+        let env = |term| [("TERM", term), ("TERMINFO", &*terminfo)];
+
+        assert_eq!(detect_level(&env("fake-term"), true), Level::Xterm);
+        assert_eq!(detect_level(&env("bogus"), true), Level::Rgb);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn terminfo_dirs_replace_system_dirs() {
+        let dir = terminfo_fixture("dirs", &[("x/xterm", terminfo(0o432, "xterm", 256))]);
+
+        let terminfo_dirs = String::from(dir.to_str().unwrap());
+        let env = |term| [("TERM", term), ("TERMINFO_DIRS", &*terminfo_dirs)];
+
+        // the fixture wins over the name, and the system entry for `linux` isn't found
+        assert_eq!(detect_level(&env("xterm"), true), Level::Xterm);
+        assert_eq!(detect_level(&env("linux"), true), Level::Ansi);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn not_a_terminal() {
+        assert_eq!(
+            detect_level(&[("TERM", "xterm-256color")], false),
+            Level::None
+        );
+        assert_eq!(detect_level(&[("CI", "true")], false), Level::None);
+        assert_eq!(
+            detect_level(&[("CI", "true"), ("GITLAB_CI", "true")], false),
+            Level::Ansi
+        );
+        assert_eq!(
+            detect_level(&[("CI", "true"), ("GITHUB_ACTIONS", "true")], false),
+            Level::Rgb
+        );
+    }
+
+    #[test]
+    fn dumb_terminal() {
+        assert_eq!(detect_level(&[("TERM", "dumb")], true), Level::None);
+        assert_eq!(
+            detect_level(&[("TERM", "dumb"), ("COLORTERM", "truecolor")], true),
+            Level::None
+        );
+        assert_eq!(
+            detect_level(
+                &[("TERM", "dumb"), ("CI", "1"), ("GITHUB_ACTIONS", "1")],
+                false
+            ),
+            Level::None
+        );
+    }
+
+    #[test]
+    fn truecolor() {
+        assert_eq!(
+            detect_level(&[("TERM", "xterm"), ("COLORTERM", "truecolor")], true),
+            Level::Rgb
+        );
+        assert_eq!(detect_level(&[("COLORTERM", "24bit")], true), Level::Rgb);
+        assert_eq!(
+            detect_level(&[("TERM_PROGRAM", "iTerm.app")], true),
+            Level::Rgb
+        );
+        assert_eq!(detect_level(&[("TERM", "xterm-direct")], true), Level::Rgb);
+    }
+
+    #[test]
+    fn term_names() {
+        // an empty terminfo database, so only the names are used
+        let dir = terminfo_fixture("names", &[]);
+        let terminfo_dirs = String::from(dir.to_str().unwrap());
+        let env = |term| [("TERM", term), ("TERMINFO_DIRS", &*terminfo_dirs)];
+
+        assert_eq!(
+            detect_level(&[("TERM_PROGRAM", "Apple_Terminal")], true),
+            Level::Xterm
+        );
+        assert_eq!(detect_level(&env("xterm-256color"), true), Level::Xterm);
+        assert_eq!(detect_level(&env("screen-256color"), true), Level::Xterm);
+        assert_eq!(detect_level(&env("xterm"), true), Level::Ansi);
+        assert_eq!(detect_level(&env("linux"), true), Level::Ansi);
+        assert_eq!(detect_level(&env("unknown-terminal"), true), Level::None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
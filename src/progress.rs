@@ -0,0 +1,700 @@
+//! Progress bars and spinners
+//!
+//! [`ProgressBar`] and [`Spinner`] render a single frame with [`Display`], which works without
+//! `std`. [`redraw`] wraps a frame so that it overwrites the current line (with `\r` and `CSI K`),
+//! but only when the stream is a terminal, as decided by [`mode::should_color`]. Otherwise
+//! nothing is written at all, so progress doesn't end up in logs or pipes.
+//!
+//! ```rust
+//! use colorz::{ansi, progress::ProgressBar, Style};
+//!
+//! let bar = ProgressBar::new(200)
+//!     .width(20)
+//!     .filled_style(Style::new().fg(ansi::Green).into_runtime_style())
+//!     .position(50);
+//!
+//! eprint!("{}", bar.redraw());
+//! ```
+//!
+//! With the `std` feature, [`ProgressBar::run`] and [`Spinner::run`] redraw the frame from a
+//! background thread while some work is done, and hide the cursor while they draw.
+//!
+//! ```rust
+//! # #[cfg(feature = "std")]
+//! # fn main() {
+//! use colorz::progress::ProgressBar;
+//!
+//! let sum = ProgressBar::new(100).run(std::time::Duration::from_millis(50), |progress| {
+//!     let mut sum = 0;
+//!     for i in 0..100 {
+//!         sum += i;
+//!         progress.inc(1);
+//!     }
+//!     sum
+//! });
+//!
+//! assert_eq!(sum, 4950);
+//! # }
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
+//! ```
+
+use core::fmt::{self, Display, Write};
+
+#[cfg(feature = "std")]
+use crate::control::{HideCursor, ShowCursor};
+use crate::{control::ClearToEndOfLine, mode, mode::Stream, rgb::RgbColor, Color, Colorize, Style};
+
+/// Is progress drawn on this stream (only if it's a terminal, or coloring is forced)
+fn is_drawn(stream: Stream) -> bool {
+    mode::should_color(Some(stream), &[])
+}
+
+/// A frame which overwrites the current line, see [`redraw`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Redraw<T> {
+    value: T,
+    stream: Stream,
+}
+
+/// Overwrite the current line with `value`, if `stream` is a terminal
+///
+/// This writes `\r`, the value, then `CSI K` to clear the rest of the line. If progress
+/// shouldn't be drawn on the stream (see the [module docs](self)), this writes nothing.
+///
+/// ```rust
+/// use colorz::{mode::Stream, progress::redraw};
+///
+/// # #[cfg(not(feature = "strip-colors"))]
+/// assert_eq!(redraw("loading", Stream::AlwaysColor).to_string(), "\rloading\x1b[K");
+/// assert_eq!(redraw("loading", Stream::NeverColor).to_string(), "");
+/// ```
+#[inline]
+pub const fn redraw<T: Display>(value: T, stream: Stream) -> Redraw<T> {
+    Redraw { value, stream }
+}
+
+impl<T: Display> Display for Redraw<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_drawn(self.stream) {
+            write!(f, "\r{}{}", self.value, ClearToEndOfLine::ESCAPE)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A char repeated a number of times
+struct Repeat(char, usize);
+
+impl Display for Repeat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (0..self.1).try_for_each(|_| f.write_char(self.0))
+    }
+}
+
+/// A progress bar, see the [module docs](self) for details
+///
+/// It's shown as `width` cells followed by the percentage, like `████████░░░░░░░░  50%`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProgressBar {
+    position: u64,
+    length: u64,
+    width: usize,
+    filled_char: char,
+    empty_char: char,
+    filled: Style,
+    empty: Style,
+    gradient: Option<(RgbColor, RgbColor)>,
+    percent: bool,
+    stream: Stream,
+}
+
+impl ProgressBar {
+    /// Create a progress bar for `length` steps, 40 cells wide, which is drawn on [`Stream::Stderr`]
+    #[inline]
+    pub const fn new(length: u64) -> Self {
+        Self {
+            position: 0,
+            length,
+            width: 40,
+            filled_char: '█',
+            empty_char: '░',
+            filled: Style::new().const_into_runtime_style(),
+            empty: Style::new().dimmed().const_into_runtime_style(),
+            gradient: None,
+            percent: true,
+            stream: Stream::Stderr,
+        }
+    }
+
+    /// Set the number of steps which are done
+    #[inline]
+    pub const fn position(mut self, position: u64) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Set the total number of steps
+    #[inline]
+    pub const fn length(mut self, length: u64) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Set the number of cells in the bar
+    #[inline]
+    pub const fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Set the chars used for the filled and empty cells (`█` and `░` by default)
+    #[inline]
+    pub const fn chars(mut self, filled: char, empty: char) -> Self {
+        self.filled_char = filled;
+        self.empty_char = empty;
+        self
+    }
+
+    /// Set the style of the filled cells
+    #[inline]
+    pub const fn filled_style(mut self, style: Style) -> Self {
+        self.filled = style;
+        self
+    }
+
+    /// Set the style of the empty cells (dimmed by default)
+    #[inline]
+    pub const fn empty_style(mut self, style: Style) -> Self {
+        self.empty = style;
+        self
+    }
+
+    /// Color the filled cells with a gradient from `start` (the first cell) to `end` (the last cell)
+    ///
+    /// This replaces the foreground of the [filled style](Self::filled_style), and falls back to
+    /// the filled style if the stream doesn't support Rgb colors
+    #[inline]
+    pub const fn gradient(mut self, start: RgbColor, end: RgbColor) -> Self {
+        self.gradient = Some((start, end));
+        self
+    }
+
+    /// Show the percentage after the bar (on by default)
+    #[inline]
+    pub const fn percent(mut self, percent: bool) -> Self {
+        self.percent = percent;
+        self
+    }
+
+    /// Set the stream the bar is drawn on, which decides if it is colored and drawn at all
+    #[inline]
+    pub const fn stream(mut self, stream: Stream) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    /// The number of filled cells, and the percentage done
+    const fn progress(&self) -> (usize, u64) {
+        if self.length == 0 {
+            return (self.width, 100);
+        }
+
+        let position = if self.position < self.length {
+            self.position as u128
+        } else {
+            self.length as u128
+        };
+
+        let length = self.length as u128;
+        let filled = position * self.width as u128 / length;
+        let percent = position * 100 / length;
+
+        (filled as usize, percent as u64)
+    }
+
+    /// Overwrite the current line with this bar, see [`redraw`]
+    #[inline]
+    pub const fn redraw(&self) -> Redraw<&Self> {
+        redraw(self, self.stream)
+    }
+}
+
+/// The color of a cell in a gradient
+const fn lerp(start: RgbColor, end: RgbColor, cell: usize, width: usize) -> RgbColor {
+    const fn channel(start: u8, end: u8, cell: usize, width: usize) -> u8 {
+        if width <= 1 {
+            return start;
+        }
+
+        let delta = end as i64 - start as i64;
+        (start as i64 + delta * cell as i64 / (width as i64 - 1)) as u8
+    }
+
+    RgbColor {
+        red: channel(start.red, end.red, cell, width),
+        green: channel(start.green, end.green, cell, width),
+        blue: channel(start.blue, end.blue, cell, width),
+    }
+}
+
+impl Display for ProgressBar {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (filled, percent) = self.progress();
+
+        let gradient = self
+            .gradient
+            .filter(|&(start, _)| Style::new().fg(Color::Rgb(start)).should_color(self.stream));
+
+        match gradient {
+            Some((start, end)) if filled != 0 => {
+                let mut style = self.filled;
+
+                for cell in 0..filled {
+                    style.foreground = Some(Color::Rgb(lerp(start, end, cell, self.width)));
+                    write!(f, "{}{}", style.apply(), self.filled_char)?;
+                }
+
+                style.clear().fmt(f)?;
+            }
+            Some(_) => (),
+            None if filled != 0 => Repeat(self.filled_char, filled)
+                .style_with(self.filled)
+                .stream(self.stream)
+                .fmt(f)?,
+            None => (),
+        }
+
+        if filled != self.width {
+            Repeat(self.empty_char, self.width - filled)
+                .style_with(self.empty)
+                .stream(self.stream)
+                .fmt(f)?;
+        }
+
+        if self.percent {
+            write!(f, " {percent:>3}%")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A spinner which shows one frame of an animation at a time, see the [module docs](self) for details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Spinner {
+    frames: &'static [&'static str],
+    frame: usize,
+    style: Style,
+    stream: Stream,
+}
+
+impl Default for Spinner {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Spinner {
+    /// Braille dots going around in a circle (the default)
+    pub const DOTS: &'static [&'static str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+    /// An ASCII line spinning around
+    pub const LINE: &'static [&'static str] = &["-", "\\", "|", "/"];
+
+    /// Create a spinner with the [`DOTS`](Self::DOTS) frames, which is drawn on [`Stream::Stderr`]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            frames: Self::DOTS,
+            frame: 0,
+            style: Style::new().const_into_runtime_style(),
+            stream: Stream::Stderr,
+        }
+    }
+
+    /// Set the frames of the animation
+    #[inline]
+    pub const fn frames(mut self, frames: &'static [&'static str]) -> Self {
+        self.frames = frames;
+        self
+    }
+
+    /// Set the style of the spinner
+    #[inline]
+    pub const fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the stream the spinner is drawn on, which decides if it is colored and drawn at all
+    #[inline]
+    pub const fn stream(mut self, stream: Stream) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    /// Move on to the next frame
+    #[inline]
+    pub const fn tick(&mut self) {
+        self.frame = self.frame.wrapping_add(1);
+    }
+
+    /// Overwrite the current line with this spinner, see [`redraw`]
+    #[inline]
+    pub const fn redraw(&self) -> Redraw<&Self> {
+        redraw(self, self.stream)
+    }
+}
+
+impl Display for Spinner {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.frames.is_empty() {
+            return Ok(());
+        }
+
+        let frame = self.frames[self.frame % self.frames.len()];
+        frame.style_with(self.style).stream(self.stream).fmt(f)
+    }
+}
+
+/// A handle to update a [`ProgressBar`] while it's being drawn by [`ProgressBar::run`]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct Progress {
+    position: std::sync::atomic::AtomicU64,
+}
+
+#[cfg(feature = "std")]
+impl Progress {
+    /// Add to the number of steps which are done
+    #[inline]
+    pub fn inc(&self, steps: u64) {
+        self.position
+            .fetch_add(steps, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Set the number of steps which are done
+    #[inline]
+    pub fn set(&self, position: u64) {
+        self.position
+            .store(position, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// The number of steps which are done
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.position.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ProgressBar {
+    /// Do some work while the bar is redrawn every `interval` on a background thread
+    ///
+    /// The bar is drawn on stdout for [`Stream::Stdout`] and on stderr otherwise. The cursor
+    /// is hidden until the work is done, then the bar is drawn one last time, followed by a newline.
+    /// If the bar isn't drawn on the stream (see the [module docs](self)), the work is
+    /// done without any background thread.
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[inline]
+    pub fn run<T>(self, interval: std::time::Duration, work: impl FnOnce(&Progress) -> T) -> T {
+        let progress = Progress {
+            position: std::sync::atomic::AtomicU64::new(self.position),
+        };
+
+        draw_loop(
+            self.stream,
+            interval,
+            |line| {
+                let _ = write!(line, "{}", self.position(progress.position()));
+            },
+            |s| write_to(self.stream, s),
+            || work(&progress),
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl Spinner {
+    /// Do some work while the spinner and a message are redrawn every `interval` on a background thread
+    ///
+    /// This works like [`ProgressBar::run`], and the spinner moves on to the next frame each time
+    /// it's drawn.
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[inline]
+    pub fn run<T>(
+        mut self,
+        message: &str,
+        interval: std::time::Duration,
+        work: impl FnOnce() -> T,
+    ) -> T {
+        let stream = self.stream;
+
+        draw_loop(
+            stream,
+            interval,
+            |line| {
+                let _ = write!(line, "{self} {message}");
+                self.tick();
+            },
+            |s| write_to(stream, s),
+            work,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+fn write_to(stream: Stream, s: &str) {
+    use std::io::Write;
+
+    // drawing progress is best effort, so errors are ignored
+    let _ = match stream {
+        Stream::Stdout => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(s.as_bytes()).and_then(|()| stdout.flush())
+        }
+        _ => {
+            let mut stderr = std::io::stderr().lock();
+            stderr.write_all(s.as_bytes()).and_then(|()| stderr.flush())
+        }
+    };
+}
+
+#[cfg(feature = "std")]
+fn draw_loop<T>(
+    stream: Stream,
+    interval: std::time::Duration,
+    mut render: impl FnMut(&mut std::string::String) + Send,
+    mut output: impl FnMut(&str) + Send,
+    work: impl FnOnce() -> T,
+) -> T {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Stops the drawing thread when the work is done, even if it panics
+    struct Finish<'a> {
+        done: &'a AtomicBool,
+        thread: std::thread::Thread,
+    }
+
+    impl Drop for Finish<'_> {
+        fn drop(&mut self) {
+            self.done.store(true, Ordering::Release);
+            self.thread.unpark();
+        }
+    }
+
+    if !is_drawn(stream) {
+        return work();
+    }
+
+    let done = AtomicBool::new(false);
+
+    std::thread::scope(|scope| {
+        let drawer = scope.spawn(|| {
+            let mut line = std::string::String::new();
+            output(HideCursor::ESCAPE);
+
+            loop {
+                let finished = done.load(Ordering::Acquire);
+
+                line.clear();
+                line.push('\r');
+                render(&mut line);
+                line.push_str(ClearToEndOfLine::ESCAPE);
+                output(&line);
+
+                if finished {
+                    break;
+                }
+
+                std::thread::park_timeout(interval);
+            }
+
+            output("\n");
+            output(ShowCursor::ESCAPE);
+        });
+
+        let _finish = Finish {
+            done: &done,
+            thread: drawer.thread().clone(),
+        };
+
+        work()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::{format, string::ToString};
+
+    use super::{redraw, ProgressBar, Spinner};
+    use crate::{ansi, mode::Stream, rgb::RgbColor, Style};
+
+    #[test]
+    fn bar() {
+        let bar = ProgressBar::new(8)
+            .width(4)
+            .chars('#', '-')
+            .stream(Stream::NeverColor);
+
+        assert_eq!(bar.to_string(), "----   0%");
+        assert_eq!(bar.position(3).to_string(), "#---  37%");
+        assert_eq!(bar.position(8).to_string(), "#### 100%");
+        assert_eq!(bar.position(20).to_string(), "#### 100%");
+        assert_eq!(bar.length(0).percent(false).to_string(), "####");
+        assert_eq!(bar.redraw().to_string(), "");
+    }
+
+    #[test]
+    fn styled() {
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        let bar = ProgressBar::new(4)
+            .width(4)
+            .position(3)
+            .chars('#', '-')
+            .filled_style(Style::new().fg(ansi::Green).into_runtime_style())
+            .percent(false)
+            .stream(Stream::AlwaysColor);
+
+        assert_eq!(
+            bar.redraw().to_string(),
+            "\r\x1b[32m###\x1b[39m\x1b[2m-\x1b[22m\x1b[K"
+        );
+
+        let bar = bar.gradient(
+            RgbColor {
+                red: 0,
+                green: 0,
+                blue: 0,
+            },
+            RgbColor {
+                red: 30,
+                green: 60,
+                blue: 90,
+            },
+        );
+
+        assert_eq!(
+            bar.to_string(),
+            "\x1b[38;2;0;0;0m#\x1b[38;2;10;20;30m#\x1b[38;2;20;40;60m#\x1b[39m\x1b[2m-\x1b[22m"
+        );
+    }
+
+    #[test]
+    fn spinner() {
+        let mut spinner = Spinner::new()
+            .frames(Spinner::LINE)
+            .stream(Stream::NeverColor);
+
+        let mut frames = std::vec::Vec::new();
+        for _ in 0..5 {
+            frames.push(spinner.to_string());
+            spinner.tick();
+        }
+
+        assert_eq!(frames, ["-", "\\", "|", "/", "-"]);
+        assert_eq!(spinner.frames(&[]).to_string(), "");
+
+        if !cfg!(feature = "strip-colors") {
+            let line = format!("{} done", spinner.stream(Stream::AlwaysColor));
+            assert_eq!(
+                redraw(line, Stream::AlwaysColor).to_string(),
+                "\r\\ done\x1b[K"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn run() {
+        let interval = std::time::Duration::from_millis(1);
+
+        let position = ProgressBar::new(10)
+            .stream(Stream::NeverColor)
+            .run(interval, |progress| {
+                progress.inc(3);
+                progress.inc(4);
+                progress.position()
+            });
+
+        assert_eq!(position, 7);
+
+        let value = Spinner::new()
+            .stream(Stream::NeverColor)
+            .run("working", interval, || 42);
+
+        assert_eq!(value, 42);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn draw_loop() {
+        use core::fmt::Write;
+        use std::sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Mutex,
+        };
+
+        use super::Progress;
+        use crate::testing::VirtualTerminal;
+
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        let filled = Style::new().fg(ansi::Green).into_runtime_style();
+        let bar = ProgressBar::new(4)
+            .width(4)
+            .chars('#', '-')
+            .filled_style(filled)
+            .percent(false)
+            .stream(Stream::AlwaysColor);
+
+        let progress = Progress {
+            position: AtomicU64::new(0),
+        };
+        let term = Mutex::new(VirtualTerminal::new(10, 3));
+        let hidden = AtomicBool::new(false);
+
+        let value = super::draw_loop(
+            Stream::AlwaysColor,
+            std::time::Duration::from_millis(1),
+            |line| {
+                let _ = write!(line, "{}", bar.position(progress.position()));
+            },
+            |s| {
+                let mut term = term.lock().unwrap();
+                term.write_str(s);
+                hidden.fetch_or(!term.cursor_visible(), Ordering::Relaxed);
+            },
+            || {
+                progress.inc(3);
+                42
+            },
+        );
+
+        assert_eq!(value, 42);
+
+        // every frame overwrites the same line, and the last one is drawn after the work is done
+        let term = term.into_inner().unwrap();
+        term.assert_row_text(0, "###-");
+        term.assert_cell(0, 0, '#', filled);
+        term.assert_row_text(1, "");
+        assert_eq!(term.cursor(), (1, 0));
+
+        assert!(hidden.into_inner());
+        assert!(term.cursor_visible());
+    }
+}
@@ -1,9 +1,6 @@
 //! 48-bit color values. Not as widely supported as standard ANSI or Xterm.
 
-use crate::{ColorSpec, WriteColor};
-
-#[cfg(doc)]
-use crate::Color;
+use crate::{Color, ColorSpec, StyleEscape, WriteColor};
 
 /// An Rgb value for color
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -65,7 +62,7 @@ impl RgbBuffer {
         self.len += 1;
     }
 
-    fn write_escape_end(&mut self) {
+    const fn write_escape_end(&mut self) {
         self.write_char(b'm')
     }
 
@@ -74,7 +71,7 @@ impl RgbBuffer {
         self.len += s.len() as u8;
     }
 
-    fn write_char(&mut self, x: u8) {
+    const fn write_char(&mut self, x: u8) {
         self.data[self.len as usize] = x;
         self.len += 1;
     }
@@ -213,6 +210,21 @@ impl WriteColor for RgbColor {
         buffer.write_escape_end();
         f.write_str(buffer.to_str())
     }
+
+    #[inline]
+    fn push_foreground_args(self, escape: &mut StyleEscape) {
+        escape.push_foreground(Color::Rgb(self))
+    }
+
+    #[inline]
+    fn push_background_args(self, escape: &mut StyleEscape) {
+        escape.push_background(Color::Rgb(self))
+    }
+
+    #[inline]
+    fn push_underline_args(self, escape: &mut StyleEscape) {
+        escape.push_underline(Color::Rgb(self))
+    }
 }
 
 const fn const_rgb_buffer(first: u8, r: u8, g: u8, b: u8) -> RgbBuffer {
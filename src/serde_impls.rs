@@ -0,0 +1,807 @@
+use core::fmt;
+
+use serde::{
+    de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor},
+    ser::{Error as _, Serialize, SerializeSeq, SerializeStruct, Serializer},
+};
+
+use crate::{
+    mode::{Mode, Stream},
+    rgb::RgbColor,
+    Color, Effect, EffectFlags, Style,
+};
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+impl Serialize for Color {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Color::Ansi(color) => serializer.serialize_str(color.name()),
+            Color::Xterm(color) => {
+                let code = color as u8;
+                let buf = [b'0' + code / 100, b'0' + code / 10 % 10, b'0' + code % 10];
+                let start = match code {
+                    0..=9 => 2,
+                    10..=99 => 1,
+                    100..=255 => 0,
+                };
+
+                serializer
+                    .serialize_str(core::str::from_utf8(&buf[start..]).map_err(S::Error::custom)?)
+            }
+            Color::Css(color) => serializer.serialize_str(color.name()),
+            Color::Rgb(RgbColor { red, green, blue }) => {
+                let mut buf = *b"#000000";
+
+                for (i, x) in [red, green, blue].into_iter().enumerate() {
+                    buf[1 + 2 * i] = HEX[usize::from(x >> 4)];
+                    buf[2 + 2 * i] = HEX[usize::from(x & 0xf)];
+                }
+
+                serializer.serialize_str(core::str::from_utf8(&buf).map_err(S::Error::custom)?)
+            }
+        }
+    }
+}
+
+struct ColorVisitor;
+
+impl Visitor<'_> for ColorVisitor {
+    type Value = Color;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a color name, a `#rrggbb` hex code or an xterm color code")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        match u8::try_from(v) {
+            Ok(code) => Ok(Color::Xterm(code.into())),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Unsigned(v), &self)),
+        }
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        match u8::try_from(v) {
+            Ok(code) => Ok(Color::Xterm(code.into())),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Signed(v), &self)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(ColorVisitor)
+        } else {
+            deserializer.deserialize_str(ColorVisitor)
+        }
+    }
+}
+
+impl Serialize for Effect {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+struct EffectVisitor;
+
+impl Visitor<'_> for EffectVisitor {
+    type Value = Effect;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an effect name")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Effect {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(EffectVisitor)
+    }
+}
+
+impl Serialize for EffectFlags {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.iter().count()))?;
+
+        for effect in *self {
+            seq.serialize_element(&effect)?;
+        }
+
+        seq.end()
+    }
+}
+
+struct EffectFlagsVisitor;
+
+impl<'de> Visitor<'de> for EffectFlagsVisitor {
+    type Value = EffectFlags;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of effect names")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut effects = EffectFlags::new();
+
+        while let Some(effect) = seq.next_element::<Effect>()? {
+            effects.set(effect);
+        }
+
+        Ok(effects)
+    }
+}
+
+impl<'de> Deserialize<'de> for EffectFlags {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(EffectFlagsVisitor)
+    }
+}
+
+const STYLE_FIELDS: &[&str] = &["foreground", "background", "underline_color", "effects"];
+
+/// Styles are serialized as a struct. In human readable formats unset colors and empty effects
+/// are skipped, in compact formats every field is written and the colors are [`Option`]s.
+impl<F, B, U> Serialize for Style<F, B, U>
+where
+    F: Into<Option<Color>> + Copy,
+    B: Into<Option<Color>> + Copy,
+    U: Into<Option<Color>> + Copy,
+{
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let style = (*self).into_runtime_style();
+        let colors = [style.foreground, style.background, style.underline_color];
+
+        if !serializer.is_human_readable() {
+            // formats which aren't self-describing need the same fields every time
+            let mut state = serializer.serialize_struct("Style", STYLE_FIELDS.len())?;
+
+            for (field, color) in STYLE_FIELDS.iter().zip(colors) {
+                state.serialize_field(field, &color)?;
+            }
+
+            state.serialize_field("effects", &style.effects)?;
+            return state.end();
+        }
+
+        let len = colors.iter().flatten().count() + usize::from(!style.effects.is_plain());
+
+        let mut state = serializer.serialize_struct("Style", len)?;
+
+        for (field, color) in STYLE_FIELDS.iter().zip(colors) {
+            match color {
+                Some(color) => state.serialize_field(field, &color)?,
+                None => state.skip_field(field)?,
+            }
+        }
+
+        if style.effects.is_plain() {
+            state.skip_field("effects")?;
+        } else {
+            state.serialize_field("effects", &style.effects)?;
+        }
+
+        state.end()
+    }
+}
+
+enum StyleField {
+    Foreground,
+    Background,
+    UnderlineColor,
+    Effects,
+}
+
+struct StyleFieldVisitor;
+
+impl Visitor<'_> for StyleFieldVisitor {
+    type Value = StyleField;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a style field")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(match v {
+            "foreground" | "fg" => StyleField::Foreground,
+            "background" | "bg" => StyleField::Background,
+            "underline_color" | "ul" => StyleField::UnderlineColor,
+            "effects" => StyleField::Effects,
+            _ => return Err(E::unknown_field(v, STYLE_FIELDS)),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for StyleField {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(StyleFieldVisitor)
+    }
+}
+
+struct StyleVisitor {
+    // compact formats write the colors as `Option`s
+    human_readable: bool,
+}
+
+impl StyleVisitor {
+    fn next_color<'de, A: MapAccess<'de>>(&self, map: &mut A) -> Result<Option<Color>, A::Error> {
+        if self.human_readable {
+            map.next_value().map(Some)
+        } else {
+            map.next_value()
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for StyleVisitor {
+    type Value = Style;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a style struct or a style string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut style = Style::new().into_runtime_style();
+
+        while let Some(field) = map.next_key()? {
+            match field {
+                StyleField::Foreground => style.foreground = self.next_color(&mut map)?,
+                StyleField::Background => style.background = self.next_color(&mut map)?,
+                StyleField::UnderlineColor => style.underline_color = self.next_color(&mut map)?,
+                StyleField::Effects => style.effects = map.next_value()?,
+            }
+        }
+
+        Ok(style)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut next = |index| {
+            seq.next_element()?
+                .ok_or_else(|| de::Error::invalid_length(index, &"a style with 4 fields"))
+        };
+
+        Ok(Style {
+            foreground: next(0)?,
+            background: next(1)?,
+            underline_color: next(2)?,
+            effects: seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(3, &"a style with 4 fields"))?,
+        })
+    }
+}
+
+/// Styles can be deserialized from a struct (with optional fields), or from a
+/// string in human readable formats (see [`Style`'s `FromStr` impl](Style#fromstr))
+impl<'de> Deserialize<'de> for Style {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(StyleVisitor {
+                human_readable: true,
+            })
+        } else {
+            deserializer.deserialize_struct(
+                "Style",
+                STYLE_FIELDS,
+                StyleVisitor {
+                    human_readable: false,
+                },
+            )
+        }
+    }
+}
+
+impl Serialize for Mode {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Mode::Detect => "detect",
+            Mode::Always => "always",
+            Mode::Never => "never",
+        })
+    }
+}
+
+struct ModeVisitor;
+
+impl Visitor<'_> for ModeVisitor {
+    type Value = Mode;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(r#""detect", "always" or "never""#)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Mode::from_ascii_bytes(v.as_bytes()).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Mode::from_ascii_bytes(v).map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Mode {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(ModeVisitor)
+    }
+}
+
+/// Registered handles ([`Stream::Handle`]) are only valid in the process
+/// that registered them, so they can't be serialized
+impl Serialize for Stream {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+            Stream::AlwaysColor => "always",
+            Stream::NeverColor => "never",
+            #[cfg(feature = "std")]
+            Stream::Handle(_) => {
+                return Err(S::Error::custom("registered handles can't be serialized"))
+            }
+        })
+    }
+}
+
+struct StreamVisitor;
+
+impl Visitor<'_> for StreamVisitor {
+    type Value = Stream;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(r#""stdout", "stderr", "always" or "never""#)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Stream::from_ascii_bytes(v.as_bytes()).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Stream::from_ascii_bytes(v).map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Stream {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(StreamVisitor)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Serialize for crate::theme::Theme {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "alloc")]
+struct ThemeVisitor;
+
+#[cfg(feature = "alloc")]
+impl<'de> Visitor<'de> for ThemeVisitor {
+    type Value = crate::theme::Theme;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of names to styles")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut theme = crate::theme::Theme::new();
+
+        while let Some((name, style)) = map.next_entry::<alloc::string::String, Style>()? {
+            theme.insert(name, style);
+        }
+
+        Ok(theme)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de> Deserialize<'de> for crate::theme::Theme {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(ThemeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use serde_test::{
+        assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, assert_tokens, Configure,
+        Readable, Token,
+    };
+
+    use crate::{
+        ansi::AnsiColor,
+        css::CssColor,
+        mode::{Mode, Stream},
+        rgb::RgbColor,
+        xterm::XtermColor,
+        Color, Effect, EffectFlags, Style,
+    };
+
+    const ANSI: [AnsiColor; 17] = [
+        AnsiColor::Black,
+        AnsiColor::Red,
+        AnsiColor::Green,
+        AnsiColor::Yellow,
+        AnsiColor::Blue,
+        AnsiColor::Magenta,
+        AnsiColor::Cyan,
+        AnsiColor::White,
+        AnsiColor::BrightBlack,
+        AnsiColor::BrightRed,
+        AnsiColor::BrightGreen,
+        AnsiColor::BrightYellow,
+        AnsiColor::BrightBlue,
+        AnsiColor::BrightMagenta,
+        AnsiColor::BrightCyan,
+        AnsiColor::BrightWhite,
+        AnsiColor::Default,
+    ];
+
+    #[test]
+    fn color() {
+        for color in ANSI {
+            assert_tokens(&Color::Ansi(color).readable(), &[Token::Str(color.name())]);
+            assert_tokens(&Color::Ansi(color).compact(), &[Token::Str(color.name())]);
+        }
+
+        for code in 0..=255 {
+            let color = XtermColor::from_code(code);
+            let name = color.args().strip_prefix("5;").unwrap();
+            assert_eq!(name.parse::<u8>(), Ok(code));
+            assert_tokens(&Color::Xterm(color).readable(), &[Token::Str(name)]);
+            assert_tokens(&Color::Xterm(color).compact(), &[Token::Str(name)]);
+            assert_de_tokens(&Color::Xterm(color).readable(), &[Token::U64(code.into())]);
+        }
+
+        for &color in CssColor::ALL {
+            assert_tokens(&Color::Css(color).readable(), &[Token::Str(color.name())]);
+            assert_tokens(&Color::Css(color).compact(), &[Token::Str(color.name())]);
+        }
+
+        for (red, green, blue, s) in [
+            (0, 0, 0, "#000000"),
+            (0xff, 0x88, 0x00, "#ff8800"),
+            (0x12, 0xab, 0xef, "#12abef"),
+        ] {
+            let color = Color::Rgb(RgbColor { red, green, blue });
+            assert_tokens(&color.readable(), &[Token::Str(s)]);
+            assert_tokens(&color.compact(), &[Token::Str(s)]);
+        }
+
+        for x in 0..=255 {
+            let color = Color::Rgb(RgbColor {
+                red: x,
+                green: !x,
+                blue: x.rotate_left(4),
+            });
+            // tokens only hold static strings
+            let s = std::format!("#{x:02x}{:02x}{:02x}", !x, x.rotate_left(4)).leak();
+            assert_tokens(&color.readable(), &[Token::Str(s)]);
+            assert_tokens(&color.compact(), &[Token::Str(s)]);
+        }
+
+        assert_de_tokens(
+            &Color::Rgb(RgbColor {
+                red: 0xab,
+                green: 0xcd,
+                blue: 0xef,
+            })
+            .readable(),
+            &[Token::Str("#ABCDEF")],
+        );
+        assert_de_tokens_error::<Readable<Color>>(
+            &[Token::Str("rainbow")],
+            "Invalid color: unknown color",
+        );
+        assert_de_tokens_error::<Readable<Color>>(
+            &[Token::U64(256)],
+            "invalid value: integer `256`, expected a color name, a `#rrggbb` hex code or an xterm color code",
+        );
+    }
+
+    #[test]
+    fn effects() {
+        for effect in EffectFlags::all() {
+            assert_tokens(&effect, &[Token::Str(effect.name())]);
+            assert_tokens(
+                &EffectFlags::new().with(effect),
+                &[
+                    Token::Seq { len: Some(1) },
+                    Token::Str(effect.name()),
+                    Token::SeqEnd,
+                ],
+            );
+        }
+
+        assert_tokens(
+            &EffectFlags::new(),
+            &[Token::Seq { len: Some(0) }, Token::SeqEnd],
+        );
+        assert_tokens(
+            &EffectFlags::from_array([Effect::Bold, Effect::Italic, Effect::SubScript]),
+            &[
+                Token::Seq { len: Some(3) },
+                Token::Str("bold"),
+                Token::Str("italics"),
+                Token::Str("subscript"),
+                Token::SeqEnd,
+            ],
+        );
+        assert_de_tokens_error::<Effect>(
+            &[Token::Str("sparkle")],
+            r#"Invalid effect: valid options include "bold", "dimmed", "italics", "underline", "double_underline", "blink", "blink_fast", "reverse", "hide", "strikethrough", "overline", "superscript", "subscript""#,
+        );
+    }
+
+    #[test]
+    fn style() {
+        let style = Style::new().into_runtime_style();
+        assert_tokens(
+            &style.readable(),
+            &[
+                Token::Struct {
+                    name: "Style",
+                    len: 0,
+                },
+                Token::StructEnd,
+            ],
+        );
+
+        let style = Style::new()
+            .fg(Color::Ansi(AnsiColor::Red))
+            .bg(Color::Xterm(XtermColor::from_code(42)))
+            .underline_color(Color::Css(CssColor::AliceBlue))
+            .bold()
+            .underline()
+            .into_runtime_style();
+
+        let tokens = [
+            Token::Struct {
+                name: "Style",
+                len: 4,
+            },
+            Token::Str("foreground"),
+            Token::Str("red"),
+            Token::Str("background"),
+            Token::Str("42"),
+            Token::Str("underline_color"),
+            Token::Str("AliceBlue"),
+            Token::Str("effects"),
+            Token::Seq { len: Some(2) },
+            Token::Str("bold"),
+            Token::Str("underline"),
+            Token::SeqEnd,
+            Token::StructEnd,
+        ];
+
+        assert_tokens(&style.readable(), &tokens);
+        assert_tokens(
+            &style.compact(),
+            &[
+                Token::Struct {
+                    name: "Style",
+                    len: 4,
+                },
+                Token::Str("foreground"),
+                Token::Some,
+                Token::Str("red"),
+                Token::Str("background"),
+                Token::Some,
+                Token::Str("42"),
+                Token::Str("underline_color"),
+                Token::Some,
+                Token::Str("AliceBlue"),
+                Token::Str("effects"),
+                Token::Seq { len: Some(2) },
+                Token::Str("bold"),
+                Token::Str("underline"),
+                Token::SeqEnd,
+                Token::StructEnd,
+            ],
+        );
+        assert_de_tokens(
+            &style.readable(),
+            &[Token::Str("bold underline red bg:42 ul:AliceBlue")],
+        );
+        assert_de_tokens(
+            &style.readable(),
+            &[
+                Token::Map { len: None },
+                Token::Str("effects"),
+                Token::Seq { len: None },
+                Token::Str("underline"),
+                Token::Str("bold"),
+                Token::SeqEnd,
+                Token::Str("fg"),
+                Token::Str("red"),
+                Token::Str("bg"),
+                Token::U8(42),
+                Token::Str("ul"),
+                Token::Str("AliceBlue"),
+                Token::MapEnd,
+            ],
+        );
+
+        let style = Style::new()
+            .bg(crate::css::AliceBlue)
+            .blink()
+            .into_runtime_style();
+        assert_tokens(
+            &style.compact(),
+            &[
+                Token::Struct {
+                    name: "Style",
+                    len: 4,
+                },
+                Token::Str("foreground"),
+                Token::None,
+                Token::Str("background"),
+                Token::Some,
+                Token::Str("AliceBlue"),
+                Token::Str("underline_color"),
+                Token::None,
+                Token::Str("effects"),
+                Token::Seq { len: Some(1) },
+                Token::Str("blink"),
+                Token::SeqEnd,
+                Token::StructEnd,
+            ],
+        );
+
+        // compile time styles serialize like their runtime equivalent
+        assert_ser_tokens(
+            &Style::new().fg(crate::ansi::Red).readable(),
+            &[
+                Token::Struct {
+                    name: "Style",
+                    len: 1,
+                },
+                Token::Str("foreground"),
+                Token::Str("red"),
+                Token::StructEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn style_round_trip_compact() {
+        // bincode isn't self-describing, so styles have to be read back without looking at the data
+        let round_trip = |style: Style| {
+            let bytes = bincode::serialize(&style).unwrap();
+            assert_eq!(bincode::deserialize::<Style>(&bytes).unwrap(), style);
+        };
+
+        let colors = ANSI
+            .into_iter()
+            .map(Color::Ansi)
+            .chain((0..=255).map(|code| Color::Xterm(XtermColor::from_code(code))))
+            .chain(CssColor::ALL.iter().copied().map(Color::Css))
+            .chain((0..=255).map(|x| {
+                Color::Rgb(RgbColor {
+                    red: x,
+                    green: !x,
+                    blue: x.rotate_left(4),
+                })
+            }));
+
+        for color in colors {
+            round_trip(Style::new().fg(color).into_runtime_style());
+            round_trip(Style::new().bg(color).into_runtime_style());
+            round_trip(Style::new().underline_color(color).into_runtime_style());
+        }
+
+        for effect in EffectFlags::all() {
+            round_trip(Style::new().with(effect).into_runtime_style());
+        }
+
+        round_trip(Style::new().into_runtime_style());
+        round_trip(
+            Style::new()
+                .fg(Color::Ansi(AnsiColor::Red))
+                .bg(Color::Css(CssColor::AliceBlue))
+                .underline_color(Color::Rgb(RgbColor {
+                    red: 1,
+                    green: 2,
+                    blue: 3,
+                }))
+                .effect_flags(EffectFlags::all())
+                .into_runtime_style(),
+        );
+    }
+
+    #[test]
+    fn mode() {
+        assert_tokens(&Mode::Detect, &[Token::Str("detect")]);
+        assert_tokens(&Mode::Always, &[Token::Str("always")]);
+        assert_tokens(&Mode::Never, &[Token::Str("never")]);
+        assert_de_tokens(&Mode::Never, &[Token::Str("NEVER")]);
+        assert_de_tokens_error::<Mode>(
+            &[Token::Str("sometimes")],
+            r#"Invalid mode: valid options include "detect", "always", "never""#,
+        );
+    }
+
+    #[test]
+    fn stream() {
+        assert_tokens(&Stream::Stdout, &[Token::Str("stdout")]);
+        assert_tokens(&Stream::Stderr, &[Token::Str("stderr")]);
+        assert_tokens(&Stream::AlwaysColor, &[Token::Str("always")]);
+        assert_tokens(&Stream::NeverColor, &[Token::Str("never")]);
+        assert_de_tokens(&Stream::Stderr, &[Token::Bytes(b"stderr")]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn theme() {
+        let theme = crate::theme::Theme::parse("error = bold red; path = cyan").unwrap();
+
+        assert_tokens(
+            &theme.clone().readable(),
+            &[
+                Token::Map { len: Some(2) },
+                Token::Str("error"),
+                Token::Struct {
+                    name: "Style",
+                    len: 2,
+                },
+                Token::Str("foreground"),
+                Token::Str("red"),
+                Token::Str("effects"),
+                Token::Seq { len: Some(1) },
+                Token::Str("bold"),
+                Token::SeqEnd,
+                Token::StructEnd,
+                Token::Str("path"),
+                Token::Struct {
+                    name: "Style",
+                    len: 1,
+                },
+                Token::Str("foreground"),
+                Token::Str("cyan"),
+                Token::StructEnd,
+                Token::MapEnd,
+            ],
+        );
+        assert_de_tokens(
+            &theme.readable(),
+            &[
+                Token::Map { len: Some(2) },
+                Token::Str("path"),
+                Token::Str("cyan"),
+                Token::Str("error"),
+                Token::Str("red bold"),
+                Token::MapEnd,
+            ],
+        );
+    }
+}
@@ -0,0 +1,654 @@
+//! Strings made of differently styled spans, built at runtime
+//!
+//! A [`StyledString`] keeps the text and [`Style`] of each span separately, so it can be sliced,
+//! split into lines and measured without having to deal with escape sequences. When it's displayed,
+//! only the attributes which change between spans are written, instead of clearing and
+//! re-applying the whole style for every span.
+//!
+//! ```rust
+//! use colorz::{ansi, mode::Stream, string::StyledString, Style};
+//!
+//! let mut s = StyledString::new();
+//! s.push_styled("error", Style::new().fg(ansi::Red).bold());
+//! s.push_styled(": ", Style::new().bold());
+//! s.push_str("file not found");
+//!
+//! assert_eq!(s.plain(), "error: file not found");
+//! assert_eq!(s.width(), 21);
+//!
+//! let s = s.stream(Stream::AlwaysColor);
+//! # #[cfg(not(feature = "strip-colors"))]
+//! assert_eq!(s.to_string(), "\x1b[1;31merror\x1b[39m: \x1b[22mfile not found");
+//! ```
+//!
+//! Escaped text, like the output of other programs, can be turned back into a [`StyledString`]
+//! with [`StyledString::from_escaped`], and [`StyledString::to_escaped`] does the reverse.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Write};
+use core::ops::{Add, AddAssign, Bound, RangeBounds};
+
+use crate::{ansi::AnsiColor, mode::Stream, rgb::RgbColor, text, xterm::XtermColor};
+use crate::{style, Color, EffectFlags, Style, StyledValue};
+
+pub(crate) const PLAIN: Style = Style::new().const_into_runtime_style();
+
+const ANSI_COLORS: [AnsiColor; 16] = [
+    AnsiColor::Black,
+    AnsiColor::Red,
+    AnsiColor::Green,
+    AnsiColor::Yellow,
+    AnsiColor::Blue,
+    AnsiColor::Magenta,
+    AnsiColor::Cyan,
+    AnsiColor::White,
+    AnsiColor::BrightBlack,
+    AnsiColor::BrightRed,
+    AnsiColor::BrightGreen,
+    AnsiColor::BrightYellow,
+    AnsiColor::BrightBlue,
+    AnsiColor::BrightMagenta,
+    AnsiColor::BrightCyan,
+    AnsiColor::BrightWhite,
+];
+
+/// A piece of text with a single style, see [`StyledString`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// The text, without any escape sequences
+    pub text: String,
+    /// The style of the text
+    pub style: Style,
+}
+
+/// A string made of [`Span`]s, see the [module docs](self) for details
+///
+/// Adjacent spans with the same style are merged, and empty spans are left out.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct StyledString {
+    spans: Vec<Span>,
+    stream: Option<Stream>,
+}
+
+impl StyledString {
+    /// Create an empty string
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            spans: Vec::new(),
+            stream: None,
+        }
+    }
+
+    /// Set the stream used to decide if the spans are colored
+    #[inline]
+    pub const fn stream(mut self, stream: Stream) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    /// The spans of this string
+    #[inline]
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    /// Turn this string into its spans
+    #[inline]
+    pub fn into_spans(self) -> Vec<Span> {
+        self.spans
+    }
+
+    /// Append unstyled text
+    #[inline]
+    pub fn push_str(&mut self, text: &str) {
+        self.push_span(text, PLAIN)
+    }
+
+    /// Append text with a style
+    ///
+    /// ```rust
+    /// use colorz::{ansi, string::StyledString, Style};
+    ///
+    /// let mut s = StyledString::new();
+    /// s.push_styled("a", Style::new().fg(ansi::Red));
+    /// s.push_styled("b", Style::new().fg(ansi::Red));
+    ///
+    /// assert_eq!(s.spans().len(), 1);
+    /// ```
+    #[inline]
+    pub fn push_styled<F, B, U>(&mut self, text: &str, style: Style<F, B, U>)
+    where
+        F: Into<Option<Color>>,
+        B: Into<Option<Color>>,
+        U: Into<Option<Color>>,
+    {
+        self.push_span(text, style.into_runtime_style())
+    }
+
+    fn push_span(&mut self, text: &str, style: Style) {
+        if text.is_empty() {
+            return;
+        }
+
+        match self.spans.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(text),
+            _ => self.spans.push(Span {
+                text: text.into(),
+                style,
+            }),
+        }
+    }
+
+    /// Append all spans of another string
+    #[inline]
+    pub fn append(&mut self, other: &StyledString) {
+        for span in &other.spans {
+            self.push_span(&span.text, span.style)
+        }
+    }
+
+    /// The length of the text in bytes, without any escape sequences
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.spans.iter().map(|span| span.text.len()).sum()
+    }
+
+    /// Is there no text
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// The number of columns the text takes up in a terminal, see [`text::visible_width`]
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.spans
+            .iter()
+            .map(|span| text::visible_width(&span.text))
+            .sum()
+    }
+
+    /// The text without any styles
+    #[inline]
+    pub fn plain(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    /// The spans in a byte range of the text
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds, or doesn't start and end on char boundaries
+    ///
+    /// ```rust
+    /// use colorz::{ansi, string::StyledString, Style};
+    ///
+    /// let mut s = StyledString::new();
+    /// s.push_str("hello ");
+    /// s.push_styled("world", Style::new().fg(ansi::Red));
+    ///
+    /// let slice = s.slice(3..8);
+    /// assert_eq!(slice.plain(), "lo wo");
+    /// assert_eq!(slice.spans().len(), 2);
+    /// ```
+    #[inline]
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Self {
+        let len = self.len();
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => len,
+        };
+
+        assert!(
+            start <= end && end <= len,
+            "range {start}..{end} is out of bounds for a string of length {len}"
+        );
+
+        let mut slice = Self {
+            spans: Vec::new(),
+            stream: self.stream,
+        };
+        let mut offset = 0;
+
+        for span in &self.spans {
+            let span_end = offset + span.text.len();
+
+            if span_end > start && offset < end {
+                let from = start.saturating_sub(offset);
+                let to = end.min(span_end) - offset;
+                slice.push_span(&span.text[from..to], span.style);
+            }
+
+            offset = span_end;
+        }
+
+        slice
+    }
+
+    /// The byte offset of the first `\n` at or after `from`
+    fn find_newline(&self, from: usize) -> Option<usize> {
+        let mut offset = 0;
+
+        for span in &self.spans {
+            let span_end = offset + span.text.len();
+
+            if span_end > from {
+                let skip = from.saturating_sub(offset);
+                if let Some(i) = span.text[skip..].find('\n') {
+                    return Some(offset + skip + i);
+                }
+            }
+
+            offset = span_end;
+        }
+
+        None
+    }
+
+    /// Split the string into lines, like [`str::lines`]
+    ///
+    /// ```rust
+    /// use colorz::{ansi, string::StyledString, Style};
+    ///
+    /// let mut s = StyledString::new();
+    /// s.push_styled("a\nb", Style::new().fg(ansi::Red));
+    /// s.push_str("c\r\n");
+    ///
+    /// let lines: Vec<_> = s.lines().map(|line| line.plain()).collect();
+    /// assert_eq!(lines, ["a", "bc"]);
+    /// ```
+    #[inline]
+    pub const fn lines(&self) -> Lines<'_> {
+        Lines {
+            string: self,
+            start: 0,
+        }
+    }
+
+    /// Parse text with SGR escape sequences (`\x1b[...m`) into spans
+    ///
+    /// Colors which are written as RGB, like [`CssColor`](crate::css::CssColor)s, are parsed as
+    /// [`RgbColor`]s. All other escape sequences are left out.
+    ///
+    /// ```rust
+    /// use colorz::{ansi, string::StyledString, Style};
+    ///
+    /// let s = StyledString::from_escaped("a\x1b[1;31mb\x1b[0mc");
+    ///
+    /// let mut expected = StyledString::new();
+    /// expected.push_str("a");
+    /// expected.push_styled("b", Style::new().fg(ansi::Red).bold());
+    /// expected.push_str("c");
+    ///
+    /// assert_eq!(s, expected);
+    /// ```
+    #[inline]
+    pub fn from_escaped(s: &str) -> Self {
+        let mut string = Self::new();
+        let mut style = PLAIN;
+
+        for segment in text::segments(s) {
+            match segment {
+                text::Segment::Text(text) => string.push_span(text, style),
+                text::Segment::Escape(escape) => {
+                    if let Some(params) = escape
+                        .strip_prefix("\x1b[")
+                        .and_then(|escape| escape.strip_suffix('m'))
+                    {
+                        apply_sgr(&mut style, params)
+                    }
+                }
+            }
+        }
+
+        string
+    }
+
+    /// Write the text with escape sequences, regardless of the coloring mode
+    ///
+    /// ```rust
+    /// use colorz::{ansi, string::StyledString, Style};
+    ///
+    /// let mut s = StyledString::new();
+    /// s.push_styled("hi", Style::new().fg(ansi::Red));
+    ///
+    /// assert_eq!(s.to_escaped(), "\x1b[31mhi\x1b[39m");
+    /// assert_eq!(StyledString::from_escaped(&s.to_escaped()), s);
+    /// ```
+    #[inline]
+    pub fn to_escaped(&self) -> String {
+        let mut s = String::new();
+        // writing to a string can't fail
+        let _ = self.fmt_spans(&mut s, |_| true);
+        s
+    }
+
+    fn fmt_spans(&self, f: &mut dyn Write, should_color: impl Fn(&Style) -> bool) -> fmt::Result {
+        let mut current = PLAIN;
+
+        for span in &self.spans {
+            let style = if should_color(&span.style) {
+                span.style
+            } else {
+                PLAIN
+            };
+
+            style::fmt_transition(f, &current, &style)?;
+            f.write_str(&span.text)?;
+            current = style;
+        }
+
+        style::fmt_transition(f, &current, &PLAIN)
+    }
+}
+
+fn parse_code(param: &str) -> Option<u8> {
+    if param.is_empty() {
+        Some(0)
+    } else {
+        param.parse().ok()
+    }
+}
+
+/// Parse the rest of a `38`, `48` or `58` sequence
+fn parse_extended<'a>(params: &mut impl Iterator<Item = &'a str>) -> Option<Color> {
+    let mut next = || params.next().and_then(parse_code);
+
+    match next()? {
+        5 => Some(Color::Xterm(XtermColor::from_code(next()?))),
+        2 => Some(Color::Rgb(RgbColor {
+            red: next()?,
+            green: next()?,
+            blue: next()?,
+        })),
+        _ => None,
+    }
+}
+
+/// Apply the parameters of an SGR sequence to `style`, stopping at the first invalid one
+pub(crate) fn apply_sgr(style: &mut Style, params: &str) {
+    let mut params = params.split(';');
+
+    while let Some(param) = params.next() {
+        let Some(code) = parse_code(param) else {
+            return;
+        };
+
+        match code {
+            0 => *style = PLAIN,
+            30..=37 => style.foreground = Some(Color::Ansi(ANSI_COLORS[usize::from(code - 30)])),
+            90..=97 => style.foreground = Some(Color::Ansi(ANSI_COLORS[usize::from(code - 82)])),
+            40..=47 => style.background = Some(Color::Ansi(ANSI_COLORS[usize::from(code - 40)])),
+            100..=107 => style.background = Some(Color::Ansi(ANSI_COLORS[usize::from(code - 92)])),
+            39 => style.foreground = None,
+            49 => style.background = None,
+            59 => style.underline_color = None,
+            38 | 48 | 58 => {
+                let Some(color) = parse_extended(&mut params) else {
+                    return;
+                };
+
+                match code {
+                    38 => style.foreground = Some(color),
+                    48 => style.background = Some(color),
+                    _ => style.underline_color = Some(color),
+                }
+            }
+            _ => {
+                let matches = |args: &str| args.parse() == Ok(code);
+
+                if let Some(effect) = EffectFlags::all().iter().find(|e| matches(e.apply_args())) {
+                    style.effects.set(effect);
+                } else {
+                    EffectFlags::all()
+                        .iter()
+                        .filter(|e| matches(e.clear_args()))
+                        .for_each(|effect| style.effects.unset(effect));
+                }
+            }
+        }
+    }
+}
+
+impl Display for StyledString {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_spans(f, |style| style.should_color(self.stream))
+    }
+}
+
+impl From<&str> for StyledString {
+    #[inline]
+    fn from(text: &str) -> Self {
+        let mut string = Self::new();
+        string.push_str(text);
+        string
+    }
+}
+
+impl From<String> for StyledString {
+    #[inline]
+    fn from(text: String) -> Self {
+        text.as_str().into()
+    }
+}
+
+impl<T: Display, F, B, U> From<StyledValue<T, F, B, U>> for StyledString
+where
+    F: Into<Option<Color>>,
+    B: Into<Option<Color>>,
+    U: Into<Option<Color>>,
+{
+    #[inline]
+    fn from(value: StyledValue<T, F, B, U>) -> Self {
+        let mut text = String::new();
+        // writing to a string can't fail
+        let _ = write!(text, "{}", value.value);
+
+        let mut string = Self::new();
+        string.push_styled(&text, value.style);
+        string.stream = value.stream;
+        string
+    }
+}
+
+impl Extend<Span> for StyledString {
+    #[inline]
+    fn extend<I: IntoIterator<Item = Span>>(&mut self, spans: I) {
+        for span in spans {
+            self.push_span(&span.text, span.style)
+        }
+    }
+}
+
+impl FromIterator<Span> for StyledString {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = Span>>(spans: I) -> Self {
+        let mut string = Self::new();
+        string.extend(spans);
+        string
+    }
+}
+
+impl AddAssign<&StyledString> for StyledString {
+    #[inline]
+    fn add_assign(&mut self, other: &StyledString) {
+        self.append(other)
+    }
+}
+
+impl Add<&StyledString> for StyledString {
+    type Output = StyledString;
+
+    #[inline]
+    fn add(mut self, other: &StyledString) -> Self::Output {
+        self.append(other);
+        self
+    }
+}
+
+/// An iterator over the lines of a [`StyledString`], see [`StyledString::lines`]
+#[derive(Debug, Clone)]
+pub struct Lines<'a> {
+    string: &'a StyledString,
+    start: usize,
+}
+
+impl Iterator for Lines<'_> {
+    type Item = StyledString;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let len = self.string.len();
+        if self.start >= len {
+            return None;
+        }
+
+        let end = self.string.find_newline(self.start).unwrap_or(len);
+        let mut line = self.string.slice(self.start..end);
+        self.start = end + 1;
+
+        if let Some(last) = line.spans.last_mut() {
+            if last.text.ends_with('\r') {
+                last.text.pop();
+                if last.text.is_empty() {
+                    line.spans.pop();
+                }
+            }
+        }
+
+        Some(line)
+    }
+}
+
+impl core::iter::FusedIterator for Lines<'_> {}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{ansi, css, xterm, Colorize};
+
+    #[test]
+    fn minimal_escapes() {
+        let mut s = StyledString::new();
+        s.push_styled("a", Style::new().bold().dimmed());
+        s.push_styled("b", Style::new().dimmed().fg(ansi::Red));
+        s.push_styled("c", Style::new().dimmed().bg(ansi::Blue));
+        s.push_str("d");
+
+        assert_eq!(
+            s.to_escaped(),
+            "\x1b[1;2ma\x1b[22;2;31mb\x1b[39;44mc\x1b[22;49md"
+        );
+    }
+
+    #[test]
+    fn stream() {
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        let s = StyledString::from("hi".red().underline().underline_color(ansi::Blue));
+
+        assert_eq!(s.clone().stream(Stream::NeverColor).to_string(), "hi");
+        assert_eq!(
+            s.stream(Stream::AlwaysColor).to_string(),
+            "\x1b[4;31;58;5;4mhi\x1b[24;39;59m"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut s = StyledString::new();
+        s.push_styled("rgb", Style::new().fg(css::Red).bg(ansi::BrightBlack));
+        s.push_styled(
+            "xterm",
+            Style::new().underline_color(ansi::Red).double_underline(),
+        );
+        s.push_styled("effects", Style::new().blink().strikethrough().overline());
+
+        let parsed = StyledString::from_escaped(&s.to_escaped());
+
+        assert_eq!(parsed.plain(), "rgbxtermeffects");
+        assert_eq!(
+            parsed.spans()[0].style,
+            Style::new()
+                .fg(RgbColor {
+                    red: 255,
+                    green: 0,
+                    blue: 0
+                })
+                .bg(ansi::BrightBlack)
+                .into_runtime_style()
+        );
+        assert_eq!(parsed.slice(3..).to_escaped(), s.slice(3..).to_escaped());
+    }
+
+    #[test]
+    fn underline_color_round_trip() {
+        let aqua = Style::new().underline_color(xterm::Aqua);
+
+        let mut s = StyledString::new();
+        s.push_styled("a", aqua);
+        s.push_styled("b", aqua.underline());
+        s.push_styled("c", aqua.underline().fg(xterm::Aqua));
+        s.push_styled("d", Style::new().underline());
+        s.push_styled("e", aqua);
+        s.push_str("f");
+
+        let escaped = s.to_escaped();
+        assert_eq!(
+            escaped,
+            "\x1b[58;5;51ma\x1b[4mb\x1b[38;5;51mc\x1b[39;59md\x1b[24;58;5;51me\x1b[59mf"
+        );
+
+        let parsed = StyledString::from_escaped(&escaped);
+        assert_eq!(parsed.spans(), s.spans());
+        assert!(parsed.spans().last().unwrap().style.is_plain());
+        assert!(!parsed.spans()[0].style.is_plain());
+    }
+
+    #[test]
+    fn from_escaped() {
+        let s = StyledString::from_escaped("\x1b[1mA\x1b]0;title\x07\x1b[;4mB\x1b[38;5mC\x1b[K");
+
+        assert_eq!(s.spans().len(), 2);
+        assert_eq!(s.spans()[0].text, "A");
+        assert_eq!(s.spans()[1].text, "BC");
+        assert_eq!(
+            s.spans()[1].style,
+            Style::new().underline().into_runtime_style()
+        );
+    }
+
+    #[test]
+    fn slices_and_lines() {
+        let mut s = StyledString::new();
+        s.push_styled("日本", Style::new().bold());
+        s.push_str("\n\nx\r\n");
+
+        assert_eq!(s.width(), 5);
+        assert_eq!(s.slice(..).spans(), s.spans());
+        assert!(s.slice(3..3).is_empty());
+        assert_eq!(s.slice(3..=6).plain(), "本\n");
+
+        let lines: Vec<_> = s.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].spans(), s.slice(..6).spans());
+        assert!(lines[1].is_empty());
+        assert_eq!(lines[2].plain(), "x");
+
+        let joined = lines[0].clone() + &lines[2];
+        assert_eq!(joined.plain(), "日本x");
+    }
+}
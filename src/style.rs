@@ -1,4 +1,4 @@
-use core::{fmt, num::NonZeroU16};
+use core::{fmt, num::NonZeroU16, ops};
 
 use crate::{ansi, mode::Stream, Color, ComptimeColor, OptionalColor, WriteColor};
 
@@ -25,6 +25,31 @@ use crate::{ansi, mode::Stream, Color, ComptimeColor, OptionalColor, WriteColor}
 ///
 /// let x = "hello world".style_with(style);
 /// ```
+///
+/// # FromStr
+///
+/// A runtime [`Style`] can be parsed from a whitespace separated list of items
+/// * the [name of an effect](Effect::name), i.e. `bold` or `double_underline`
+/// * a [color](Color#fromstr), which sets the foreground color
+/// * a color prefixed with `fg:`, `bg:` or `ul:`, which sets the foreground, background or underline color respectively
+///
+/// If a color is specified more than once, the last one is used.
+///
+/// ```
+/// use colorz::{Style, ansi, css};
+///
+/// let style: Style = "bold red bg:bright black ul:AliceBlue".parse().unwrap();
+///
+/// assert_eq!(
+///     style,
+///     Style::new()
+///         .fg(ansi::Red)
+///         .bg(ansi::BrightBlack)
+///         .underline_color(css::AliceBlue)
+///         .bold()
+///         .into_runtime_style()
+/// );
+/// ```
 #[non_exhaustive]
 #[must_use = "A `Style` value doesn't do anything on it's own"]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -33,7 +58,7 @@ pub struct Style<F = Option<Color>, B = Option<Color>, U = Option<Color>> {
     pub foreground: F,
     /// The background color
     pub background: B,
-    /// The underline color
+    /// The underline color, which is written even without an underline effect
     pub underline_color: U,
     /// The various effects (like bold, italics, etc.)
     pub effects: EffectFlags,
@@ -90,6 +115,9 @@ macro_rules! Effect {
 
         const ALL_EFFECTS: EffectFlags = EffectFlags::new() $(.with(Effect::$name))*;
 
+        /// All effects, in the order of their bits in [`EffectFlags`]
+        const EFFECT_LIST: &[Effect] = &[$(Effect::$name,)*];
+
         impl Effect {
             fn decode(x: u8) -> Self {
                 #[cold]
@@ -104,6 +132,23 @@ macro_rules! Effect {
                 }
             }
 
+            /// The name of this effect, which is the name of the corresponding [`Style`] method
+            ///
+            /// This is the name accepted by [`Effect`]'s [`FromStr`](core::str::FromStr) impl
+            ///
+            /// ```
+            /// use colorz::Effect;
+            ///
+            /// assert_eq!(Effect::Bold.name(), "bold");
+            /// assert_eq!(Effect::DoubleUnderline.name(), "double_underline");
+            /// ```
+            #[inline]
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(Self::$name => stringify!($set_func),)*
+                }
+            }
+
             /// The ANSI effect args
             #[inline]
             pub const fn apply_args(self) -> &'static str {
@@ -136,6 +181,13 @@ macro_rules! Effect {
                 }
             }
 
+            pub(crate) const fn try_decode(x: u8) -> Option<Self> {
+                match x {
+                    $(val::$name => Some(Self::$name),)*
+                    _ => None,
+                }
+            }
+
             const fn mask(self) -> u16 {
                 1 << self as u8
             }
@@ -231,22 +283,60 @@ impl EffectFlags {
 
     /// Add an effect to the set in place
     #[inline(always)]
-    pub fn set(&mut self, opt: Effect) {
+    pub const fn set(&mut self, opt: Effect) {
         *self = self.with(opt)
     }
 
     /// Remove an effect from the set in place
     #[inline(always)]
-    pub fn unset(&mut self, opt: Effect) {
+    pub const fn unset(&mut self, opt: Effect) {
         *self = self.without(opt)
     }
 
     /// Toggle an effect in the set in place
     #[inline(always)]
-    pub fn toggle(&mut self, opt: Effect) {
+    pub const fn toggle(&mut self, opt: Effect) {
         *self = self.toggled(opt)
     }
 
+    /// The first effect in the set, usable in a const context (unlike [`iter`](Self::iter))
+    #[inline(always)]
+    #[allow(dead_code)]
+    pub(crate) const fn const_first(self) -> Option<Effect> {
+        if self.data == 0 {
+            None
+        } else {
+            Effect::try_decode(self.data.trailing_zeros() as u8)
+        }
+    }
+
+    /// The effects in either set, usable in a const context (unlike `|`)
+    #[must_use = "EffectFlags::union returns a new instance without modifying the original"]
+    #[inline(always)]
+    pub const fn union(self, other: EffectFlags) -> Self {
+        Self {
+            data: self.data | other.data,
+        }
+    }
+
+    /// The effects in both sets, usable in a const context (unlike `&`)
+    #[must_use = "EffectFlags::intersection returns a new instance without modifying the original"]
+    #[inline(always)]
+    pub const fn intersection(self, other: EffectFlags) -> Self {
+        Self {
+            data: self.data & other.data,
+        }
+    }
+
+    /// The effects in this set but not in `other`, usable in a const context (unlike `-`)
+    #[must_use = "EffectFlags::difference returns a new instance without modifying the original"]
+    #[inline(always)]
+    pub const fn difference(self, other: EffectFlags) -> Self {
+        Self {
+            data: self.data & !other.data,
+        }
+    }
+
     /// Iterate over all effects
     #[inline]
     pub const fn iter(self) -> EffectFlagsIter {
@@ -261,6 +351,78 @@ impl Default for EffectFlags {
     }
 }
 
+impl From<Effect> for EffectFlags {
+    #[inline]
+    fn from(effect: Effect) -> Self {
+        Self::new().with(effect)
+    }
+}
+
+macro_rules! effect_ops {
+    ($($lhs:ident $rhs:ident)*) => {$(
+        impl ops::BitOr<$rhs> for $lhs {
+            type Output = EffectFlags;
+
+            #[inline]
+            fn bitor(self, rhs: $rhs) -> EffectFlags {
+                EffectFlags::from(self).union(rhs.into())
+            }
+        }
+
+        impl ops::BitAnd<$rhs> for $lhs {
+            type Output = EffectFlags;
+
+            #[inline]
+            fn bitand(self, rhs: $rhs) -> EffectFlags {
+                EffectFlags::from(self).intersection(rhs.into())
+            }
+        }
+
+        impl ops::Sub<$rhs> for $lhs {
+            type Output = EffectFlags;
+
+            #[inline]
+            fn sub(self, rhs: $rhs) -> EffectFlags {
+                EffectFlags::from(self).difference(rhs.into())
+            }
+        }
+    )*};
+}
+
+effect_ops! {
+    EffectFlags EffectFlags
+    EffectFlags Effect
+    Effect EffectFlags
+    Effect Effect
+}
+
+macro_rules! effect_assign_ops {
+    ($($rhs:ident)*) => {$(
+        impl ops::BitOrAssign<$rhs> for EffectFlags {
+            #[inline]
+            fn bitor_assign(&mut self, rhs: $rhs) {
+                *self = *self | rhs;
+            }
+        }
+
+        impl ops::BitAndAssign<$rhs> for EffectFlags {
+            #[inline]
+            fn bitand_assign(&mut self, rhs: $rhs) {
+                *self = *self & rhs;
+            }
+        }
+
+        impl ops::SubAssign<$rhs> for EffectFlags {
+            #[inline]
+            fn sub_assign(&mut self, rhs: $rhs) {
+                *self = *self - rhs;
+            }
+        }
+    )*};
+}
+
+effect_assign_ops! { EffectFlags Effect }
+
 impl Style<crate::NoColor, crate::NoColor, crate::NoColor> {
     /// Create a new style
     #[inline(always)]
@@ -313,6 +475,138 @@ impl<F: Into<Option<Color>>, B: Into<Option<Color>>, U: Into<Option<Color>>> Sty
     }
 }
 
+impl<F: Into<Option<Color>>, B: Into<Option<Color>>, U: Into<Option<Color>>> Style<F, B, U> {
+    /// Layer `other` on top of this style
+    ///
+    /// The colors set in `other` replace the colors in this style, and the effects of both
+    /// styles are combined, so `other` can only add effects. Use [`patch`](Self::patch) to
+    /// turn effects off as well.
+    ///
+    /// ```rust
+    /// use colorz::{ansi, Style};
+    ///
+    /// let theme = Style::new().fg(ansi::White).bg(ansi::Black).bold();
+    /// let user = Style::new().fg(ansi::Cyan).italics();
+    ///
+    /// assert_eq!(
+    ///     theme.merge(user),
+    ///     Style::new().fg(ansi::Cyan).bg(ansi::Black).bold().italics().into_runtime_style()
+    /// );
+    /// ```
+    #[inline]
+    pub fn merge<F2, B2, U2>(self, other: Style<F2, B2, U2>) -> Style
+    where
+        F2: Into<Option<Color>>,
+        B2: Into<Option<Color>>,
+        U2: Into<Option<Color>>,
+    {
+        other.into_runtime_style().or(self)
+    }
+
+    /// Layer `other` on top of this style, after turning off the `removed` effects
+    ///
+    /// This is [`merge`](Self::merge) for overrides which also take effects away from the
+    /// layer below them. Effects in both `removed` and `other` are kept.
+    ///
+    /// ```rust
+    /// use colorz::{ansi, Effect, Style};
+    ///
+    /// let theme = Style::new().fg(ansi::White).bold().underline();
+    /// let user = Style::new().fg(ansi::Cyan).italics();
+    ///
+    /// assert_eq!(
+    ///     theme.patch(user, Effect::Bold),
+    ///     Style::new().fg(ansi::Cyan).underline().italics().into_runtime_style()
+    /// );
+    /// ```
+    #[inline]
+    pub fn patch<F2, B2, U2>(
+        self,
+        other: Style<F2, B2, U2>,
+        removed: impl Into<EffectFlags>,
+    ) -> Style
+    where
+        F2: Into<Option<Color>>,
+        B2: Into<Option<Color>>,
+        U2: Into<Option<Color>>,
+    {
+        self.into_runtime_style()
+            .const_patch(other.into_runtime_style(), removed.into())
+    }
+
+    /// Fill the colors which aren't set in this style from `other`
+    ///
+    /// The colors set in this style are kept, and the effects of both styles are combined.
+    /// This is [`merge`](Self::merge) with the layers swapped.
+    ///
+    /// ```rust
+    /// use colorz::{ansi, Style};
+    ///
+    /// let fallback = Style::new().fg(ansi::White).bg(ansi::Black);
+    ///
+    /// assert_eq!(
+    ///     Style::new().fg(ansi::Red).bold().or(fallback),
+    ///     Style::new().fg(ansi::Red).bg(ansi::Black).bold().into_runtime_style()
+    /// );
+    /// ```
+    #[inline]
+    pub fn or<F2, B2, U2>(self, other: Style<F2, B2, U2>) -> Style
+    where
+        F2: Into<Option<Color>>,
+        B2: Into<Option<Color>>,
+        U2: Into<Option<Color>>,
+    {
+        self.into_runtime_style()
+            .const_or(other.into_runtime_style())
+    }
+}
+
+impl Style {
+    /// [`or`](Self::or), usable in a const context
+    #[inline]
+    pub const fn const_or(self, other: Style) -> Style {
+        const fn or(color: Option<Color>, other: Option<Color>) -> Option<Color> {
+            match color {
+                Some(color) => Some(color),
+                None => other,
+            }
+        }
+
+        Style {
+            foreground: or(self.foreground, other.foreground),
+            background: or(self.background, other.background),
+            underline_color: or(self.underline_color, other.underline_color),
+            effects: self.effects.union(other.effects),
+        }
+    }
+
+    /// [`merge`](Self::merge), usable in a const context
+    ///
+    /// ```rust
+    /// use colorz::{ansi, Style};
+    ///
+    /// const BASE: Style = Style::new().fg(ansi::White).bold().const_into_runtime_style();
+    /// const ERROR: Style = BASE.const_merge(Style::new().fg(ansi::Red).const_into_runtime_style());
+    ///
+    /// assert_eq!(ERROR, Style::new().fg(ansi::Red).bold().into_runtime_style());
+    /// ```
+    #[inline]
+    pub const fn const_merge(self, other: Style) -> Style {
+        other.const_or(self)
+    }
+
+    /// [`patch`](Self::patch), usable in a const context
+    #[inline]
+    pub const fn const_patch(self, other: Style, removed: EffectFlags) -> Style {
+        let base = Style {
+            effects: self.effects.difference(removed),
+            ..self
+        };
+
+        base.const_merge(other)
+    }
+}
+
 impl<F: ComptimeColor, B: ComptimeColor, U: ComptimeColor> Style<F, B, U> {
     /// Convert to a type-erased style
     #[inline]
@@ -326,6 +620,397 @@ impl<F: ComptimeColor, B: ComptimeColor, U: ComptimeColor> Style<F, B, U> {
     }
 }
 
+const STYLE_ESCAPE_CAPACITY: usize = 128;
+
+#[derive(Clone, Copy)]
+enum Layer {
+    Foreground,
+    Background,
+    Underline,
+}
+
+/// The escape sequence of a whole [`Style`], built in a `const` context
+///
+/// See [`Style::apply_escape`] and [`Style::clear_escape`] for details
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StyleEscape {
+    data: [u8; STYLE_ESCAPE_CAPACITY],
+    len: u8,
+}
+
+impl StyleEscape {
+    #[inline]
+    const fn new() -> Self {
+        Self {
+            data: [0; STYLE_ESCAPE_CAPACITY],
+            len: 0,
+        }
+    }
+
+    #[inline]
+    const fn push_str(&mut self, s: &str) {
+        let s = s.as_bytes();
+        let mut i = 0;
+        while i < s.len() {
+            self.data[self.len as usize] = s[i];
+            self.len += 1;
+            i += 1;
+        }
+    }
+
+    /// Start the next argument, or the sequence if this is the first argument
+    #[inline]
+    pub(crate) const fn push_arg(&mut self, args: &str) {
+        self.push_str(if self.len == 0 { "\x1b[" } else { ";" });
+        self.push_str(args);
+    }
+
+    #[inline]
+    const fn push_u8(&mut self, x: u8) {
+        if x >= 100 {
+            self.data[self.len as usize] = x / 100 + b'0';
+            self.len += 1;
+        }
+        if x >= 10 {
+            self.data[self.len as usize] = x / 10 % 10 + b'0';
+            self.len += 1;
+        }
+        self.data[self.len as usize] = x % 10 + b'0';
+        self.len += 1;
+    }
+
+    #[inline]
+    const fn push_color(&mut self, color: Color, layer: Layer) {
+        let args = match (color, layer) {
+            (Color::Ansi(color), Layer::Foreground) => color.foreground_args(),
+            (Color::Ansi(color), Layer::Background) => color.background_args(),
+            (Color::Ansi(color), Layer::Underline) => color.underline_args(),
+            (Color::Xterm(color), Layer::Foreground) => color.foreground_args(),
+            (Color::Xterm(color), Layer::Background) => color.background_args(),
+            (Color::Xterm(color), Layer::Underline) => color.underline_args(),
+            (Color::Css(color), Layer::Foreground) => color.foreground_args(),
+            (Color::Css(color), Layer::Background) => color.background_args(),
+            (Color::Css(color), Layer::Underline) => color.underline_args(),
+            (Color::Rgb(color), _) => {
+                self.push_arg(match layer {
+                    Layer::Foreground => "38;2;",
+                    Layer::Background => "48;2;",
+                    Layer::Underline => "58;2;",
+                });
+                self.push_u8(color.red);
+                self.push_str(";");
+                self.push_u8(color.green);
+                self.push_str(";");
+                self.push_u8(color.blue);
+                return;
+            }
+        };
+
+        self.push_arg(args)
+    }
+
+    #[inline]
+    pub(crate) const fn push_foreground(&mut self, color: Color) {
+        self.push_color(color, Layer::Foreground)
+    }
+
+    #[inline]
+    pub(crate) const fn push_background(&mut self, color: Color) {
+        self.push_color(color, Layer::Background)
+    }
+
+    #[inline]
+    pub(crate) const fn push_underline(&mut self, color: Color) {
+        self.push_color(color, Layer::Underline)
+    }
+
+    /// Start the next argument with a [`WriteColor`] method
+    #[inline]
+    pub(crate) fn write_args<C: Copy>(
+        &mut self,
+        color: C,
+        fmt: fn(C, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) {
+        self.push_arg("");
+        // the buffer fits the longest style, so this can't fail
+        let _ = fmt::Write::write_fmt(self, format_args!("{}", Args(color, fmt)));
+    }
+
+    /// Write the finished sequence, without moving the buffer
+    #[inline]
+    fn fmt_finished(&mut self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.len != 0 {
+            self.push_str("m");
+        }
+
+        f.write_str(self.as_str())
+    }
+
+    #[inline]
+    const fn finish(mut self) -> Self {
+        if self.len != 0 {
+            self.push_str("m");
+        }
+        self
+    }
+
+    /// The escape sequence, which is empty for a plain style
+    #[inline]
+    pub const fn as_str(&self) -> &str {
+        let data = self.data.split_at(self.len as usize).0;
+
+        match core::str::from_utf8(data) {
+            Ok(x) => x,
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+impl fmt::Write for StyleEscape {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.len as usize + s.len() > STYLE_ESCAPE_CAPACITY {
+            return Err(fmt::Error);
+        }
+
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl fmt::Debug for StyleEscape {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl fmt::Display for StyleEscape {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Style {
+    /// The escape sequence which applies this style, as a single SGR sequence
+    ///
+    /// Unlike [`apply`](Self::apply) this can be evaluated at compile time, so writing the style
+    /// is a single [`write_str`](fmt::Write::write_str). It isn't checked against the
+    /// [coloring mode](crate::mode), see [`Style::should_color`] for that.
+    ///
+    /// ```
+    /// use colorz::{ansi, rgb::RgbColor, Color, Style, StyleEscape};
+    ///
+    /// const STYLE: Style = Style::new()
+    ///     .fg(Some(Color::Rgb(RgbColor { red: 255, green: 128, blue: 0 })))
+    ///     .bg(None::<Color>)
+    ///     .underline_color(Some(Color::Ansi(ansi::AnsiColor::Red)))
+    ///     .underline();
+    /// const APPLY: StyleEscape = STYLE.apply_escape();
+    ///
+    /// assert_eq!(APPLY.as_str(), "\x1b[38;2;255;128;0;4;58;5;1m");
+    /// ```
+    #[inline]
+    pub const fn apply_escape(self) -> StyleEscape {
+        let mut escape = StyleEscape::new();
+
+        if let Some(color) = self.foreground {
+            escape.push_foreground(color);
+        }
+
+        if let Some(color) = self.background {
+            escape.push_background(color);
+        }
+
+        let mut i = 0;
+        while i < EFFECT_LIST.len() {
+            if self.effects.is(EFFECT_LIST[i]) {
+                escape.push_arg(EFFECT_LIST[i].apply_args());
+            }
+            i += 1;
+        }
+
+        if let Some(color) = self.underline_color {
+            escape.push_underline(color);
+        }
+
+        escape.finish()
+    }
+
+    /// The escape sequence which clears this style, as a single SGR sequence
+    ///
+    /// This reverses [`apply_escape`](Self::apply_escape), like [`clear`](Self::clear)
+    #[inline]
+    pub const fn clear_escape(self) -> StyleEscape {
+        let mut escape = StyleEscape::new();
+
+        if self.foreground.is_some() {
+            escape.push_arg(ansi::Default::FOREGROUND_ARGS);
+        }
+
+        if self.background.is_some() {
+            escape.push_arg(ansi::Default::BACKGROUND_ARGS);
+        }
+
+        // some effects share a clear code, like bold and dimmed
+        let mut cleared = EffectFlags::new();
+        let mut i = 0;
+        while i < EFFECT_LIST.len() {
+            let effect = EFFECT_LIST[i];
+
+            if self.effects.is(effect) && !cleared.is(effect) {
+                escape.push_arg(effect.clear_args());
+
+                let mut j = i;
+                while j < EFFECT_LIST.len() {
+                    if const_str_eq(EFFECT_LIST[j].clear_args(), effect.clear_args()) {
+                        cleared.set(EFFECT_LIST[j]);
+                    }
+                    j += 1;
+                }
+            }
+            i += 1;
+        }
+
+        if self.underline_color.is_some() {
+            escape.push_arg("59");
+        }
+
+        escape.finish()
+    }
+}
+
+const fn const_str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+/// Do two colors write the same arguments
+#[cfg(feature = "macros")]
+const fn same_color(a: Option<Color>, b: Option<Color>, layer: Layer) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => {
+            let mut x = StyleEscape::new();
+            let mut y = StyleEscape::new();
+            x.push_color(a, layer);
+            y.push_color(b, layer);
+            const_str_eq(x.as_str(), y.as_str())
+        }
+        _ => false,
+    }
+}
+
+/// The escape sequence which switches from one style to another, like [`fmt_transition`]
+#[cfg(feature = "macros")]
+pub(crate) const fn transition_escape(from: Style, to: Style) -> StyleEscape {
+    let mut escape = StyleEscape::new();
+
+    // effects which share a clear code (like bold and dimmed) are cleared together,
+    // so any which are still used have to be applied again
+    let mut cleared = EffectFlags::new();
+    let mut i = 0;
+    while i < EFFECT_LIST.len() {
+        let effect = EFFECT_LIST[i];
+
+        if from.effects.is(effect) && !to.effects.is(effect) && !cleared.is(effect) {
+            escape.push_arg(effect.clear_args());
+
+            let mut j = 0;
+            while j < EFFECT_LIST.len() {
+                if const_str_eq(EFFECT_LIST[j].clear_args(), effect.clear_args()) {
+                    cleared.set(EFFECT_LIST[j]);
+                }
+                j += 1;
+            }
+        }
+        i += 1;
+    }
+
+    let mut i = 0;
+    while i < EFFECT_LIST.len() {
+        let effect = EFFECT_LIST[i];
+
+        if to.effects.is(effect) && (!from.effects.is(effect) || cleared.is(effect)) {
+            escape.push_arg(effect.apply_args());
+        }
+        i += 1;
+    }
+
+    if !same_color(from.foreground, to.foreground, Layer::Foreground) {
+        match to.foreground {
+            Some(color) => escape.push_foreground(color),
+            None => escape.push_arg(ansi::Default::FOREGROUND_ARGS),
+        }
+    }
+
+    if !same_color(from.background, to.background, Layer::Background) {
+        match to.background {
+            Some(color) => escape.push_background(color),
+            None => escape.push_arg(ansi::Default::BACKGROUND_ARGS),
+        }
+    }
+
+    if !same_color(from.underline_color, to.underline_color, Layer::Underline) {
+        match to.underline_color {
+            Some(color) => escape.push_underline(color),
+            None => escape.push_arg("59"),
+        }
+    }
+
+    escape.finish()
+}
+
+impl<F: ComptimeColor, B: ComptimeColor, U: ComptimeColor> Style<F, B, U> {
+    /// The escape sequence which applies this style, as a single SGR sequence
+    ///
+    /// This is evaluated at compile time if it's used in a `const`, so writing a fully static
+    /// style is a single [`write_str`](fmt::Write::write_str).
+    /// See `apply_escape` on the runtime [`Style`] for details.
+    ///
+    /// ```
+    /// use colorz::{ansi, NoColor, Style, StyleEscape};
+    ///
+    /// const STYLE: Style<ansi::Red, ansi::Black, NoColor> = Style::new()
+    ///     .fg(ansi::Red)
+    ///     .bg(ansi::Black)
+    ///     .bold()
+    ///     .dimmed();
+    /// const APPLY: StyleEscape = STYLE.apply_escape();
+    /// const CLEAR: StyleEscape = STYLE.clear_escape();
+    ///
+    /// assert_eq!(APPLY.as_str(), "\x1b[31;40;1;2m");
+    /// assert_eq!(CLEAR.as_str(), "\x1b[39;49;22m");
+    ///
+    /// println!("{APPLY}hello{CLEAR}");
+    /// ```
+    #[inline]
+    pub const fn apply_escape(self) -> StyleEscape {
+        self.const_into_runtime_style().apply_escape()
+    }
+
+    /// The escape sequence which clears this style, as a single SGR sequence
+    ///
+    /// See [`apply_escape`](Self::apply_escape) for details
+    #[inline]
+    pub const fn clear_escape(self) -> StyleEscape {
+        self.const_into_runtime_style().clear_escape()
+    }
+}
+
 impl Default for Style<crate::NoColor, crate::NoColor, crate::NoColor> {
     #[inline]
     fn default() -> Self {
@@ -373,6 +1058,7 @@ impl<F: OptionalColor, B: OptionalColor, U: OptionalColor> Style<F, B, U> {
         self.effects.is_plain()
             && self.foreground.get().is_none()
             && self.background.get().is_none()
+            && self.underline_color.get().is_none()
     }
 
     /// Does this style use the effect
@@ -558,13 +1244,9 @@ Effect! {
     ///
     /// println!("{}", "hello world".subscript());
     /// ```
-    SubScript 73 75 -> subscript,
+    SubScript 74 75 -> subscript,
 }
 
-const ANY_UNDERLINE: EffectFlags = EffectFlags::new()
-    .with(Effect::Underline)
-    .with(Effect::DoubleUnderline);
-
 impl<F: OptionalColor, B: OptionalColor, U: OptionalColor> Style<F, B, U> {
     /// Should you color based on the current coloring mode
     ///
@@ -581,15 +1263,64 @@ impl<F: OptionalColor, B: OptionalColor, U: OptionalColor> Style<F, B, U> {
         )
     }
 
-    fn fmt_apply(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.effects.is_any(ANY_UNDERLINE) {
-            if let Some(color) = self.underline_color.get() {
-                color.fmt_underline(f)?
+    /// Render into one stack buffer, so the whole style is a single `write_str`
+    fn fmt_apply_buffered(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut escape = StyleEscape::new();
+
+        if let Some(color) = self.foreground.get() {
+            color.push_foreground_args(&mut escape);
+        }
+
+        if let Some(color) = self.background.get() {
+            color.push_background_args(&mut escape);
+        }
+
+        for effect in self.effects {
+            escape.push_arg(effect.apply_args());
+        }
+
+        if let Some(color) = self.underline_color.get() {
+            color.push_underline_args(&mut escape);
+        }
+
+        escape.fmt_finished(f)
+    }
+
+    /// Render into one stack buffer, so the whole style is a single `write_str`
+    fn fmt_clear_buffered(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut escape = StyleEscape::new();
+
+        if self.foreground.get().is_some() {
+            escape.push_arg(ansi::Default::FOREGROUND_ARGS);
+        }
+
+        if self.background.get().is_some() {
+            escape.push_arg(ansi::Default::BACKGROUND_ARGS);
+        }
+
+        // effects which share a clear code are next to each other, like bold and dimmed
+        let mut last = "";
+        for effect in self.effects {
+            let args = effect.clear_args();
+            if args != last {
+                escape.push_arg(args);
+                last = args;
             }
         }
 
+        if self.underline_color.get().is_some() {
+            escape.push_arg("59");
+        }
+
+        escape.fmt_finished(f)
+    }
+
+    fn fmt_apply(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match (F::KIND, B::KIND) {
-            (_, crate::Kind::MaybeSome) | (crate::Kind::MaybeSome, _) => (),
+            (_, crate::Kind::MaybeSome) | (crate::Kind::MaybeSome, _) => {
+                return self.fmt_apply_buffered(f)
+            }
+            _ if self.underline_color.get().is_some() => return self.fmt_apply_slow(f),
             (crate::Kind::NeverSome, crate::Kind::NeverSome) => {
                 if self.effects.data.is_power_of_two() {
                     let effect = self.effects.iter().next().unwrap();
@@ -630,7 +1361,7 @@ impl<F: OptionalColor, B: OptionalColor, U: OptionalColor> Style<F, B, U> {
     }
 
     fn fmt_apply_slow(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        if self.effects.at_most_one_effect() {
+        if self.effects.at_most_one_effect() && self.underline_color.get().is_none() {
             if let Some(effect) = self.effects.iter().next() {
                 f.write_str(effect.apply_escape())?;
             }
@@ -672,13 +1403,16 @@ impl<F: OptionalColor, B: OptionalColor, U: OptionalColor> Style<F, B, U> {
             bg.fmt_background_args(f)?;
         }
 
-        if !self.effects.at_most_one_effect() {
-            self.effects.iter().try_for_each(|effect| {
-                semi!();
-                semicolon = true;
-                f.write_str(effect.apply_args())?;
-                Ok(())
-            })?;
+        self.effects.iter().try_for_each(|effect| {
+            semi!();
+            semicolon = true;
+            f.write_str(effect.apply_args())?;
+            Ok(())
+        })?;
+
+        if let Some(color) = self.underline_color.get() {
+            semi!();
+            color.fmt_underline_args(f)?;
         }
 
         f.write_str("m")?;
@@ -687,12 +1421,11 @@ impl<F: OptionalColor, B: OptionalColor, U: OptionalColor> Style<F, B, U> {
     }
 
     fn fmt_clear(&self, f: &mut fmt::Formatter<'_>) -> core::fmt::Result {
-        if self.effects.is_any(ANY_UNDERLINE) && self.underline_color.get().is_some() {
-            f.write_str("\x1b[59m")?
-        }
-
         match (F::KIND, B::KIND) {
-            (_, crate::Kind::MaybeSome) | (crate::Kind::MaybeSome, _) => (),
+            (_, crate::Kind::MaybeSome) | (crate::Kind::MaybeSome, _) => {
+                return self.fmt_clear_buffered(f)
+            }
+            _ if self.underline_color.get().is_some() => return self.fmt_clear_slow(f),
             (crate::Kind::NeverSome, crate::Kind::NeverSome) => {
                 if self.effects.data.is_power_of_two() {
                     let effect = self.effects.iter().next().unwrap();
@@ -729,8 +1462,7 @@ impl<F: OptionalColor, B: OptionalColor, U: OptionalColor> Style<F, B, U> {
 
     #[cold]
     fn fmt_clear_slow(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let at_most_one_effect = self.effects.at_most_one_effect();
-        if at_most_one_effect {
+        if self.effects.at_most_one_effect() && self.underline_color.get().is_none() {
             if let Some(effect) = self.effects.iter().next() {
                 f.write_str(effect.clear_escape())?;
             }
@@ -772,13 +1504,21 @@ impl<F: OptionalColor, B: OptionalColor, U: OptionalColor> Style<F, B, U> {
             ansi::Default.fmt_background_args(f)?;
         }
 
-        if !at_most_one_effect {
-            self.effects.iter().try_for_each(|effect| {
+        // effects which share a clear code are next to each other, like bold and dimmed
+        let mut last = "";
+        for effect in self.effects {
+            let args = effect.clear_args();
+            if args != last {
                 semi!();
                 semicolon = true;
-                f.write_str(effect.clear_args())?;
-                Ok(())
-            })?;
+                f.write_str(args)?;
+                last = args;
+            }
+        }
+
+        if self.underline_color.get().is_some() {
+            semi!();
+            f.write_str("59")?;
         }
 
         f.write_str("m")?;
@@ -835,6 +1575,88 @@ impl<F: OptionalColor, B: OptionalColor, U: OptionalColor> Style<F, B, U> {
     }
 }
 
+/// Writes the arguments of a single SGR sequence, separated by `;`
+#[cfg(feature = "alloc")]
+struct Sgr<'a> {
+    f: &'a mut dyn fmt::Write,
+    open: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl Sgr<'_> {
+    fn arg(&mut self) -> Result<&mut dyn fmt::Write, fmt::Error> {
+        self.f.write_str(if self.open { ";" } else { "\x1b[" })?;
+        self.open = true;
+        Ok(&mut *self.f)
+    }
+
+    fn finish(self) -> fmt::Result {
+        if self.open {
+            self.f.write_str("m")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Formats a color's args with a [`WriteColor`] method
+struct Args<C>(C, fn(C, &mut fmt::Formatter<'_>) -> fmt::Result);
+
+impl<C: Copy> fmt::Display for Args<C> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.1)(self.0, f)
+    }
+}
+
+/// Write only the attributes which differ between `from` and `to`, in a single SGR sequence
+#[cfg(feature = "alloc")]
+pub(crate) fn fmt_transition(f: &mut dyn fmt::Write, from: &Style, to: &Style) -> fmt::Result {
+    let mut sgr = Sgr { f, open: false };
+
+    // effects which share a clear code (like bold and dimmed) are cleared together,
+    // so any which are still used have to be applied again
+    let mut cleared = EffectFlags::new();
+    for effect in from.effects.iter().filter(|&effect| !to.is(effect)) {
+        if !cleared.is(effect) {
+            sgr.arg()?.write_str(effect.clear_args())?;
+            EffectFlags::all()
+                .iter()
+                .filter(|other| other.clear_args() == effect.clear_args())
+                .for_each(|other| cleared.set(other));
+        }
+    }
+
+    for effect in to.effects.iter() {
+        if !from.is(effect) || cleared.is(effect) {
+            sgr.arg()?.write_str(effect.apply_args())?;
+        }
+    }
+
+    if from.foreground != to.foreground {
+        match to.foreground {
+            Some(color) => write!(sgr.arg()?, "{}", Args(color, Color::fmt_foreground_args))?,
+            None => sgr.arg()?.write_str("39")?,
+        }
+    }
+
+    if from.background != to.background {
+        match to.background {
+            Some(color) => write!(sgr.arg()?, "{}", Args(color, Color::fmt_background_args))?,
+            None => sgr.arg()?.write_str("49")?,
+        }
+    }
+
+    if from.underline_color != to.underline_color {
+        match to.underline_color {
+            Some(color) => write!(sgr.arg()?, "{}", Args(color, Color::fmt_underline_args))?,
+            None => sgr.arg()?.write_str("59")?,
+        }
+    }
+
+    sgr.finish()
+}
+
 /// An iterator for the [`EffectFlags`] type, which yields [`Effect`]s
 #[derive(Clone)]
 pub struct EffectFlagsIter {
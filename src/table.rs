@@ -0,0 +1,602 @@
+//! Tables of styled values, aligned by their visible width
+//!
+//! Cells can be any [`Display`] value, including [`StyledValue`](crate::StyledValue)s, and are
+//! measured with [`text::visible_width`], so escape sequences and wide characters don't break the
+//! alignment. Cells should fit on a single line.
+//!
+//! ```rust
+//! use colorz::{ansi, table::{Align, Table}, Colorize, Style};
+//!
+//! let table = Table::new()
+//!     .header(["name", "status", "time"])
+//!     .row(["build", &"ok".green().to_string(), "1.2s"])
+//!     .row(["test", &"failed".red().to_string(), "12.0s"])
+//!     .align(2, Align::Right)
+//!     .stripes(Style::new().bg(ansi::BrightBlack).into_runtime_style());
+//!
+//! println!("{table}");
+//! ```
+//!
+//! prints something like
+//!
+//! ```text
+//! ┌───────┬────────┬───────┐
+//! │ name  │ status │  time │
+//! ├───────┼────────┼───────┤
+//! │ build │ ok     │  1.2s │
+//! │ test  │ failed │ 12.0s │
+//! └───────┴────────┴───────┘
+//! ```
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::{self, Display, Write};
+
+use crate::{mode::Stream, text, Style};
+
+/// How to align the contents of a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Align {
+    /// Align to the left (the default)
+    #[default]
+    Left,
+    /// Align to the right
+    Right,
+    /// Center the contents, leaning to the left if it can't be centered exactly
+    Center,
+}
+
+/// The characters used to draw the borders of a [`Table`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Border {
+    /// Horizontal lines
+    pub horizontal: char,
+    /// Vertical lines
+    pub vertical: char,
+    /// The top left corner
+    pub top_left: char,
+    /// Where a vertical line meets the top border
+    pub top_middle: char,
+    /// The top right corner
+    pub top_right: char,
+    /// Where a horizontal line meets the left border
+    pub middle_left: char,
+    /// Where a horizontal line meets a vertical line
+    pub middle: char,
+    /// Where a horizontal line meets the right border
+    pub middle_right: char,
+    /// The bottom left corner
+    pub bottom_left: char,
+    /// Where a vertical line meets the bottom border
+    pub bottom_middle: char,
+    /// The bottom right corner
+    pub bottom_right: char,
+}
+
+impl Border {
+    /// A border drawn with `+`, `-` and `|`
+    pub const ASCII: Self = Self::uniform('-', '|', '+');
+
+    /// A border drawn with light box drawing characters (the default)
+    pub const SINGLE: Self = Self {
+        horizontal: '─',
+        vertical: '│',
+        top_left: '┌',
+        top_middle: '┬',
+        top_right: '┐',
+        middle_left: '├',
+        middle: '┼',
+        middle_right: '┤',
+        bottom_left: '└',
+        bottom_middle: '┴',
+        bottom_right: '┘',
+    };
+
+    /// A border drawn with light box drawing characters, with rounded corners
+    pub const ROUNDED: Self = Self {
+        top_left: '╭',
+        top_right: '╮',
+        bottom_left: '╰',
+        bottom_right: '╯',
+        ..Self::SINGLE
+    };
+
+    /// A border drawn with double box drawing characters
+    pub const DOUBLE: Self = Self {
+        horizontal: '═',
+        vertical: '║',
+        top_left: '╔',
+        top_middle: '╦',
+        top_right: '╗',
+        middle_left: '╠',
+        middle: '╬',
+        middle_right: '╣',
+        bottom_left: '╚',
+        bottom_middle: '╩',
+        bottom_right: '╝',
+    };
+
+    /// A border which uses the same character for all intersections
+    #[inline]
+    pub const fn uniform(horizontal: char, vertical: char, intersection: char) -> Self {
+        Self {
+            horizontal,
+            vertical,
+            top_left: intersection,
+            top_middle: intersection,
+            top_right: intersection,
+            middle_left: intersection,
+            middle: intersection,
+            middle_right: intersection,
+            bottom_left: intersection,
+            bottom_middle: intersection,
+            bottom_right: intersection,
+        }
+    }
+}
+
+/// The layout of a column in a [`Table`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Column {
+    /// How to align the contents of the column
+    pub align: Align,
+    /// The maximum visible width of the column, longer cells are truncated with an ellipsis
+    pub max_width: Option<usize>,
+}
+
+/// A table of cells, see the [module docs](self) for details
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Table {
+    header: Option<Vec<String>>,
+    rows: Vec<Vec<String>>,
+    columns: Vec<Column>,
+    border: Option<Border>,
+    border_style: Style,
+    header_style: Style,
+    stripe_style: Style,
+    ellipsis: char,
+    padding: usize,
+    stream: Option<Stream>,
+}
+
+impl Default for Table {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn render_cells<I>(cells: I) -> Vec<String>
+where
+    I: IntoIterator,
+    I::Item: Display,
+{
+    let mut row = Vec::new();
+
+    for cell in cells {
+        let mut s = String::new();
+        // writing to a string can't fail
+        let _ = write!(s, "{cell}");
+        row.push(s);
+    }
+
+    row
+}
+
+impl Table {
+    /// Create an empty table with a [`Border::SINGLE`] border and a bold header
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            header: None,
+            rows: Vec::new(),
+            columns: Vec::new(),
+            border: Some(Border::SINGLE),
+            border_style: Style::new().const_into_runtime_style(),
+            header_style: Style::new().bold().const_into_runtime_style(),
+            stripe_style: Style::new().const_into_runtime_style(),
+            ellipsis: '…',
+            padding: 1,
+            stream: None,
+        }
+    }
+
+    /// Set the header row
+    #[inline]
+    pub fn header<I>(mut self, cells: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.header = Some(render_cells(cells));
+        self
+    }
+
+    /// Add a row to the table
+    #[inline]
+    pub fn row<I>(mut self, cells: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.push_row(cells);
+        self
+    }
+
+    /// Add a row to the table in place
+    #[inline]
+    pub fn push_row<I>(&mut self, cells: I)
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.rows.push(render_cells(cells));
+    }
+
+    fn column_mut(&mut self, column: usize) -> &mut Column {
+        if self.columns.len() <= column {
+            self.columns.resize(column + 1, Column::default());
+        }
+
+        &mut self.columns[column]
+    }
+
+    /// Set the layout of a column (starting from 0)
+    #[inline]
+    pub fn column(mut self, column: usize, layout: Column) -> Self {
+        *self.column_mut(column) = layout;
+        self
+    }
+
+    /// Set the alignment of a column (starting from 0)
+    #[inline]
+    pub fn align(mut self, column: usize, align: Align) -> Self {
+        self.column_mut(column).align = align;
+        self
+    }
+
+    /// Set the maximum visible width of a column (starting from 0)
+    ///
+    /// Cells which are wider are truncated, and end with the [ellipsis](Self::ellipsis)
+    #[inline]
+    pub fn max_width(mut self, column: usize, max_width: usize) -> Self {
+        self.column_mut(column).max_width = Some(max_width);
+        self
+    }
+
+    /// Set the characters used to draw the border
+    #[inline]
+    pub const fn border(mut self, border: Border) -> Self {
+        self.border = Some(border);
+        self
+    }
+
+    /// Don't draw a border, columns are only separated by the [padding](Self::padding)
+    #[inline]
+    pub const fn no_border(mut self) -> Self {
+        self.border = None;
+        self
+    }
+
+    /// Set the style of the border
+    #[inline]
+    pub const fn border_style(mut self, style: Style) -> Self {
+        self.border_style = style;
+        self
+    }
+
+    /// Set the style of the header row
+    #[inline]
+    pub const fn header_style(mut self, style: Style) -> Self {
+        self.header_style = style;
+        self
+    }
+
+    /// Set the style of every other row (starting from the second row), usually a background color
+    #[inline]
+    pub const fn stripes(mut self, style: Style) -> Self {
+        self.stripe_style = style;
+        self
+    }
+
+    /// Set the character which ends truncated cells (`…` by default)
+    #[inline]
+    pub const fn ellipsis(mut self, ellipsis: char) -> Self {
+        self.ellipsis = ellipsis;
+        self
+    }
+
+    /// Set the number of spaces on either side of each cell (1 by default)
+    #[inline]
+    pub const fn padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Set the stream used to decide if the border, header and stripe styles are colored
+    ///
+    /// This doesn't affect any styled values in the cells, which were already formatted
+    #[inline]
+    pub const fn stream(mut self, stream: Stream) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    fn widths(&self) -> Vec<usize> {
+        let mut widths = Vec::new();
+
+        for row in self.header.iter().chain(&self.rows) {
+            if widths.len() < row.len() {
+                widths.resize(row.len(), 0);
+            }
+
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(text::visible_width(cell));
+            }
+        }
+
+        for (width, column) in widths.iter_mut().zip(&self.columns) {
+            if let Some(max_width) = column.max_width {
+                *width = (*width).min(max_width);
+            }
+        }
+
+        widths
+    }
+
+    fn fmt_line(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        widths: &[usize],
+        [left, middle, right]: [char; 3],
+    ) -> fmt::Result {
+        let horizontal = self.border.map_or(' ', |border| border.horizontal);
+        let style = self.border_style;
+        let color = !style.is_plain() && style.should_color(self.stream);
+
+        if color {
+            style.apply().fmt(f)?;
+        }
+
+        f.write_char(left)?;
+        for (i, &width) in widths.iter().enumerate() {
+            if i != 0 {
+                f.write_char(middle)?;
+            }
+
+            for _ in 0..width + 2 * self.padding {
+                f.write_char(horizontal)?;
+            }
+        }
+        f.write_char(right)?;
+
+        if color {
+            style.clear().fmt(f)?;
+        }
+
+        Ok(())
+    }
+
+    fn fmt_vertical(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(border) = self.border else {
+            return Ok(());
+        };
+
+        let style = self.border_style;
+
+        if !style.is_plain() && style.should_color(self.stream) {
+            write!(f, "{}{}{}", style.apply(), border.vertical, style.clear())
+        } else {
+            f.write_char(border.vertical)
+        }
+    }
+
+    fn fmt_row(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        widths: &[usize],
+        row: &[String],
+        style: Style,
+    ) -> fmt::Result {
+        let color = !style.is_plain() && style.should_color(self.stream);
+
+        self.fmt_vertical(f)?;
+
+        for (i, &width) in widths.iter().enumerate() {
+            let cell = row.get(i).map_or("", String::as_str);
+            let align = self.columns.get(i).map_or(Align::Left, |c| c.align);
+            let cell = text::truncate(cell, width, self.ellipsis);
+            let space = width.saturating_sub(text::visible_width(&cell));
+
+            let (before, after) = match align {
+                Align::Left => (0, space),
+                Align::Right => (space, 0),
+                Align::Center => (space / 2, space - space / 2),
+            };
+
+            if color {
+                style.apply().fmt(f)?;
+            }
+
+            write!(
+                f,
+                "{:before$}{cell}{:after$}",
+                "",
+                "",
+                before = before + self.padding,
+                after = after + self.padding,
+            )?;
+
+            if color {
+                style.clear().fmt(f)?;
+            }
+
+            self.fmt_vertical(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Table {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let widths = self.widths();
+
+        if widths.is_empty() {
+            return Ok(());
+        }
+
+        let mut first = true;
+        let mut newline = |f: &mut fmt::Formatter<'_>| {
+            if core::mem::take(&mut first) {
+                Ok(())
+            } else {
+                f.write_char('\n')
+            }
+        };
+
+        if let Some(border) = self.border {
+            newline(f)?;
+            self.fmt_line(
+                f,
+                &widths,
+                [border.top_left, border.top_middle, border.top_right],
+            )?;
+        }
+
+        if let Some(header) = &self.header {
+            newline(f)?;
+            self.fmt_row(f, &widths, header, self.header_style)?;
+
+            if let Some(border) = self.border {
+                newline(f)?;
+                self.fmt_line(
+                    f,
+                    &widths,
+                    [border.middle_left, border.middle, border.middle_right],
+                )?;
+            }
+        }
+
+        let plain = Style::new().into_runtime_style();
+
+        for (i, row) in self.rows.iter().enumerate() {
+            newline(f)?;
+            let style = if i % 2 == 1 { self.stripe_style } else { plain };
+            self.fmt_row(f, &widths, row, style)?;
+        }
+
+        if let Some(border) = self.border {
+            newline(f)?;
+            self.fmt_line(
+                f,
+                &widths,
+                [
+                    border.bottom_left,
+                    border.bottom_middle,
+                    border.bottom_right,
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{format, string::ToString};
+
+    use super::{Align, Border, Table};
+    use crate::{ansi, mode::Stream, Colorize, Style};
+
+    #[test]
+    fn plain() {
+        let table = Table::new()
+            .border(Border::ASCII)
+            .header(["name", "count", "note"])
+            .row(["apple", "3", "日本"])
+            .row(["kiwi", "12"])
+            .align(1, Align::Right)
+            .align(2, Align::Center)
+            .stream(Stream::NeverColor);
+
+        assert_eq!(
+            table.to_string(),
+            "\
++-------+-------+------+
+| name  | count | note |
++-------+-------+------+
+| apple |     3 | 日本 |
+| kiwi  |    12 |      |
++-------+-------+------+"
+        );
+
+        let table = Table::new()
+            .no_border()
+            .row(["a", "bb"])
+            .row(["ccc", "d"])
+            .stream(Stream::NeverColor);
+
+        assert_eq!(table.to_string(), " a    bb \n ccc  d  ");
+        assert_eq!(Table::new().to_string(), "");
+    }
+
+    #[test]
+    fn truncate() {
+        let red = "hello world".red().stream(Stream::AlwaysColor).to_string();
+
+        let table = Table::new()
+            .no_border()
+            .padding(0)
+            .row([red.as_str(), "日本語"])
+            .max_width(0, 5)
+            .max_width(1, 4)
+            .stream(Stream::NeverColor);
+
+        let expected = if cfg!(feature = "strip-colors") {
+            "hell…"
+        } else {
+            "\x1b[31mhell…\x1b[39m"
+        };
+
+        assert_eq!(table.to_string(), format!("{expected}日… "));
+
+        // an ellipsis which is wider than the column is left out
+        let table = Table::new()
+            .no_border()
+            .padding(0)
+            .row(["abcdef", "x"])
+            .max_width(0, 1)
+            .ellipsis('日')
+            .stream(Stream::NeverColor);
+
+        assert_eq!(table.to_string(), " x");
+    }
+
+    #[test]
+    fn styled() {
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        let table = Table::new()
+            .header(["a"])
+            .row(["b"])
+            .row(["c"])
+            .border_style(Style::new().fg(ansi::Blue).into_runtime_style())
+            .stripes(Style::new().bg(ansi::BrightBlack).into_runtime_style())
+            .stream(Stream::AlwaysColor);
+
+        assert_eq!(
+            table.to_string(),
+            "\
+\x1b[34m┌───┐\x1b[39m
+\x1b[34m│\x1b[39m\x1b[1m a \x1b[22m\x1b[34m│\x1b[39m
+\x1b[34m├───┤\x1b[39m
+\x1b[34m│\x1b[39m b \x1b[34m│\x1b[39m
+\x1b[34m│\x1b[39m\x1b[100m c \x1b[49m\x1b[34m│\x1b[39m
+\x1b[34m└───┘\x1b[39m"
+        );
+    }
+}
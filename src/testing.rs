@@ -0,0 +1,631 @@
+//! An in-memory terminal, for testing styled output without a tty
+//!
+//! A [`VirtualTerminal`] interprets text written to it into a grid of [`Cell`]s, each with a char
+//! and the [`Style`] it was written with. SGR sequences, cursor movement and erasing are
+//! interpreted, along with the other sequences in [`control`](crate::control). Other escape
+//! sequences are ignored.
+//!
+//! ```rust
+//! use colorz::{
+//!     ansi,
+//!     control::{self, Control},
+//!     mode::Stream,
+//!     testing::VirtualTerminal,
+//!     Colorize, Style,
+//! };
+//!
+//! let mut term = VirtualTerminal::new(20, 3);
+//! term.write_str(&format!("{}: done\n", "ok".green().bold().stream(Stream::AlwaysColor)));
+//! term.write_str(&control::CursorTo { x: 4, y: 2 }.stream(Stream::AlwaysColor).to_string());
+//! term.write_str("!");
+//!
+//! # #[cfg(not(feature = "strip-colors"))]
+//! # {
+//! term.assert_row_text(0, "ok: done");
+//! term.assert_cell_style(0, 1, Style::new().fg(ansi::Green).bold());
+//! term.assert_cell_style(0, 2, Style::new());
+//! term.assert_cell(2, 4, '!', Style::new());
+//! assert_eq!(term.cursor(), (2, 5));
+//! # }
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{
+    string::{apply_sgr, PLAIN},
+    text::{self, Segment},
+    Color, Style,
+};
+
+/// A single cell of a [`VirtualTerminal`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cell {
+    /// The char in this cell, a space for empty cells and `'\0'` for the second half of a wide char
+    pub ch: char,
+    /// The style this cell was written with
+    pub style: Style,
+}
+
+const BLANK: Cell = Cell {
+    ch: ' ',
+    style: PLAIN,
+};
+
+/// The screen which isn't shown, swapped in and out with the alternate screen
+#[derive(Debug, Clone)]
+struct Screen {
+    cells: Vec<Cell>,
+    cursor: (usize, usize),
+}
+
+/// An in-memory terminal, see the [module docs](self) for details
+///
+/// Text wraps at the end of a line, and the screen scrolls up when a line is written below the last row.
+/// `\n` moves to the start of the next line, like a tty which translates newlines.
+/// Erasing fills cells with spaces without a style.
+#[derive(Debug, Clone)]
+pub struct VirtualTerminal {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    /// `(row, col)`
+    cursor: (usize, usize),
+    /// set after writing to the last column, the next char goes on the next line
+    pending_wrap: bool,
+    style: Style,
+    saved: ((usize, usize), Style),
+    cursor_visible: bool,
+    bracketed_paste: bool,
+    alternate: Option<Screen>,
+    title: Option<String>,
+}
+
+impl VirtualTerminal {
+    /// Create an empty terminal with `width` columns and `height` rows
+    ///
+    /// # Panics
+    ///
+    /// If `width` or `height` is zero
+    #[inline]
+    pub fn new(width: usize, height: usize) -> Self {
+        assert!(
+            width != 0 && height != 0,
+            "a terminal needs at least one cell"
+        );
+
+        Self {
+            width,
+            height,
+            cells: alloc::vec![BLANK; width * height],
+            cursor: (0, 0),
+            pending_wrap: false,
+            style: PLAIN,
+            saved: ((0, 0), PLAIN),
+            cursor_visible: true,
+            bracketed_paste: false,
+            alternate: None,
+            title: None,
+        }
+    }
+
+    /// The number of columns
+    #[inline]
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows
+    #[inline]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The cursor position, as `(row, col)` starting from zero
+    #[inline]
+    pub const fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// The style which the next char will be written with
+    #[inline]
+    pub const fn style(&self) -> Style {
+        self.style
+    }
+
+    /// Is the cursor shown
+    #[inline]
+    pub const fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    /// Is bracketed paste enabled
+    #[inline]
+    pub const fn bracketed_paste(&self) -> bool {
+        self.bracketed_paste
+    }
+
+    /// Is the alternate screen shown
+    #[inline]
+    pub const fn is_alternate_screen(&self) -> bool {
+        self.alternate.is_some()
+    }
+
+    /// The last window title which was set
+    #[inline]
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// The cell at `row` and `col`
+    ///
+    /// # Panics
+    ///
+    /// If the cell is outside of the screen
+    #[inline]
+    pub fn cell(&self, row: usize, col: usize) -> Cell {
+        assert!(
+            row < self.height && col < self.width,
+            "cell ({row}, {col}) is outside of the {}x{} screen",
+            self.width,
+            self.height
+        );
+
+        self.cells[row * self.width + col]
+    }
+
+    /// The text of a row, without trailing spaces
+    ///
+    /// # Panics
+    ///
+    /// If the row is outside of the screen
+    #[inline]
+    pub fn row_text(&self, row: usize) -> String {
+        assert!(row < self.height, "row {row} is outside of the screen");
+
+        let cells = &self.cells[row * self.width..][..self.width];
+        let text: String = cells
+            .iter()
+            .map(|cell| cell.ch)
+            .filter(|&c| c != '\0')
+            .collect();
+        String::from(text.trim_end_matches(' '))
+    }
+
+    /// The text of the whole screen, with each row on its own line and trailing empty rows left out
+    #[inline]
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+
+        for row in 0..self.height {
+            if row != 0 {
+                text.push('\n');
+            }
+
+            text.push_str(&self.row_text(row));
+        }
+
+        String::from(text.trim_end_matches('\n'))
+    }
+
+    /// Interpret text, which may contain escape sequences
+    #[inline]
+    pub fn write_str(&mut self, s: &str) {
+        for segment in text::segments(s) {
+            match segment {
+                Segment::Text(text) => text.chars().for_each(|c| self.write_char(c)),
+                Segment::Escape(escape) => self.escape(escape),
+            }
+        }
+    }
+
+    /// Assert that a cell has the char `ch` and the style `style`, see [`assert_cell_style`](Self::assert_cell_style)
+    ///
+    /// # Panics
+    ///
+    /// If the cell doesn't match, or is outside of the screen
+    #[inline]
+    #[track_caller]
+    pub fn assert_cell<F, B, U>(&self, row: usize, col: usize, ch: char, style: Style<F, B, U>)
+    where
+        F: Into<Option<Color>>,
+        B: Into<Option<Color>>,
+        U: Into<Option<Color>>,
+    {
+        let cell = self.cell(row, col);
+        assert!(
+            cell.ch == ch,
+            "expected {ch:?} at ({row}, {col}), found {:?} in row {:?}",
+            cell.ch,
+            self.row_text(row)
+        );
+        self.assert_cell_style(row, col, style);
+    }
+
+    /// Assert that a cell was written with `style`
+    ///
+    /// Styles are compared as they're written, so [`css`](crate::css) colors match the same
+    /// [`RgbColor`](crate::rgb::RgbColor) and the default [ANSI](crate::ansi) color matches no color.
+    ///
+    /// # Panics
+    ///
+    /// If the cell has another style, or is outside of the screen
+    #[inline]
+    #[track_caller]
+    pub fn assert_cell_style<F, B, U>(&self, row: usize, col: usize, style: Style<F, B, U>)
+    where
+        F: Into<Option<Color>>,
+        B: Into<Option<Color>>,
+        U: Into<Option<Color>>,
+    {
+        let expected = as_written(style.into_runtime_style());
+        let cell = self.cell(row, col);
+        assert!(
+            cell.style == expected,
+            "expected the cell at ({row}, {col}) in row {:?} to have the style\n{expected:?}\nfound\n{:?}",
+            self.row_text(row),
+            cell.style,
+        );
+    }
+
+    /// Assert that a row has the text `text`, without trailing spaces
+    ///
+    /// # Panics
+    ///
+    /// If the row has other text, or is outside of the screen
+    #[inline]
+    #[track_caller]
+    pub fn assert_row_text(&self, row: usize, text: &str) {
+        let found = self.row_text(row);
+        assert!(
+            found == text,
+            "expected row {row} to be {text:?}, found {found:?}"
+        );
+    }
+
+    fn write_char(&mut self, c: char) {
+        match c {
+            '\n' => {
+                self.cursor.1 = 0;
+                self.line_feed();
+            }
+            '\r' => self.move_to(self.cursor.0, 0),
+            '\t' => self.move_to(self.cursor.0, (self.cursor.1 / 8 + 1) * 8),
+            '\x08' => self.move_to(self.cursor.0, self.cursor.1.saturating_sub(1)),
+            _ if c.is_control() => (),
+            _ => self.print(c),
+        }
+    }
+
+    fn print(&mut self, c: char) {
+        let width = text::char_width(c);
+        if width == 0 {
+            return;
+        }
+
+        // wide chars which don't fit at the end of a line go on the next line
+        if self.pending_wrap || self.cursor.1 + width > self.width {
+            self.cursor.1 = 0;
+            self.line_feed();
+        }
+
+        let (row, col) = self.cursor;
+        self.cells[row * self.width + col] = Cell {
+            ch: c,
+            style: self.style,
+        };
+        if width == 2 && col + 1 < self.width {
+            self.cells[row * self.width + col + 1] = Cell {
+                ch: '\0',
+                style: self.style,
+            };
+        }
+
+        if col + width >= self.width {
+            self.cursor.1 = self.width - 1;
+            self.pending_wrap = true;
+        } else {
+            self.cursor.1 = col + width;
+        }
+    }
+
+    fn line_feed(&mut self) {
+        self.pending_wrap = false;
+
+        if self.cursor.0 + 1 < self.height {
+            self.cursor.0 += 1;
+        } else {
+            // scroll up
+            self.cells.drain(..self.width);
+            self.cells.resize(self.width * self.height, BLANK);
+        }
+    }
+
+    fn move_to(&mut self, row: usize, col: usize) {
+        self.cursor = (row.min(self.height - 1), col.min(self.width - 1));
+        self.pending_wrap = false;
+    }
+
+    fn erase(&mut self, start: (usize, usize), end: (usize, usize)) {
+        let start = start.0 * self.width + start.1;
+        let end = end.0 * self.width + end.1;
+        self.cells[start..end].fill(BLANK);
+    }
+
+    fn escape(&mut self, escape: &str) {
+        match escape {
+            "\x1b7" => self.saved = (self.cursor, self.style),
+            "\x1b8" => {
+                let ((row, col), style) = self.saved;
+                self.move_to(row, col);
+                self.style = style;
+            }
+            _ => {
+                if let Some(csi) = escape.strip_prefix("\x1b[") {
+                    self.csi(csi);
+                } else if let Some(osc) = escape.strip_prefix("\x1b]") {
+                    let osc = osc
+                        .strip_suffix('\x07')
+                        .or_else(|| osc.strip_suffix("\x1b\\"))
+                        .unwrap_or(osc);
+
+                    if let Some(("0" | "2", title)) = osc.split_once(';') {
+                        self.title = Some(String::from(title));
+                    }
+                }
+            }
+        }
+    }
+
+    fn csi(&mut self, csi: &str) {
+        let Some(kind) = csi.chars().next_back() else {
+            return;
+        };
+        let params = &csi[..csi.len() - kind.len_utf8()];
+
+        if let Some(mode) = params.strip_prefix('?') {
+            self.private_mode(mode, kind);
+            return;
+        }
+
+        if kind == 'm' {
+            apply_sgr(&mut self.style, params);
+            return;
+        }
+
+        let mut args = params.split(';').map(|arg| arg.parse::<usize>().ok());
+        let mut arg = |default: usize| match args.next().flatten() {
+            // a zero is the same as the default for cursor movement
+            Some(0) | None => default,
+            Some(arg) => arg,
+        };
+
+        let (row, col) = self.cursor;
+        let (last_row, last_col) = (self.height - 1, self.width - 1);
+
+        match kind {
+            'A' => self.move_to(row.saturating_sub(arg(1)), col),
+            'B' => self.move_to(row.saturating_add(arg(1)), col),
+            'C' => self.move_to(row, col.saturating_add(arg(1))),
+            'D' => self.move_to(row, col.saturating_sub(arg(1))),
+            'E' => self.move_to(row.saturating_add(arg(1)), 0),
+            'F' => self.move_to(row.saturating_sub(arg(1)), 0),
+            'G' => self.move_to(row, arg(1) - 1),
+            'd' => self.move_to(arg(1) - 1, col),
+            'H' | 'f' => {
+                let row = arg(1) - 1;
+                self.move_to(row, arg(1) - 1);
+            }
+            'J' => match params {
+                "" | "0" => self.erase((row, col), (last_row, last_col + 1)),
+                "1" => self.erase((0, 0), (row, col + 1)),
+                "2" | "3" => self.erase((0, 0), (last_row, last_col + 1)),
+                _ => (),
+            },
+            'K' => match params {
+                "" | "0" => self.erase((row, col), (row, last_col + 1)),
+                "1" => self.erase((row, 0), (row, col + 1)),
+                "2" => self.erase((row, 0), (row, last_col + 1)),
+                _ => (),
+            },
+            's' => self.saved.0 = self.cursor,
+            'u' => self.move_to(self.saved.0 .0, self.saved.0 .1),
+            _ => (),
+        }
+    }
+
+    fn private_mode(&mut self, mode: &str, kind: char) {
+        let enable = match kind {
+            'h' => true,
+            'l' => false,
+            _ => return,
+        };
+
+        match mode {
+            "25" => self.cursor_visible = enable,
+            "2004" => self.bracketed_paste = enable,
+            "1049" if enable && self.alternate.is_none() => {
+                let cells = core::mem::replace(
+                    &mut self.cells,
+                    alloc::vec![BLANK; self.width * self.height],
+                );
+                self.alternate = Some(Screen {
+                    cells,
+                    cursor: self.cursor,
+                });
+            }
+            "1049" if !enable => {
+                if let Some(screen) = self.alternate.take() {
+                    self.cells = screen.cells;
+                    self.move_to(screen.cursor.0, screen.cursor.1);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+impl fmt::Write for VirtualTerminal {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        VirtualTerminal::write_str(self, s);
+        Ok(())
+    }
+}
+
+/// The style a terminal ends up with after the style is written
+fn as_written(style: Style) -> Style {
+    let mut written = PLAIN;
+    let escape = style.apply_escape();
+    if let Some(params) = escape
+        .as_str()
+        .strip_prefix("\x1b[")
+        .and_then(|e| e.strip_suffix('m'))
+    {
+        apply_sgr(&mut written, params);
+    }
+    written
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{ansi, control, control::Control, css, mode::Stream, xterm};
+
+    fn write(term: &mut VirtualTerminal, value: impl fmt::Display) {
+        term.write_str(&value.to_string());
+    }
+
+    #[test]
+    fn styles() {
+        let mut term = VirtualTerminal::new(10, 2);
+        term.write_str("\x1b[1;38;2;255;0;0mab\x1b[22;48;5;51mc\x1b[0md");
+
+        term.assert_cell(0, 0, 'a', Style::new().fg(css::Red).bold());
+        term.assert_cell(0, 1, 'b', Style::new().fg(css::Red).bold());
+        term.assert_cell(0, 2, 'c', Style::new().fg(css::Red).bg(xterm::Aqua));
+        term.assert_cell(0, 3, 'd', Style::new());
+        term.assert_cell(0, 4, ' ', Style::new());
+        term.assert_cell_style(0, 3, Style::new().fg(ansi::Default));
+        assert_eq!(term.text(), "abcd");
+    }
+
+    #[test]
+    fn wrapping_and_scrolling() {
+        let mut term = VirtualTerminal::new(3, 2);
+        term.write_str("abcd");
+        assert_eq!(term.text(), "abc\nd");
+        assert_eq!(term.cursor(), (1, 1));
+
+        term.write_str("ef\ngh");
+        assert_eq!(term.text(), "def\ngh");
+
+        // the cursor stays on the last column until the next char
+        term.write_str("i");
+        assert_eq!(term.cursor(), (1, 2));
+        term.write_str("\rj");
+        assert_eq!(term.text(), "def\njhi");
+
+        let mut term = VirtualTerminal::new(3, 2);
+        term.write_str("a日本");
+        assert_eq!(term.text(), "a日\n本");
+        assert_eq!(term.cell(0, 2).ch, '\0');
+    }
+
+    #[test]
+    fn cursor_and_erase() {
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        let mut term = VirtualTerminal::new(5, 3);
+        term.write_str("hello\nworld\nagain");
+
+        write(
+            &mut term,
+            control::CursorTo { x: 1, y: 1 }.stream(Stream::AlwaysColor),
+        );
+        write(
+            &mut term,
+            control::ClearToEndOfLine.stream(Stream::AlwaysColor),
+        );
+        assert_eq!(term.text(), "hello\nw\nagain");
+
+        write(&mut term, control::CursorUp(1).stream(Stream::AlwaysColor));
+        write(
+            &mut term,
+            control::ClearToStartOfLine.stream(Stream::AlwaysColor),
+        );
+        assert_eq!(term.text(), "  llo\nw\nagain");
+
+        write(
+            &mut term,
+            control::CursorDown(9).stream(Stream::AlwaysColor),
+        );
+        assert_eq!(term.cursor(), (2, 1));
+        write(
+            &mut term,
+            control::ClearToStartOfScreen.stream(Stream::AlwaysColor),
+        );
+        assert_eq!(term.text(), "\n\n  ain");
+
+        write(&mut term, control::SaveCursor.stream(Stream::AlwaysColor));
+        write(
+            &mut term,
+            control::CursorToColumn(4).stream(Stream::AlwaysColor),
+        );
+        term.write_str("!");
+        write(
+            &mut term,
+            control::RestoreCursor.stream(Stream::AlwaysColor),
+        );
+        assert_eq!(term.cursor(), (2, 1));
+
+        write(&mut term, control::ClearScreen.stream(Stream::AlwaysColor));
+        assert_eq!(term.text(), "");
+    }
+
+    #[test]
+    fn modes() {
+        if cfg!(feature = "strip-colors") {
+            return;
+        }
+
+        let mut term = VirtualTerminal::new(5, 2);
+        term.write_str("main");
+
+        write(&mut term, control::HideCursor.stream(Stream::AlwaysColor));
+        write(
+            &mut term,
+            control::EnterAlternateScreen.stream(Stream::AlwaysColor),
+        );
+        write(
+            &mut term,
+            control::SetWindowTitle("app").stream(Stream::AlwaysColor),
+        );
+        term.write_str("\x1b[2;1Halt");
+        assert!(!term.cursor_visible());
+        assert!(term.is_alternate_screen());
+        assert_eq!(term.title(), Some("app"));
+        assert_eq!(term.text(), "\nalt");
+
+        write(
+            &mut term,
+            control::LeaveAlternateScreen.stream(Stream::AlwaysColor),
+        );
+        assert!(!term.is_alternate_screen());
+        assert_eq!(term.text(), "main");
+        assert_eq!(term.cursor(), (0, 4));
+    }
+
+    #[test]
+    #[should_panic = "expected the cell at (0, 0)"]
+    fn wrong_style() {
+        let mut term = VirtualTerminal::new(5, 1);
+        term.write_str("\x1b[31mx");
+        term.assert_cell_style(0, 0, Style::new().fg(ansi::Blue));
+    }
+}
@@ -0,0 +1,362 @@
+//! Measuring and splitting text which may contain ANSI escape sequences
+//!
+//! Text which has already been styled contains escape sequences, which take up bytes but not
+//! columns in the terminal. [`visible_width`] measures how many columns a string takes
+//! when it is printed, and [`segments`] splits it into text and escape sequences.
+//!
+//! ```rust
+//! use colorz::{text, Colorize};
+//!
+//! let hello = format!("{}", "hello".red().stream(colorz::mode::Stream::AlwaysColor));
+//!
+//! assert_eq!(text::visible_width(&hello), 5);
+//! assert_eq!(text::visible_width("日本"), 4);
+//! ```
+
+use core::iter::FusedIterator;
+
+/// A piece of a string, see [`segments`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Segment<'a> {
+    /// Text without any escape sequences
+    Text(&'a str),
+    /// A single escape sequence, including the leading `\x1b`
+    Escape(&'a str),
+}
+
+/// An iterator over the [`Segment`]s of a string, see [`segments`]
+#[derive(Debug, Clone)]
+pub struct Segments<'a> {
+    rest: &'a str,
+}
+
+/// Split a string into text and escape sequences
+///
+/// CSI sequences (`\x1b[...m`, `\x1b[2K`, etc.), OSC sequences (`\x1b]...\x07`) and two-byte
+/// escape sequences are recognized. An unterminated escape sequence extends to the end of the string.
+///
+/// ```rust
+/// use colorz::text::{segments, Segment};
+///
+/// let mut segments = segments("a\x1b[31mbc\x1b[39m");
+///
+/// assert_eq!(segments.next(), Some(Segment::Text("a")));
+/// assert_eq!(segments.next(), Some(Segment::Escape("\x1b[31m")));
+/// assert_eq!(segments.next(), Some(Segment::Text("bc")));
+/// assert_eq!(segments.next(), Some(Segment::Escape("\x1b[39m")));
+/// assert_eq!(segments.next(), None);
+/// ```
+#[inline]
+pub const fn segments(s: &str) -> Segments<'_> {
+    Segments { rest: s }
+}
+
+const fn escape_len(s: &[u8]) -> usize {
+    // s[0] is always `\x1b`
+    match s {
+        [_, b'[', ..] => {
+            // parameter and intermediate bytes, then a single final byte
+            let mut i = 2;
+            while i < s.len() {
+                if let 0x40..=0x7e = s[i] {
+                    return i + 1;
+                }
+                i += 1;
+            }
+            s.len()
+        }
+        [_, b']', ..] => {
+            // terminated by BEL or ST (`\x1b\\`)
+            let mut i = 2;
+            while i < s.len() {
+                match s[i] {
+                    0x07 => return i + 1,
+                    0x1b if i + 1 < s.len() && s[i + 1] == b'\\' => return i + 2,
+                    _ => i += 1,
+                }
+            }
+            s.len()
+        }
+        // the second byte may be part of a multi-byte char, in which case it isn't part of the escape
+        [_, b, ..] if b.is_ascii() => 2,
+        _ => 1,
+    }
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = Segment<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        let bytes = self.rest.as_bytes();
+
+        let (segment, rest) = if bytes[0] == 0x1b {
+            let (escape, rest) = self.rest.split_at(escape_len(bytes));
+            (Segment::Escape(escape), rest)
+        } else {
+            let len = bytes.iter().position(|&b| b == 0x1b).unwrap_or(bytes.len());
+            let (text, rest) = self.rest.split_at(len);
+            (Segment::Text(text), rest)
+        };
+
+        self.rest = rest;
+        Some(segment)
+    }
+}
+
+impl FusedIterator for Segments<'_> {}
+
+/// The number of columns a char takes up in a terminal
+///
+/// This is an approximation of the Unicode East Asian Width property, without any lookup tables.
+/// Control characters and combining marks take no columns, East Asian wide and fullwidth
+/// characters and most emoji take two columns, and everything else takes one column.
+///
+/// ```rust
+/// use colorz::text::char_width;
+///
+/// assert_eq!(char_width('a'), 1);
+/// assert_eq!(char_width('日'), 2);
+/// assert_eq!(char_width('\u{301}'), 0); // combining acute accent
+/// ```
+#[inline]
+pub const fn char_width(c: char) -> usize {
+    match c as u32 {
+        // control characters
+        0x00..=0x1f | 0x7f..=0x9f => 0,
+        0x20..=0x7e => 1,
+        // combining marks, zero width spaces and joiners, and variation selectors
+        0x0300..=0x036f
+        | 0x0483..=0x0489
+        | 0x0591..=0x05bd
+        | 0x0610..=0x061a
+        | 0x064b..=0x065f
+        | 0x1ab0..=0x1aff
+        | 0x1dc0..=0x1dff
+        | 0x200b..=0x200f
+        | 0x2028..=0x202e
+        | 0x2060..=0x2064
+        | 0x20d0..=0x20ff
+        | 0xfe00..=0xfe0f
+        | 0xfe20..=0xfe2f
+        | 0xfeff
+        | 0xe0100..=0xe01ef => 0,
+        // East Asian wide and fullwidth characters
+        0x1100..=0x115f
+        | 0x231a..=0x231b
+        | 0x2329..=0x232a
+        | 0x23e9..=0x23ec
+        | 0x23f0
+        | 0x23f3
+        | 0x25fd..=0x25fe
+        | 0x2614..=0x2615
+        | 0x2648..=0x2653
+        | 0x267f
+        | 0x2693
+        | 0x26a1
+        | 0x26aa..=0x26ab
+        | 0x26bd..=0x26be
+        | 0x26c4..=0x26c5
+        | 0x26ce
+        | 0x26d4
+        | 0x26ea
+        | 0x26f2..=0x26f3
+        | 0x26f5
+        | 0x26fa
+        | 0x26fd
+        | 0x2705
+        | 0x270a..=0x270b
+        | 0x2728
+        | 0x274c
+        | 0x274e
+        | 0x2753..=0x2755
+        | 0x2757
+        | 0x2795..=0x2797
+        | 0x27b0
+        | 0x27bf
+        | 0x2b1b..=0x2b1c
+        | 0x2b50
+        | 0x2b55
+        | 0x2e80..=0x303e
+        | 0x3041..=0x33ff
+        | 0x3400..=0x4dbf
+        | 0x4e00..=0x9fff
+        | 0xa000..=0xa4cf
+        | 0xa960..=0xa97f
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe10..=0xfe19
+        | 0xfe30..=0xfe6f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x16fe0..=0x16fe4
+        | 0x17000..=0x18cff
+        | 0x1b000..=0x1b2ff
+        | 0x1f004
+        | 0x1f0cf
+        | 0x1f18e
+        | 0x1f191..=0x1f19a
+        | 0x1f200..=0x1f251
+        | 0x1f300..=0x1f320
+        | 0x1f32d..=0x1f335
+        | 0x1f337..=0x1f37c
+        | 0x1f37e..=0x1f393
+        | 0x1f3a0..=0x1f3ca
+        | 0x1f3cf..=0x1f3d3
+        | 0x1f3e0..=0x1f3f0
+        | 0x1f3f4
+        | 0x1f3f8..=0x1f43e
+        | 0x1f440
+        | 0x1f442..=0x1f4fc
+        | 0x1f4ff..=0x1f53d
+        | 0x1f54b..=0x1f54e
+        | 0x1f550..=0x1f567
+        | 0x1f57a
+        | 0x1f595..=0x1f596
+        | 0x1f5a4
+        | 0x1f5fb..=0x1f64f
+        | 0x1f680..=0x1f6c5
+        | 0x1f6cc
+        | 0x1f6d0..=0x1f6d2
+        | 0x1f6d5..=0x1f6d7
+        | 0x1f6dc..=0x1f6df
+        | 0x1f6eb..=0x1f6ec
+        | 0x1f6f4..=0x1f6fc
+        | 0x1f7e0..=0x1f7eb
+        | 0x1f7f0
+        | 0x1f90c..=0x1f93a
+        | 0x1f93c..=0x1f945
+        | 0x1f947..=0x1f9ff
+        | 0x1fa70..=0x1faff
+        | 0x20000..=0x2fffd
+        | 0x30000..=0x3fffd => 2,
+        _ => 1,
+    }
+}
+
+/// The number of columns a string takes up in a terminal, ignoring any escape sequences
+///
+/// See [`char_width`] for how the width of each char is calculated
+///
+/// ```rust
+/// use colorz::text::visible_width;
+///
+/// assert_eq!(visible_width("hello"), 5);
+/// assert_eq!(visible_width("\x1b[1;31mhello\x1b[0m"), 5);
+/// assert_eq!(visible_width("日本語"), 6);
+/// ```
+#[inline]
+pub fn visible_width(s: &str) -> usize {
+    segments(s)
+        .map(|segment| match segment {
+            Segment::Text(text) => text.chars().map(char_width).sum(),
+            Segment::Escape(_) => 0,
+        })
+        .sum()
+}
+
+/// Truncate a string to at most `width` visible columns, ending it with `ellipsis` if it was cut
+///
+/// The result is never wider than `width`, so the ellipsis is left out if it doesn't fit.
+/// All escape sequences are kept, even the ones after the cut, so styles are still cleared at the end.
+///
+/// ```rust
+/// use colorz::text::truncate;
+///
+/// assert_eq!(truncate("hello world", 6, '…'), "hello…");
+/// assert_eq!(truncate("\x1b[31mhello\x1b[39m", 3, '…'), "\x1b[31mhe…\x1b[39m");
+/// assert_eq!(truncate("hello", 5, '…'), "hello");
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn truncate(s: &str, width: usize, ellipsis: char) -> alloc::borrow::Cow<'_, str> {
+    if visible_width(s) <= width {
+        return alloc::borrow::Cow::Borrowed(s);
+    }
+
+    let budget = width.saturating_sub(char_width(ellipsis));
+    let mut used = 0;
+    let mut truncated = false;
+    let mut out = alloc::string::String::new();
+
+    for segment in segments(s) {
+        match segment {
+            // keep escape sequences after the cut, so styles are still closed
+            Segment::Escape(escape) => out.push_str(escape),
+            Segment::Text(_) if truncated => (),
+            Segment::Text(text) => {
+                for c in text.chars() {
+                    let columns = char_width(c);
+
+                    if used + columns > budget {
+                        truncated = true;
+                        if char_width(ellipsis) <= width {
+                            out.push(ellipsis);
+                        }
+                        break;
+                    }
+
+                    used += columns;
+                    out.push(c);
+                }
+            }
+        }
+    }
+
+    alloc::borrow::Cow::Owned(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{segments, visible_width, Segment};
+
+    #[test]
+    fn escapes() {
+        let s = "\x1b]0;title\x07a\x1b]2;x\x1b\\\x1b7b\x1b[38;5;1";
+
+        assert!(segments(s).eq([
+            Segment::Escape("\x1b]0;title\x07"),
+            Segment::Text("a"),
+            Segment::Escape("\x1b]2;x\x1b\\"),
+            Segment::Escape("\x1b7"),
+            Segment::Text("b"),
+            Segment::Escape("\x1b[38;5;1"),
+        ]));
+
+        assert!(segments("\x1b").eq([Segment::Escape("\x1b")]));
+        assert!(segments("\x1bé").eq([Segment::Escape("\x1b"), Segment::Text("é")]));
+        assert_eq!(visible_width(s), 2);
+    }
+
+    #[test]
+    fn widths() {
+        assert_eq!(visible_width(""), 0);
+        assert_eq!(visible_width("e\u{301}"), 1);
+        assert_eq!(visible_width("ｆｕｌｌ"), 8);
+        assert_eq!(visible_width("한국어"), 6);
+        assert_eq!(visible_width("🦀 rust"), 7);
+        assert_eq!(visible_width("a\tb\n"), 2);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn truncate() {
+        use super::truncate;
+
+        assert_eq!(truncate("日本語", 4, '…'), "日…");
+        assert_eq!(truncate("日本語", 6, '…'), "日本語");
+        assert_eq!(truncate("abc", 0, '…'), "");
+        assert_eq!(truncate("abc", 1, '…'), "…");
+        assert_eq!(truncate("abc", 1, '日'), "");
+        assert_eq!(truncate("abc", 2, '日'), "日");
+        assert_eq!(
+            truncate("a\x1b[1mbc\x1b[22m\x1b]0;x\x07", 2, '~'),
+            "a\x1b[1m~\x1b[22m\x1b]0;x\x07"
+        );
+    }
+}
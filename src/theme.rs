@@ -0,0 +1,470 @@
+//! Named themes, which map semantic roles (like `"error"`, `"warning"` or `"path"`) to [`Style`]s
+//!
+//! Instead of hardcoding styles, look them up by name in a [`Theme`]. Users can then re-theme
+//! the whole program by loading a theme from a file or an environment variable.
+//!
+//! ```rust
+//! use colorz::{ansi, theme::Theme, Style};
+//!
+//! const ERROR: Style = Style::new().fg(ansi::Red).bold().const_into_runtime_style();
+//! const PATH: Style = Style::new().fg(ansi::Cyan).const_into_runtime_style();
+//!
+//! let mut theme = Theme::new().with("error", ERROR).with("path", PATH);
+//!
+//! // user overrides, for example from a config file
+//! theme.extend("error = bright red underline".parse::<Theme>().unwrap());
+//!
+//! println!("{}: could not open {}", theme.paint("error", "error"), theme.paint("path", "foo.txt"));
+//! ```
+//!
+//! # Theme files
+//!
+//! A theme can be parsed from a string, with one `name = style` entry per line,
+//! where the style uses [`Style`'s `FromStr` format](Style#fromstr). Entries may also be separated
+//! by `;`, which is useful for environment variables. Empty entries and lines starting with `#`
+//! are ignored.
+//!
+//! ```text
+//! # my-theme.txt
+//! error = bold red
+//! warning = bold yellow
+//! line-number = bright blue
+//! path = underline fg:#ff8800
+//! ```
+//!
+//! With the `std` feature, themes can be loaded from a file with [`Theme::load`], and
+//! overridden by an environment variable with [`Theme::override_from_env`]. There is also
+//! a global theme (see [`set_global_theme`]), which is used by [`Colorize::themed`](crate::Colorize::themed).
+
+use alloc::{borrow::Cow, collections::BTreeMap};
+use core::{fmt, str::FromStr};
+
+use crate::{Color, Colorize, ParseStyleError, Style, StyledValue};
+
+/// A collection of named [`Style`]s, see the [module docs](self) for details
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Theme {
+    styles: BTreeMap<Cow<'static, str>, Style>,
+}
+
+/// An error if parsing a theme from a string fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseThemeError {
+    /// An entry didn't have the form `name = style`
+    MissingEquals {
+        /// The line the error was found on (starting from 1)
+        line: usize,
+    },
+    /// An entry had an invalid style
+    InvalidStyle {
+        /// The line the error was found on (starting from 1)
+        line: usize,
+        /// The reason the style was invalid
+        error: ParseStyleError,
+    },
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseThemeError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::MissingEquals { .. } => None,
+            Self::InvalidStyle { error, .. } => Some(error),
+        }
+    }
+}
+
+impl fmt::Display for ParseThemeError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingEquals { line } => {
+                write!(f, "Invalid theme on line {line}: expected `name = style`")
+            }
+            Self::InvalidStyle { line, error } => {
+                write!(f, "Invalid theme on line {line}: {error}")
+            }
+        }
+    }
+}
+
+/// An error if loading a theme from a file fails
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub enum LoadThemeError {
+    /// The file couldn't be read
+    Io(std::io::Error),
+    /// The file didn't contain a valid theme
+    Parse(ParseThemeError),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LoadThemeError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Parse(error) => Some(error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for LoadThemeError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "Could not read theme: {error}"),
+            Self::Parse(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Theme {
+    /// Create an empty theme
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            styles: BTreeMap::new(),
+        }
+    }
+
+    /// Add a named style to the theme, replacing any previous style with that name
+    ///
+    /// ```rust
+    /// use colorz::{ansi, theme::Theme, Style};
+    ///
+    /// let theme = Theme::new().with("error", Style::new().fg(ansi::Red).into_runtime_style());
+    ///
+    /// assert_eq!(theme.get("error"), Some(Style::new().fg(ansi::Red).into_runtime_style()));
+    /// ```
+    #[inline]
+    pub fn with(mut self, name: impl Into<Cow<'static, str>>, style: Style) -> Self {
+        self.insert(name, style);
+        self
+    }
+
+    /// Add a named style to the theme, returning the previous style with that name
+    #[inline]
+    pub fn insert(&mut self, name: impl Into<Cow<'static, str>>, style: Style) -> Option<Style> {
+        self.styles.insert(name.into(), style)
+    }
+
+    /// Remove a named style from the theme, returning it if it was in the theme
+    #[inline]
+    pub fn remove(&mut self, name: &str) -> Option<Style> {
+        self.styles.remove(name)
+    }
+
+    /// Get a named style, if it is in the theme
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<Style> {
+        self.styles.get(name).copied()
+    }
+
+    /// Get a named style, or a plain style if it isn't in the theme
+    #[inline]
+    pub fn style(&self, name: &str) -> Style {
+        self.get(name)
+            .unwrap_or_else(|| Style::new().into_runtime_style())
+    }
+
+    /// Style a value with a named style (or a plain style if it isn't in the theme)
+    ///
+    /// ```rust
+    /// use colorz::{ansi, theme::Theme, Style};
+    ///
+    /// let theme = Theme::new().with("error", Style::new().fg(ansi::Red).into_runtime_style());
+    ///
+    /// println!("{}: oops", theme.paint("error", "error"));
+    /// ```
+    #[inline]
+    pub fn paint<T>(
+        &self,
+        name: &str,
+        value: T,
+    ) -> StyledValue<T, Option<Color>, Option<Color>, Option<Color>> {
+        value.into_style_with(self.style(name))
+    }
+
+    /// The number of named styles in the theme
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.styles.len()
+    }
+
+    /// Are there no named styles in the theme
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.styles.is_empty()
+    }
+
+    /// Iterate over all named styles in the theme, sorted by name
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Style)> + '_ {
+        self.styles.iter().map(|(name, &style)| (&**name, style))
+    }
+
+    /// Parse a theme, see the [module docs](self#theme-files) for the format
+    ///
+    /// ```rust
+    /// use colorz::{ansi, theme::{Theme, ParseThemeError}, Style};
+    ///
+    /// let theme = Theme::parse("# comment\nerror = bold red; warning = yellow").unwrap();
+    ///
+    /// assert_eq!(theme.get("error"), Some(Style::new().fg(ansi::Red).bold().into_runtime_style()));
+    /// assert_eq!(theme.get("warning"), Some(Style::new().fg(ansi::Yellow).into_runtime_style()));
+    ///
+    /// assert_eq!(Theme::parse("\nerror bold"), Err(ParseThemeError::MissingEquals { line: 2 }));
+    /// ```
+    #[inline]
+    pub fn parse(s: &str) -> Result<Self, ParseThemeError> {
+        let mut theme = Self::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let line_number = i + 1;
+
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            for entry in line.split(';') {
+                if entry.trim().is_empty() {
+                    continue;
+                }
+
+                let Some((name, style)) = entry.split_once('=') else {
+                    return Err(ParseThemeError::MissingEquals { line: line_number });
+                };
+
+                let name = name.trim();
+
+                if name.is_empty() {
+                    return Err(ParseThemeError::MissingEquals { line: line_number });
+                }
+
+                let style = style
+                    .parse()
+                    .map_err(|error| ParseThemeError::InvalidStyle {
+                        line: line_number,
+                        error,
+                    })?;
+
+                theme.insert(Cow::Owned(name.into()), style);
+            }
+        }
+
+        Ok(theme)
+    }
+
+    /// Load a theme from a file, see the [module docs](self#theme-files) for the format
+    #[inline]
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, LoadThemeError> {
+        let source = std::fs::read_to_string(path).map_err(LoadThemeError::Io)?;
+        Self::parse(&source).map_err(LoadThemeError::Parse)
+    }
+
+    /// Override styles in this theme with the ones specified in the environment variable `var`
+    ///
+    /// The environment variable uses the same format as [theme files](self#theme-files),
+    /// so entries are usually separated by `;`. If the variable isn't set then the theme is unchanged.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), colorz::theme::ParseThemeError> {
+    /// use colorz::{ansi, theme::Theme, Style};
+    ///
+    /// let mut theme = Theme::new().with("error", Style::new().fg(ansi::Red).into_runtime_style());
+    ///
+    /// // MYAPP_COLORS="error=bold magenta; path=cyan"
+    /// theme.override_from_env("MYAPP_COLORS")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn override_from_env(&mut self, var: &str) -> Result<(), ParseThemeError> {
+        self.override_from_env_source(&crate::mode::ProcessEnv, var)
+    }
+
+    /// Override styles in this theme with the ones specified in the environment variable `var`
+    /// in the given environment, see [`override_from_env`](Self::override_from_env) for details
+    ///
+    /// ```rust
+    /// use colorz::{ansi, theme::Theme, Style};
+    ///
+    /// let mut theme = Theme::new().with("error", Style::new().fg(ansi::Red).into_runtime_style());
+    ///
+    /// theme
+    ///     .override_from_env_source(&[("MYAPP_COLORS", "error=bold magenta; path=cyan")], "MYAPP_COLORS")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(theme.get("error"), Some(Style::new().fg(ansi::Magenta).bold().into_runtime_style()));
+    /// assert_eq!(theme.get("path"), Some(Style::new().fg(ansi::Cyan).into_runtime_style()));
+    /// ```
+    #[inline]
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn override_from_env_source(
+        &mut self,
+        env: &(impl crate::mode::Environment + ?Sized),
+        var: &str,
+    ) -> Result<(), ParseThemeError> {
+        if let Some(value) = env.var(var) {
+            self.extend(Self::parse(&value.to_string_lossy())?);
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Theme {
+    type Err = ParseThemeError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl<N: Into<Cow<'static, str>>> FromIterator<(N, Style)> for Theme {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (N, Style)>>(iter: T) -> Self {
+        let mut theme = Self::new();
+        theme.extend(iter);
+        theme
+    }
+}
+
+impl<N: Into<Cow<'static, str>>> Extend<(N, Style)> for Theme {
+    #[inline]
+    fn extend<T: IntoIterator<Item = (N, Style)>>(&mut self, iter: T) {
+        self.styles
+            .extend(iter.into_iter().map(|(name, style)| (name.into(), style)));
+    }
+}
+
+impl IntoIterator for Theme {
+    type Item = (Cow<'static, str>, Style);
+    type IntoIter = alloc::collections::btree_map::IntoIter<Cow<'static, str>, Style>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.styles.into_iter()
+    }
+}
+
+#[cfg(feature = "std")]
+static GLOBAL_THEME: std::sync::RwLock<Theme> = std::sync::RwLock::new(Theme::new());
+
+/// Set the global theme, which is used by [`Colorize::themed`](crate::Colorize::themed)
+///
+/// ```rust
+/// use colorz::{ansi, theme::{self, Theme}, Colorize, Style};
+///
+/// theme::set_global_theme(Theme::new().with("error", Style::new().fg(ansi::Red).into_runtime_style()));
+///
+/// println!("{}", "oops".themed("error"));
+/// ```
+#[inline]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn set_global_theme(theme: Theme) {
+    *GLOBAL_THEME
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = theme;
+}
+
+/// Access the global theme, see [`set_global_theme`]
+///
+/// ```rust
+/// use colorz::theme;
+///
+/// let error = theme::with_global_theme(|theme| theme.style("error"));
+/// ```
+#[inline]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn with_global_theme<R>(f: impl FnOnce(&Theme) -> R) -> R {
+    f(&GLOBAL_THEME
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ParseThemeError, Theme};
+    use crate::{ansi, ParseColorError, ParseStyleError, Style};
+
+    #[test]
+    fn parse() {
+        let theme = Theme::parse(
+            "
+            # a comment
+            error = bold red
+            line-number=bright blue;path = underline fg:#ff8800 ;;
+            plain =
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            theme.iter().collect::<alloc::vec::Vec<_>>(),
+            [
+                (
+                    "error",
+                    Style::new().fg(ansi::Red).bold().into_runtime_style()
+                ),
+                (
+                    "line-number",
+                    Style::new().fg(ansi::BrightBlue).into_runtime_style()
+                ),
+                ("path", "underline #ff8800".parse().unwrap()),
+                ("plain", Style::new().into_runtime_style()),
+            ]
+        );
+
+        assert_eq!(
+            Theme::parse("a = red\n = red"),
+            Err(ParseThemeError::MissingEquals { line: 2 })
+        );
+        assert_eq!(
+            Theme::parse("a = red; b = bg:nope"),
+            Err(ParseThemeError::InvalidStyle {
+                line: 1,
+                error: ParseStyleError::InvalidColor(ParseColorError::UnknownColor)
+            })
+        );
+    }
+
+    #[test]
+    fn overrides() {
+        let mut theme = Theme::new()
+            .with("error", Style::new().fg(ansi::Red).into_runtime_style())
+            .with(
+                "warning",
+                Style::new().fg(ansi::Yellow).into_runtime_style(),
+            );
+
+        theme.extend(Theme::parse("error = magenta; note = green").unwrap());
+
+        assert_eq!(theme.len(), 3);
+        assert_eq!(
+            theme.style("error"),
+            Style::new().fg(ansi::Magenta).into_runtime_style()
+        );
+        assert_eq!(
+            theme.style("warning"),
+            Style::new().fg(ansi::Yellow).into_runtime_style()
+        );
+        assert_eq!(
+            theme.style("note"),
+            Style::new().fg(ansi::Green).into_runtime_style()
+        );
+        assert_eq!(theme.style("missing"), Style::new().into_runtime_style());
+    }
+}
@@ -111,6 +111,42 @@ macro_rules! AnsiColorMethods {
                 }
             }
 
+            /// Applies a named style from the [global theme](crate::theme::set_global_theme)
+            ///
+            /// If there is no style with that name in the global theme, then the value isn't styled.
+            /// The style is looked up immediately, so changing the global theme doesn't affect
+            /// the returned value.
+            ///
+            /// This borrows the source value, so it cannot outlive the source
+            ///
+            /// ```rust
+            /// use colorz::{ansi, theme::{self, Theme}, Colorize, Style};
+            ///
+            /// theme::set_global_theme(Theme::new().with("error", Style::new().fg(ansi::Red).into_runtime_style()));
+            ///
+            /// println!("{}", "oops".themed("error"));
+            /// ```
+            #[inline]
+            #[cfg(feature = "std")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+            fn themed(&self, name: &str) -> StyledValue<&Self, Option<crate::Color>, Option<crate::Color>, Option<crate::Color>> {
+                self.style_with(crate::theme::with_global_theme(|theme| theme.style(name)))
+            }
+
+            /// Syntax highlight the `Debug` output of a value (`{:?}` and `{:#?}`)
+            ///
+            /// See the [`debug`](crate::debug) module for details
+            ///
+            /// ```rust
+            /// use colorz::Colorize;
+            ///
+            /// println!("{:#?}", Some((1, "two")).debug_colored());
+            /// ```
+            #[inline]
+            fn debug_colored(&self) -> crate::debug::DebugColored<'_, Self> {
+                crate::debug::DebugColored::new(self)
+            }
+
             /// Changes the foreground color
             ///
             /// This borrows the source value, so it cannot outlive the source
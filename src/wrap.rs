@@ -0,0 +1,265 @@
+//! Wrapping styled text to a width, keeping the styles on every line
+//!
+//! Text is broken between words, by its [visible width](crate::text::visible_width), and words
+//! which are too long for a line are broken between chars. Each line of the output is styled on its
+//! own, so all styles are cleared at the end of a line and applied again on the next one, and
+//! escape sequences are never split.
+//!
+//! ```rust
+//! use colorz::{mode::Stream, wrap::Wrap, Colorize};
+//!
+//! let text = format!("a {} word", "very long".red().stream(Stream::AlwaysColor));
+//!
+//! let wrapped = Wrap::new(8).hanging_indent(2).fill(&text);
+//!
+//! # #[cfg(not(feature = "strip-colors"))]
+//! assert_eq!(wrapped, "a \x1b[31mvery\x1b[39m\n  \x1b[31mlong\x1b[39m\n  word");
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{string::StyledString, text};
+
+/// How to wrap text, see the [module docs](self) for details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Wrap {
+    width: usize,
+    indent: usize,
+    hanging_indent: usize,
+}
+
+impl Wrap {
+    /// Wrap text to `width` columns, without any indents
+    #[inline]
+    pub const fn new(width: usize) -> Self {
+        Self {
+            width,
+            indent: 0,
+            hanging_indent: 0,
+        }
+    }
+
+    /// Set the width, in columns, including the indent
+    #[inline]
+    pub const fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Indent the first line of each paragraph by this many spaces
+    #[inline]
+    pub const fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Indent all lines after the first line of each paragraph by this many spaces
+    #[inline]
+    pub const fn hanging_indent(mut self, indent: usize) -> Self {
+        self.hanging_indent = indent;
+        self
+    }
+
+    /// Wrap a [`StyledString`] into lines
+    ///
+    /// Each line of the input (see [`StyledString::lines`]) is a paragraph, which is wrapped on
+    /// its own. Whitespace between words on the same line is kept, and whitespace at a line break
+    /// is removed.
+    ///
+    /// ```rust
+    /// use colorz::{ansi, string::StyledString, wrap::Wrap, Style};
+    ///
+    /// let mut s = StyledString::new();
+    /// s.push_str("usage: ");
+    /// s.push_styled("tool [OPTIONS] <FILE>", Style::new().bold());
+    ///
+    /// let lines: Vec<_> = Wrap::new(16)
+    ///     .hanging_indent(7)
+    ///     .wrap(&s)
+    ///     .iter()
+    ///     .map(|line| line.plain())
+    ///     .collect();
+    ///
+    /// assert_eq!(lines, ["usage: tool", "       [OPTIONS]", "       <FILE>"]);
+    /// ```
+    #[inline]
+    pub fn wrap(&self, s: &StyledString) -> Vec<StyledString> {
+        let mut lines = Vec::new();
+
+        for paragraph in s.lines() {
+            self.wrap_paragraph(&paragraph, &mut lines);
+        }
+
+        lines
+    }
+
+    /// Wrap text which may contain SGR escape sequences, and join the lines with `\n`
+    ///
+    /// The text is parsed with [`StyledString::from_escaped`], so other escape sequences are left out.
+    ///
+    /// ```rust
+    /// use colorz::wrap::Wrap;
+    ///
+    /// assert_eq!(Wrap::new(10).fill("the quick brown fox"), "the quick\nbrown fox");
+    /// assert_eq!(Wrap::new(4).indent(2).fill("abcdef"), "  ab\ncdef");
+    /// ```
+    #[inline]
+    pub fn fill(&self, s: &str) -> String {
+        let mut filled = String::new();
+
+        for (i, line) in self.wrap(&StyledString::from_escaped(s)).iter().enumerate() {
+            if i != 0 {
+                filled.push('\n');
+            }
+
+            filled.push_str(&line.to_escaped());
+        }
+
+        filled
+    }
+
+    fn wrap_paragraph(&self, paragraph: &StyledString, lines: &mut Vec<StyledString>) {
+        let plain = paragraph.plain();
+        let mut line = Line::new(paragraph, self.indent);
+
+        for (start, end) in words(&plain) {
+            if !line.is_empty() {
+                let width = text::visible_width(&plain[line.end..end]);
+
+                if line.width + width <= self.width {
+                    line.push(paragraph, end, width);
+                    continue;
+                }
+
+                lines.push(line.text);
+                line = Line::new(paragraph, self.hanging_indent);
+                // drop the whitespace at the line break
+                line.end = start;
+            }
+
+            // the first word on a line is broken between chars if it doesn't fit,
+            // on the first line of a paragraph this includes any leading whitespace
+            let start = line.end;
+            let mut width = 0;
+            for (i, c) in plain[start..end].char_indices() {
+                let c_width = text::char_width(c);
+                let split = start + i;
+
+                if split != line.end && line.width + width + c_width > self.width {
+                    line.push(paragraph, split, width);
+                    lines.push(line.text);
+                    line = Line::new(paragraph, self.hanging_indent);
+                    line.end = split;
+                    width = 0;
+                }
+
+                width += c_width;
+            }
+
+            line.push(paragraph, end, width);
+        }
+
+        if line.is_empty() {
+            // don't indent empty lines
+            lines.push(paragraph.slice(..0));
+        } else {
+            lines.push(line.text);
+        }
+    }
+}
+
+/// A line which is being built
+struct Line {
+    text: StyledString,
+    /// The byte offset in the paragraph where the line ends
+    end: usize,
+    width: usize,
+    words: bool,
+}
+
+impl Line {
+    fn new(paragraph: &StyledString, indent: usize) -> Self {
+        let mut text = paragraph.slice(..0);
+        if indent != 0 {
+            text.push_str(&" ".repeat(indent));
+        }
+
+        Self {
+            text,
+            end: 0,
+            width: indent,
+            words: false,
+        }
+    }
+
+    const fn is_empty(&self) -> bool {
+        !self.words
+    }
+
+    /// Add the text up to `end` from the paragraph
+    fn push(&mut self, paragraph: &StyledString, end: usize, width: usize) {
+        self.text.append(&paragraph.slice(self.end..end));
+        self.end = end;
+        self.width += width;
+        self.words = true;
+    }
+}
+
+/// The byte ranges of the words in a string
+fn words(s: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    s.split(char::is_whitespace)
+        .filter(|word| !word.is_empty())
+        .map(move |word| {
+            let start = word.as_ptr() as usize - s.as_ptr() as usize;
+            (start, start + word.len())
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ansi, Style};
+
+    fn plain_lines(wrap: Wrap, s: &StyledString) -> Vec<String> {
+        wrap.wrap(s).iter().map(StyledString::plain).collect()
+    }
+
+    #[test]
+    fn breaks_long_words() {
+        let s = StyledString::from("  abcdefghij k");
+
+        assert_eq!(plain_lines(Wrap::new(4), &s), ["  ab", "cdef", "ghij", "k"]);
+        assert_eq!(
+            plain_lines(Wrap::new(3).hanging_indent(1), &s),
+            ["  a", " bc", " de", " fg", " hi", " j", " k"]
+        );
+        assert_eq!(plain_lines(Wrap::new(0), &"ab".into()), ["a", "b"]);
+        assert_eq!(
+            plain_lines(Wrap::new(3), &"日本語".into()),
+            ["日", "本", "語"]
+        );
+    }
+
+    #[test]
+    fn paragraphs() {
+        let s = StyledString::from("one two\n\n   \nthree");
+
+        assert_eq!(
+            plain_lines(Wrap::new(5).indent(2).hanging_indent(1), &s),
+            ["  one", " two", "", "", "  thr", " ee"]
+        );
+    }
+
+    #[test]
+    fn styles_carry_over() {
+        let mut s = StyledString::new();
+        s.push_styled("bold and red", Style::new().fg(ansi::Red).bold());
+        s.push_str(" plain");
+
+        assert_eq!(
+            Wrap::new(9).fill(&s.to_escaped()),
+            "\x1b[1;31mbold and\x1b[22;39m\n\x1b[1;31mred\x1b[22;39m plain"
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! `FORCE_COLOR` and the forced color support change global state, so they're checked in their own test binary
+
+#![cfg(feature = "std")]
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use colorz::{
+    ansi,
+    mode::{self, ColorSupport, EnvPolicy, Mode, Stream},
+    rgb::Rgb,
+    xterm, Colorize,
+};
+
+/// The tests change the global coloring mode and color support, so they're run one at a time
+fn lock() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[test]
+fn force_color_level() {
+    if cfg!(feature = "strip-colors") {
+        return;
+    }
+
+    let _lock = lock();
+    EnvPolicy::from_env_source(&[("FORCE_COLOR", "1")]).apply();
+
+    assert_eq!(mode::get_coloring_mode(), Mode::Always);
+    assert_eq!(mode::color_support(Stream::AlwaysColor), ColorSupport::ANSI);
+
+    // only the colors of the level are written
+    assert_eq!("x".fg(ansi::Red).to_string(), "\x1b[31mx\x1b[39m");
+    assert_eq!("x".fg(xterm::Aqua).to_string(), "x");
+    assert_eq!(
+        mode::with_coloring_mode(Mode::Always, || "x".fg(xterm::Aqua).to_string()),
+        "x"
+    );
+
+    EnvPolicy::from_env_source(&[("FORCE_COLOR", "2")]).apply();
+    assert_eq!("x".fg(xterm::Aqua).to_string(), "\x1b[38;5;51mx\x1b[39m");
+    assert_eq!("x".fg(Rgb::<1, 2, 3>).to_string(), "x");
+
+    EnvPolicy::from_env_source(&[("FORCE_COLOR", "3")]).apply();
+    assert_eq!(
+        "x".fg(Rgb::<1, 2, 3>).to_string(),
+        "\x1b[38;2;1;2;3mx\x1b[39m"
+    );
+}
+
+#[test]
+fn forced_color_support() {
+    if cfg!(feature = "strip-colors") {
+        return;
+    }
+
+    let _lock = lock();
+    mode::set_coloring_mode(Mode::Detect);
+    mode::set_color_support(Stream::AlwaysColor, ColorSupport::XTERM);
+
+    // colors which the forced support doesn't include are left out
+    assert_eq!(
+        "x".fg(ansi::Red).stream(Stream::AlwaysColor).to_string(),
+        "\x1b[31mx\x1b[39m"
+    );
+    assert_eq!(
+        "x".fg(xterm::Aqua).stream(Stream::AlwaysColor).to_string(),
+        "\x1b[38;5;51mx\x1b[39m"
+    );
+    assert_eq!(
+        "x".fg(Rgb::<1, 2, 3>)
+            .stream(Stream::AlwaysColor)
+            .to_string(),
+        "x"
+    );
+
+    mode::set_color_support(Stream::AlwaysColor, ColorSupport::ALL);
+    assert_eq!(
+        "x".fg(Rgb::<1, 2, 3>)
+            .stream(Stream::AlwaysColor)
+            .to_string(),
+        "\x1b[38;2;1;2;3mx\x1b[39m"
+    );
+}
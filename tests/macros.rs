@@ -0,0 +1,165 @@
+#![cfg(all(feature = "macros", feature = "std"))]
+
+use colorz::{ansi, css, mode::Stream, rgb::RgbColor, xterm::XtermColor, Color, Style};
+
+#[test]
+fn styles() {
+    const STYLE: Style = colorz::style!("bold bright blue bg:#10 ul:AliceBlue double_underline");
+
+    assert_eq!(
+        STYLE,
+        Style::new()
+            .fg(ansi::BrightBlue)
+            .bg(Color::Xterm(XtermColor::from_code(16)))
+            .underline_color(css::AliceBlue)
+            .bold()
+            .double_underline()
+            .into_runtime_style()
+    );
+    assert_eq!(
+        colorz::style!("fg:#ff8000 255 purple"),
+        Style::new().fg(ansi::Magenta).into_runtime_style()
+    );
+    assert_eq!(
+        colorz::style!(r"bg:#ff8000"),
+        Style::new()
+            .bg(RgbColor {
+                red: 255,
+                green: 128,
+                blue: 0
+            })
+            .into_runtime_style()
+    );
+}
+
+#[test]
+fn same_as_from_str() {
+    // `style!` has its own copy of the grammar, so every item is checked against `FromStr`
+    macro_rules! same {
+        ($($style:literal)*) => {$(
+            assert_eq!(colorz::style!($style), $style.parse::<Style>().unwrap(), "{:?}", $style);
+        )*};
+    }
+
+    same! {
+        "bold" "dimmed" "italics" "underline" "double_underline" "blink" "blink_fast"
+        "reverse" "hide" "strikethrough" "overline" "superscript" "subscript"
+    }
+
+    same! {
+        "black" "fg:black" "bg:black" "ul:black" "red" "fg:red" "bg:red" "ul:red" "green"
+        "fg:green" "bg:green" "ul:green" "yellow" "fg:yellow" "bg:yellow" "ul:yellow"
+        "blue" "fg:blue" "bg:blue" "ul:blue" "magenta" "fg:magenta" "bg:magenta"
+        "ul:magenta" "purple" "fg:purple" "bg:purple" "ul:purple" "cyan" "fg:cyan"
+        "bg:cyan" "ul:cyan" "white" "fg:white" "bg:white" "ul:white" "bright black"
+        "fg:bright black" "bg:bright black" "ul:bright black" "bright red" "fg:bright red"
+        "bg:bright red" "ul:bright red" "bright green" "fg:bright green" "bg:bright green"
+        "ul:bright green" "bright yellow" "fg:bright yellow" "bg:bright yellow"
+        "ul:bright yellow" "bright blue" "fg:bright blue" "bg:bright blue" "ul:bright blue"
+        "bright magenta" "fg:bright magenta" "bg:bright magenta" "ul:bright magenta"
+        "bright cyan" "fg:bright cyan" "bg:bright cyan" "ul:bright cyan" "bright white"
+        "fg:bright white" "bg:bright white" "ul:bright white" "default" "fg:default"
+        "bg:default" "ul:default"
+    }
+
+    same! {
+        "AliceBlue" "AntiqueWhite" "Aqua" "Aquamarine" "Azure" "Beige" "Bisque" "Black"
+        "BlanchedAlmond" "Blue" "BlueViolet" "Brown" "BurlyWood" "CadetBlue" "Chartreuse"
+        "Chocolate" "Coral" "CornflowerBlue" "Cornsilk" "Crimson" "DarkBlue" "DarkCyan"
+        "DarkGoldenRod" "DarkGray" "DarkGrey" "DarkGreen" "DarkKhaki" "DarkMagenta"
+        "DarkOliveGreen" "DarkOrange" "DarkOrchid" "DarkRed" "DarkSalmon" "DarkSeaGreen"
+        "DarkSlateBlue" "DarkSlateGray" "DarkSlateGrey" "DarkTurquoise" "DarkViolet"
+        "DeepPink" "DeepSkyBlue" "DimGray" "DimGrey" "DodgerBlue" "FireBrick" "FloralWhite"
+        "ForestGreen" "Fuchsia" "Gainsboro" "GhostWhite" "Gold" "GoldenRod" "Gray" "Grey"
+        "Green" "GreenYellow" "HoneyDew" "HotPink" "IndianRed" "Indigo" "Ivory" "Khaki"
+        "Lavender" "LavenderBlush" "LawnGreen" "LemonChiffon" "LightBlue" "LightCoral"
+        "LightCyan" "LightGoldenRodYellow" "LightGray" "LightGrey" "LightGreen" "LightPink"
+        "LightSalmon" "LightSeaGreen" "LightSkyBlue" "LightSlateGray" "LightSlateGrey"
+        "LightSteelBlue" "LightYellow" "Lime" "LimeGreen" "Linen" "Magenta" "Maroon"
+        "MediumAquaMarine" "MediumBlue" "MediumOrchid" "MediumPurple" "MediumSeaGreen"
+        "MediumSlateBlue" "MediumSpringGreen" "MediumTurquoise" "MediumVioletRed"
+        "MidnightBlue" "MintCream" "MistyRose" "Moccasin" "NavajoWhite" "Navy" "OldLace"
+        "Olive" "OliveDrab" "Orange" "OrangeRed" "Orchid" "PaleGoldenRod" "PaleGreen"
+        "PaleTurquoise" "PaleVioletRed" "PapayaWhip" "PeachPuff" "Peru" "Pink" "Plum"
+        "PowderBlue" "Purple" "RebeccaPurple" "Red" "RosyBrown" "RoyalBlue" "SaddleBrown"
+        "Salmon" "SandyBrown" "SeaGreen" "SeaShell" "Sienna" "Silver" "SkyBlue" "SlateBlue"
+        "SlateGray" "SlateGrey" "Snow" "SpringGreen" "SteelBlue" "Tan" "Teal" "Thistle"
+        "Tomato" "Turquoise" "Violet" "Wheat" "White" "WhiteSmoke" "Yellow" "YellowGreen"
+        "bg:AliceBlue" "bg:AntiqueWhite" "bg:Aqua" "ul:AliceBlue" "ul:AntiqueWhite"
+        "ul:Aqua"
+    }
+
+    same! {
+        "0" "1" "2" "3" "4" "5" "6" "7" "8" "9" "10" "11" "12" "13" "14" "15" "16" "17"
+        "18" "19" "20" "21" "22" "23" "24" "25" "26" "27" "28" "29" "30" "31" "32" "33"
+        "34" "35" "36" "37" "38" "39" "40" "41" "42" "43" "44" "45" "46" "47" "48" "49"
+        "50" "51" "52" "53" "54" "55" "56" "57" "58" "59" "60" "61" "62" "63" "64" "65"
+        "66" "67" "68" "69" "70" "71" "72" "73" "74" "75" "76" "77" "78" "79" "80" "81"
+        "82" "83" "84" "85" "86" "87" "88" "89" "90" "91" "92" "93" "94" "95" "96" "97"
+        "98" "99" "100" "101" "102" "103" "104" "105" "106" "107" "108" "109" "110" "111"
+        "112" "113" "114" "115" "116" "117" "118" "119" "120" "121" "122" "123" "124" "125"
+        "126" "127" "128" "129" "130" "131" "132" "133" "134" "135" "136" "137" "138" "139"
+        "140" "141" "142" "143" "144" "145" "146" "147" "148" "149" "150" "151" "152" "153"
+        "154" "155" "156" "157" "158" "159" "160" "161" "162" "163" "164" "165" "166" "167"
+        "168" "169" "170" "171" "172" "173" "174" "175" "176" "177" "178" "179" "180" "181"
+        "182" "183" "184" "185" "186" "187" "188" "189" "190" "191" "192" "193" "194" "195"
+        "196" "197" "198" "199" "200" "201" "202" "203" "204" "205" "206" "207" "208" "209"
+        "210" "211" "212" "213" "214" "215" "216" "217" "218" "219" "220" "221" "222" "223"
+        "224" "225" "226" "227" "228" "229" "230" "231" "232" "233" "234" "235" "236" "237"
+        "238" "239" "240" "241" "242" "243" "244" "245" "246" "247" "248" "249" "250" "251"
+        "252" "253" "254" "255" "#0" "#1" "#2" "#3" "#4" "#5" "#6" "#7" "#8" "#9" "#a" "#b"
+        "#c" "#d" "#e" "#f" "#10" "#11" "#12" "#13" "#14" "#15" "#16" "#17" "#18" "#19"
+        "#1a" "#1b" "#1c" "#1d" "#1e" "#1f" "#20" "#21" "#22" "#23" "#24" "#25" "#26" "#27"
+        "#28" "#29" "#2a" "#2b" "#2c" "#2d" "#2e" "#2f" "#30" "#31" "#32" "#33" "#34" "#35"
+        "#36" "#37" "#38" "#39" "#3a" "#3b" "#3c" "#3d" "#3e" "#3f" "#40" "#41" "#42" "#43"
+        "#44" "#45" "#46" "#47" "#48" "#49" "#4a" "#4b" "#4c" "#4d" "#4e" "#4f" "#50" "#51"
+        "#52" "#53" "#54" "#55" "#56" "#57" "#58" "#59" "#5a" "#5b" "#5c" "#5d" "#5e" "#5f"
+        "#60" "#61" "#62" "#63" "#64" "#65" "#66" "#67" "#68" "#69" "#6a" "#6b" "#6c" "#6d"
+        "#6e" "#6f" "#70" "#71" "#72" "#73" "#74" "#75" "#76" "#77" "#78" "#79" "#7a" "#7b"
+        "#7c" "#7d" "#7e" "#7f" "#80" "#81" "#82" "#83" "#84" "#85" "#86" "#87" "#88" "#89"
+        "#8a" "#8b" "#8c" "#8d" "#8e" "#8f" "#90" "#91" "#92" "#93" "#94" "#95" "#96" "#97"
+        "#98" "#99" "#9a" "#9b" "#9c" "#9d" "#9e" "#9f" "#a0" "#a1" "#a2" "#a3" "#a4" "#a5"
+        "#a6" "#a7" "#a8" "#a9" "#aa" "#ab" "#ac" "#ad" "#ae" "#af" "#b0" "#b1" "#b2" "#b3"
+        "#b4" "#b5" "#b6" "#b7" "#b8" "#b9" "#ba" "#bb" "#bc" "#bd" "#be" "#bf" "#c0" "#c1"
+        "#c2" "#c3" "#c4" "#c5" "#c6" "#c7" "#c8" "#c9" "#ca" "#cb" "#cc" "#cd" "#ce" "#cf"
+        "#d0" "#d1" "#d2" "#d3" "#d4" "#d5" "#d6" "#d7" "#d8" "#d9" "#da" "#db" "#dc" "#dd"
+        "#de" "#df" "#e0" "#e1" "#e2" "#e3" "#e4" "#e5" "#e6" "#e7" "#e8" "#e9" "#ea" "#eb"
+        "#ec" "#ed" "#ee" "#ef" "#f0" "#f1" "#f2" "#f3" "#f4" "#f5" "#f6" "#f7" "#f8" "#f9"
+        "#fa" "#fb" "#fc" "#fd" "#fe" "#ff"
+    }
+
+    same! {
+        "#0" "#F" "#Ff" "000" "001" "099" "#000000" "#ff8000" "#FF8000" "bg:#123456"
+        "ul:#abcdef" "bold bright blue bg:#10 ul:AliceBlue double_underline"
+        "fg:#ff8000 255 purple" "red blue" "bold bold" "  italics\tred  " ""
+    }
+}
+
+#[test]
+fn markup() {
+    if cfg!(feature = "strip-colors") {
+        return;
+    }
+
+    colorz::mode::set_default_stream(Stream::AlwaysColor);
+
+    let name = "world";
+    assert_eq!(colorz::cformat!("hello {name}"), "hello world");
+    assert_eq!(colorz::cformat!("<<{:<3}>", 1,), "<1  >");
+    assert_eq!(colorz::cformat!("{{<red>}}"), "{\x1b[31m}\x1b[39m");
+    assert_eq!(
+        colorz::cformat!("<red><blue>{}</>{x}</>", "a", x = 'b'),
+        "\x1b[31m\x1b[34ma\x1b[31mb\x1b[39m"
+    );
+    assert_eq!(
+        colorz::cformat!("<bold dimmed>a<dimmed>\tb</>c</>"),
+        "\x1b[1;2ma\tbc\x1b[22m"
+    );
+    assert_eq!(
+        colorz::cformat!("<underline ul:red>a<reverse>b"),
+        "\x1b[4;58;5;1ma\x1b[7mb\x1b[24;27;59m"
+    );
+
+    colorz::mode::set_default_stream(Stream::NeverColor);
+    assert_eq!(colorz::cformat!("<bold>a</>\n"), "a\n");
+}
@@ -0,0 +1,404 @@
+//! Checks the output of every way to write a [`Style`] with a reference SGR interpreter
+//!
+//! The interpreter only knows the SGR codes, so the output is checked against what a terminal
+//! would show instead of against a snapshot of the same code.
+
+use colorz::{
+    ansi::{self, AnsiColor},
+    css::{self, CssColor},
+    rgb::{Rgb, RgbColor},
+    xterm::{self, XtermColor},
+    Color, ComptimeColor, Effect, EffectFlags, NoColor, OptionalColor, Style,
+};
+
+/// A color as a terminal sees it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TermColor {
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// The state of a terminal, only for what SGR sequences can change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Term {
+    fg: Option<TermColor>,
+    bg: Option<TermColor>,
+    ul: Option<TermColor>,
+    effects: EffectFlags,
+}
+
+const APPLY_CODES: &[(u16, Effect)] = &[
+    (1, Effect::Bold),
+    (2, Effect::Dimmed),
+    (3, Effect::Italic),
+    (4, Effect::Underline),
+    (21, Effect::DoubleUnderline),
+    (5, Effect::Blink),
+    (6, Effect::BlinkFast),
+    (7, Effect::Reversed),
+    (8, Effect::Hidden),
+    (9, Effect::Strikethrough),
+    (53, Effect::Overline),
+    (73, Effect::SuperScript),
+    (74, Effect::SubScript),
+];
+
+const CLEAR_CODES: &[(u16, &[Effect])] = &[
+    (22, &[Effect::Bold, Effect::Dimmed]),
+    (23, &[Effect::Italic]),
+    (24, &[Effect::Underline, Effect::DoubleUnderline]),
+    (25, &[Effect::Blink, Effect::BlinkFast]),
+    (27, &[Effect::Reversed]),
+    (28, &[Effect::Hidden]),
+    (29, &[Effect::Strikethrough]),
+    (55, &[Effect::Overline]),
+    (75, &[Effect::SuperScript, Effect::SubScript]),
+];
+
+impl Term {
+    /// Run the output of a style, which must only contain well formed SGR sequences
+    fn run(mut self, output: &str) -> Self {
+        let mut rest = output;
+
+        while !rest.is_empty() {
+            let Some(seq) = rest.strip_prefix("\x1b[") else {
+                panic!("text outside of a sequence in {output:?}")
+            };
+            let Some(end) = seq.find('m') else {
+                panic!("unterminated sequence in {output:?}")
+            };
+
+            let mut params = seq[..end]
+                .split(';')
+                .map(|param| match param.parse::<u16>() {
+                    Ok(param) => param,
+                    Err(_) => panic!("invalid parameter {param:?} in {output:?}"),
+                });
+
+            while let Some(code) = params.next() {
+                self.code(code, &mut params, output);
+            }
+
+            rest = &seq[end + 1..];
+        }
+
+        self
+    }
+
+    fn code(&mut self, code: u16, params: &mut dyn Iterator<Item = u16>, output: &str) {
+        let mut next = || match params.next() {
+            Some(param) => u8::try_from(param).unwrap(),
+            None => panic!("missing color parameter in {output:?}"),
+        };
+
+        match code {
+            0 => *self = Term::default(),
+            30..=37 => self.fg = Some(TermColor::Indexed(code as u8 - 30)),
+            90..=97 => self.fg = Some(TermColor::Indexed(code as u8 - 90 + 8)),
+            39 => self.fg = None,
+            40..=47 => self.bg = Some(TermColor::Indexed(code as u8 - 40)),
+            100..=107 => self.bg = Some(TermColor::Indexed(code as u8 - 100 + 8)),
+            49 => self.bg = None,
+            59 => self.ul = None,
+            38 | 48 | 58 => {
+                let color = match next() {
+                    5 => TermColor::Indexed(next()),
+                    2 => TermColor::Rgb(next(), next(), next()),
+                    kind => panic!("invalid color kind {kind} in {output:?}"),
+                };
+
+                match code {
+                    38 => self.fg = Some(color),
+                    48 => self.bg = Some(color),
+                    _ => self.ul = Some(color),
+                }
+            }
+            _ => {
+                if let Some(&(_, effect)) = APPLY_CODES.iter().find(|(c, _)| *c == code) {
+                    self.effects.set(effect);
+                } else if let Some(&(_, effects)) = CLEAR_CODES.iter().find(|(c, _)| *c == code) {
+                    effects
+                        .iter()
+                        .for_each(|&effect| self.effects.unset(effect));
+                } else {
+                    panic!("unknown code {code} in {output:?}")
+                }
+            }
+        }
+    }
+}
+
+fn term_color(color: Option<Color>) -> Option<TermColor> {
+    Some(match color? {
+        Color::Ansi(AnsiColor::Default) => return None,
+        Color::Ansi(color) => TermColor::Indexed(color as u8),
+        Color::Xterm(color) => TermColor::Indexed(color as u8),
+        Color::Css(color) => {
+            let RgbColor { red, green, blue } = color.rgb();
+            TermColor::Rgb(red, green, blue)
+        }
+        Color::Rgb(RgbColor { red, green, blue }) => TermColor::Rgb(red, green, blue),
+    })
+}
+
+/// What a terminal should show after applying the style
+fn expected(style: Style) -> Term {
+    Term {
+        fg: term_color(style.foreground),
+        bg: term_color(style.background),
+        ul: term_color(style.underline_color),
+        effects: style.effects,
+    }
+}
+
+/// A terminal with every attribute set, to check that clearing a style leaves the others alone
+const BUSY: Term = Term {
+    fg: Some(TermColor::Indexed(200)),
+    bg: Some(TermColor::Indexed(201)),
+    ul: Some(TermColor::Indexed(202)),
+    effects: EffectFlags::all(),
+};
+
+/// What a terminal in the `BUSY` state should show after clearing the style
+fn expected_clear(style: Style) -> Term {
+    let mut term = BUSY;
+
+    // AnsiColor::Default is written as the default color, so it's also cleared
+    if style.foreground.is_some() {
+        term.fg = None;
+    }
+    if style.background.is_some() {
+        term.bg = None;
+    }
+    if style.underline_color.is_some() {
+        term.ul = None;
+    }
+
+    for &(_, shared) in CLEAR_CODES {
+        if shared.iter().any(|&effect| style.is(effect)) {
+            shared.iter().for_each(|&effect| term.effects.unset(effect));
+        }
+    }
+
+    term
+}
+
+/// Check a style written with `apply` and `clear`
+fn check<F, B, U>(style: Style<F, B, U>, runtime: Style)
+where
+    F: OptionalColor,
+    B: OptionalColor,
+    U: OptionalColor,
+{
+    let apply = style.apply().to_string();
+    let clear = style.clear().to_string();
+
+    assert_eq!(
+        Term::default().run(&apply),
+        expected(runtime),
+        "{runtime:?} {apply:?}"
+    );
+    assert_eq!(
+        Term::default().run(&apply).run(&clear),
+        Term::default(),
+        "{runtime:?} {clear:?}"
+    );
+    assert_eq!(
+        BUSY.run(&clear),
+        expected_clear(runtime),
+        "{runtime:?} {clear:?}"
+    );
+    assert_eq!(
+        apply.is_empty(),
+        runtime.is_plain(),
+        "{runtime:?} {apply:?}"
+    );
+    assert_eq!(
+        clear.is_empty(),
+        runtime.is_plain(),
+        "{runtime:?} {clear:?}"
+    );
+    assert_eq!(style.is_plain(), runtime.is_plain(), "{runtime:?}");
+
+    // the runtime style has to render the same way
+    assert_eq!(
+        Term::default().run(&runtime.apply().to_string()),
+        expected(runtime)
+    );
+    assert_eq!(
+        BUSY.run(&runtime.clear().to_string()),
+        expected_clear(runtime)
+    );
+
+    // and so do the const escapes
+    assert_eq!(runtime.apply_escape().as_str(), runtime.apply().to_string());
+    assert_eq!(runtime.clear_escape().as_str(), runtime.clear().to_string());
+}
+
+/// Check a style with a compile time color in every slot, including its const escapes
+fn check_const<F, B, U>(style: Style<F, B, U>)
+where
+    F: ComptimeColor + OptionalColor,
+    B: ComptimeColor + OptionalColor,
+    U: ComptimeColor + OptionalColor,
+{
+    let runtime = style.const_into_runtime_style();
+
+    check(style, runtime);
+    assert_eq!(
+        style.apply_escape().as_str(),
+        runtime.apply_escape().as_str()
+    );
+    assert_eq!(
+        style.clear_escape().as_str(),
+        runtime.clear_escape().as_str()
+    );
+}
+
+fn effect_subsets() -> impl Iterator<Item = EffectFlags> {
+    let all: Vec<Effect> = EffectFlags::all().iter().collect();
+
+    (0..1_u32 << all.len()).map(move |bits| {
+        all.iter()
+            .enumerate()
+            .filter(|(i, _)| bits & 1 << i != 0)
+            .map(|(_, &effect)| effect)
+            .collect()
+    })
+}
+
+/// A few effect sets which hit all the fast paths, and shared clear codes
+fn some_effects() -> [EffectFlags; 5] {
+    [
+        EffectFlags::new(),
+        EffectFlags::new().with(Effect::Italic),
+        EffectFlags::new().with(Effect::Underline),
+        EffectFlags::new().with(Effect::Bold).with(Effect::Dimmed),
+        EffectFlags::all(),
+    ]
+}
+
+#[test]
+fn test_reference_interpreter() {
+    for code in 0..=255 {
+        assert_eq!(XtermColor::from_code(code) as u8, code);
+    }
+
+    let term = Term::default().run("\x1b[1;31;48;5;7;58;2;1;2;3m\x1b[22m");
+    assert_eq!(term.fg, Some(TermColor::Indexed(1)));
+    assert_eq!(term.bg, Some(TermColor::Indexed(7)));
+    assert_eq!(term.ul, Some(TermColor::Rgb(1, 2, 3)));
+    assert!(term.effects.is_plain());
+    assert_eq!(Term::default().run("\x1b[4;0m"), Term::default());
+}
+
+#[test]
+#[should_panic = "invalid parameter"]
+fn test_reference_interpreter_rejects_empty_params() {
+    Term::default().run("\x1b[1;;2m");
+}
+
+#[test]
+fn test_every_effect_subset() {
+    for effects in effect_subsets() {
+        check_const(Style::new().effect_flags(effects));
+        check_const(Style::new().fg(ansi::Red).effect_flags(effects));
+        check_const(Style::new().bg(xterm::Aqua).effect_flags(effects));
+        check_const(
+            Style::new()
+                .fg(css::Orange)
+                .bg(Rgb::<1, 2, 3>)
+                .underline_color(ansi::Blue)
+                .effect_flags(effects),
+        );
+
+        let runtime = Style::new()
+            .fg(Color::Rgb(RgbColor {
+                red: 255,
+                green: 0,
+                blue: 128,
+            }))
+            .bg(AnsiColor::BrightWhite)
+            .effect_flags(effects);
+        check(runtime, runtime.into_runtime_style());
+
+        let runtime = Style::new()
+            .fg(Some(Color::Ansi(AnsiColor::Green)))
+            .bg(None::<Color>)
+            .underline_color(Some(Color::Xterm(XtermColor::from_code(100))))
+            .effect_flags(effects);
+        check(runtime, runtime.into_runtime_style());
+    }
+}
+
+/// Every color in the list in every slot, run with `$check!(style)`
+macro_rules! every_slot {
+    ($check:ident [$($color:tt)*]) => {
+        every_slot!(@fg $check [$($color)*] [$($color)*]);
+    };
+    (@fg $check:ident [$($fg:tt)*] $colors:tt) => {$(
+        every_slot!(@bg $check $fg $colors $colors);
+    )*};
+    (@bg $check:ident $fg:tt [$($bg:tt)*] $colors:tt) => {$(
+        every_slot!(@ul $check $fg $bg $colors);
+    )*};
+    (@ul $check:ident $fg:tt $bg:tt [$($ul:tt)*]) => {$(
+        for effects in some_effects() {
+            $check!(Style::new().fg $fg .bg $bg .underline_color $ul .effect_flags(effects));
+        }
+    )*};
+}
+
+macro_rules! check_const {
+    ($style:expr) => {
+        check_const($style)
+    };
+}
+
+macro_rules! check_runtime {
+    ($style:expr) => {{
+        let style = $style;
+        check(style, style.into_runtime_style())
+    }};
+}
+
+#[test]
+fn test_every_const_color() {
+    every_slot!(
+        check_const[(NoColor)(ansi::Red)(ansi::BrightCyan)(ansi::Default)(xterm::Aqua)(
+            css::Orange
+        )(Rgb::<0, 100, 255>)]
+    );
+}
+
+#[test]
+fn test_every_runtime_color() {
+    every_slot!(
+        check_runtime[(NoColor)(AnsiColor::Magenta)(AnsiColor::Default)(XtermColor::from_code(
+            232
+        ))(CssColor::RebeccaPurple)(RgbColor {
+            red: 9,
+            green: 99,
+            blue: 255
+        })(Color::Rgb(RgbColor {
+            red: 0,
+            green: 10,
+            blue: 200
+        }))(Color::Css(CssColor::Tomato))(Some(Color::Ansi(
+            AnsiColor::BrightBlack
+        )))(Some(Color::Xterm(XtermColor::from_code(16))))(None::<Color>)]
+    );
+}
+
+#[test]
+fn test_const_and_runtime_colors() {
+    every_slot!(
+        check_runtime[(NoColor)(ansi::Yellow)(css::Teal)(Color::Ansi(AnsiColor::Default))(Some(
+            Color::Rgb(RgbColor {
+                red: 1,
+                green: 2,
+                blue: 3
+            })
+        ))(None::<Color>)]
+    );
+}
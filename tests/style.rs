@@ -72,3 +72,249 @@ fn test_rgb_to_runtime() {
 
     assert_eq!(format!("{}", style.apply()), "\x1b[48;2;255;128;0m");
 }
+
+#[test]
+fn test_rgb_foreground_with_effects() {
+    let style = Style::new()
+        .fg(colorz::Color::Rgb(colorz::rgb::RgbColor {
+            red: 255,
+            green: 128,
+            blue: 0,
+        }))
+        .bold()
+        .italics();
+
+    assert_eq!(format!("{}", style.apply()), "\x1b[38;2;255;128;0;1;3m");
+}
+
+#[test]
+fn test_const_escapes() {
+    use colorz::{ansi, rgb::Rgb, EffectFlags, NoColor};
+
+    const PLAIN: Style<NoColor, NoColor, NoColor> = Style::new();
+    assert_eq!(PLAIN.apply_escape().as_str(), "");
+    assert_eq!(PLAIN.clear_escape().as_str(), "");
+
+    // the underline color is used even without an underline, so nested underlines get it
+    const NO_UNDERLINE: Style<NoColor, NoColor, ansi::Red> =
+        Style::new().underline_color(ansi::Red);
+    assert_eq!(NO_UNDERLINE.apply_escape().as_str(), "\x1b[58;5;1m");
+    assert_eq!(NO_UNDERLINE.clear_escape().as_str(), "\x1b[59m");
+
+    // the longest possible escape
+    const ALL: Style<Rgb<255, 255, 255>, Rgb<255, 255, 255>, Rgb<255, 255, 255>> = Style::new()
+        .fg(Rgb::<255, 255, 255>)
+        .bg(Rgb::<255, 255, 255>)
+        .underline_color(Rgb::<255, 255, 255>)
+        .effect_flags(EffectFlags::all());
+
+    assert_eq!(
+        ALL.apply_escape().as_str(),
+        "\x1b[38;2;255;255;255;48;2;255;255;255;1;2;3;4;21;5;6;7;8;9;53;73;74;58;2;255;255;255m"
+    );
+    assert_eq!(
+        ALL.clear_escape().as_str(),
+        "\x1b[39;49;22;23;24;25;27;28;29;55;75;59m"
+    );
+    assert_eq!(ALL.clear_escape().to_string(), ALL.clear_escape().as_str());
+}
+
+#[test]
+fn test_runtime_single_sequence() {
+    let style = Style::new()
+        .fg(Some(colorz::Color::Ansi(colorz::ansi::AnsiColor::Red)))
+        .bg(None::<colorz::Color>)
+        .underline_color(Some(colorz::rgb::RgbColor {
+            red: 1,
+            green: 2,
+            blue: 3,
+        }))
+        .bold()
+        .dimmed()
+        .underline();
+
+    assert_eq!(format!("{}", style.apply()), "\x1b[31;1;2;4;58;2;1;2;3m");
+    assert_eq!(format!("{}", style.clear()), "\x1b[39;22;24;59m");
+
+    let style = Style::new().bg(Some(colorz::xterm::Aqua));
+
+    assert_eq!(format!("{}", style.apply()), "\x1b[48;5;51m");
+    assert_eq!(format!("{}", style.clear()), "\x1b[49m");
+    assert_eq!(
+        format!("{}", Style::new().fg(None::<colorz::Color>).apply()),
+        ""
+    );
+}
+
+#[test]
+fn test_underline_color_combinations() {
+    use colorz::{ansi, rgb::RgbColor, xterm, Color, NoColor};
+
+    const RGB: RgbColor = RgbColor {
+        red: 1,
+        green: 2,
+        blue: 3,
+    };
+
+    // every kind of every color, with a single sequence for each style
+    macro_rules! check {
+        ($($fg:expr, $bg:expr, $ul:expr;)*) => {$(
+            for effects in [&[][..], &[Effect::Bold], &[Effect::Underline], &[Effect::Bold, Effect::Dimmed, Effect::DoubleUnderline]] {
+                let style = Style::new().fg($fg).bg($bg).underline_color($ul).effects(effects);
+                let runtime = style.into_runtime_style();
+
+                let apply = format!("{}", runtime.apply());
+                let clear = format!("{}", runtime.clear());
+
+                assert_eq!(apply, runtime.apply_escape().as_str(), "{runtime:?}");
+                assert_eq!(clear, runtime.clear_escape().as_str(), "{runtime:?}");
+                assert_eq!(apply.is_empty(), runtime.is_plain());
+                assert_eq!(style.is_plain(), runtime.is_plain());
+
+                // styles with an underline color are always written as a single sequence
+                if runtime.underline_color.is_some() {
+                    assert_eq!(format!("{}", style.apply()), apply);
+                    assert_eq!(format!("{}", style.clear()), clear);
+                    assert!(apply.contains("58;"), "{apply:?}");
+                    assert!(clear.ends_with("59m"), "{clear:?}");
+                } else {
+                    assert!(!apply.contains("58;"), "{apply:?}");
+                    assert!(!clear.contains("59"), "{clear:?}");
+                }
+            }
+        )*};
+    }
+
+    use colorz::Effect;
+
+    check! {
+        NoColor, NoColor, NoColor;
+        NoColor, NoColor, xterm::Aqua;
+        ansi::Red, NoColor, xterm::Aqua;
+        NoColor, ansi::Blue, RGB;
+        ansi::Red, RGB, ansi::Green;
+        ansi::Red, RGB, NoColor;
+        None::<Color>, None::<Color>, Some(Color::Rgb(RGB));
+        Some(Color::Rgb(RGB)), NoColor, Some(Color::Ansi(ansi::AnsiColor::Red));
+        NoColor, Some(Color::Rgb(RGB)), ansi::Green;
+        Some(Color::Rgb(RGB)), None::<Color>, None::<Color>;
+        ansi::Red, None::<Color>, Some(Color::Rgb(RGB));
+    }
+}
+
+#[test]
+fn test_merge_layers() {
+    use colorz::{ansi, css, Effect, EffectFlags};
+
+    let theme = Style::new().fg(ansi::White).bg(ansi::Black).bold();
+    let user = Style::new()
+        .fg(Some(colorz::Color::Css(css::CssColor::Orange)))
+        .underline_color(None::<colorz::Color>)
+        .underline();
+    let emphasis = Style::new().bg(ansi::Red).italics();
+
+    let style = theme.merge(user).merge(emphasis);
+    assert_eq!(
+        style,
+        Style::new()
+            .fg(css::Orange)
+            .bg(ansi::Red)
+            .effects_array([Effect::Bold, Effect::Underline, Effect::Italic])
+            .into_runtime_style()
+    );
+
+    // filling unset fields keeps the colors which are already set
+    assert_eq!(
+        emphasis.or(user).or(theme),
+        Style::new()
+            .fg(css::Orange)
+            .bg(ansi::Red)
+            .effects_array([Effect::Bold, Effect::Underline, Effect::Italic])
+            .into_runtime_style()
+    );
+    assert_eq!(
+        Style::new().merge(Style::new()),
+        Style::new().into_runtime_style()
+    );
+
+    // effects can be removed from a layer
+    let plain =
+        style.effect_flags(style.effects - Effect::Bold - (Effect::Underline | Effect::Blink));
+    assert_eq!(plain.effects, EffectFlags::from(Effect::Italic));
+
+    // a patch turns off effects from the layers below it, unless it sets them again
+    assert_eq!(
+        style.patch(Style::new().fg(ansi::Red), Effect::Bold | Effect::Italic),
+        Style::new()
+            .fg(ansi::Red)
+            .bg(ansi::Red)
+            .underline()
+            .into_runtime_style()
+    );
+    assert_eq!(
+        theme.patch(Style::new().bold().dimmed(), Effect::Bold),
+        theme.dimmed().into_runtime_style()
+    );
+    assert_eq!(
+        theme.patch(Style::new(), EffectFlags::new()),
+        theme.merge(Style::new())
+    );
+
+    const CONST: Style = Style::new()
+        .fg(ansi::White)
+        .bold()
+        .const_into_runtime_style()
+        .const_merge(Style::new().bg(ansi::Red).const_into_runtime_style());
+    const PATCHED: Style = CONST.const_patch(
+        Style::new().italics().const_into_runtime_style(),
+        EffectFlags::new().with(Effect::Bold),
+    );
+    assert_eq!(
+        PATCHED,
+        Style::new()
+            .fg(ansi::White)
+            .bg(ansi::Red)
+            .italics()
+            .into_runtime_style()
+    );
+    assert_eq!(
+        CONST,
+        Style::new()
+            .fg(ansi::White)
+            .bg(ansi::Red)
+            .bold()
+            .into_runtime_style()
+    );
+}
+
+#[test]
+fn test_effect_set_operations() {
+    use colorz::{Effect, EffectFlags};
+
+    let a = Effect::Bold | Effect::Italic | Effect::Underline;
+    let b = EffectFlags::from_array([Effect::Italic, Effect::Blink]);
+
+    assert_eq!(
+        a | b,
+        EffectFlags::from_array([
+            Effect::Bold,
+            Effect::Italic,
+            Effect::Underline,
+            Effect::Blink
+        ])
+    );
+    assert_eq!(a & b, EffectFlags::from(Effect::Italic));
+    assert_eq!(a - b, Effect::Bold | Effect::Underline);
+    assert_eq!(Effect::Bold & Effect::Italic, EffectFlags::new());
+    assert_eq!(Effect::Bold - a, EffectFlags::new());
+    assert_eq!(a.union(b), a | b);
+    assert_eq!(a.intersection(b), a & b);
+    assert_eq!(a.difference(b), a - b);
+
+    let mut flags = EffectFlags::new();
+    flags |= Effect::Bold;
+    flags |= b;
+    flags &= Effect::Bold | Effect::Blink;
+    flags -= Effect::Blink;
+    assert_eq!(flags, EffectFlags::from(Effect::Bold));
+}